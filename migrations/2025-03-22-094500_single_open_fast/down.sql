@@ -0,0 +1 @@
+DROP INDEX IF EXISTS fasting_events_one_open_per_user;
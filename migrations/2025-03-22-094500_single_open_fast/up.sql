@@ -0,0 +1,22 @@
+-- Close every open fast that has a later fast for the same user, at that later fast's start.
+UPDATE fasting_events
+SET stop_time = (
+    SELECT MIN(later.start_time)
+    FROM fasting_events AS later
+    WHERE later.user_id = fasting_events.user_id
+      AND (later.start_time > fasting_events.start_time
+           OR (later.start_time = fasting_events.start_time AND later.id > fasting_events.id))
+)
+WHERE stop_time IS NULL
+  AND EXISTS (
+    SELECT 1
+    FROM fasting_events AS later
+    WHERE later.user_id = fasting_events.user_id
+      AND later.stop_time IS NULL
+      AND (later.start_time > fasting_events.start_time
+           OR (later.start_time = fasting_events.start_time AND later.id > fasting_events.id))
+);
+
+CREATE UNIQUE INDEX fasting_events_one_open_per_user
+    ON fasting_events (user_id)
+    WHERE stop_time IS NULL;
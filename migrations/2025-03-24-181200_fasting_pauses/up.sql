@@ -0,0 +1,12 @@
+CREATE TABLE fasting_pauses (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    event_id INTEGER NOT NULL,
+    pause_time TIMESTAMP NOT NULL,
+    resume_time TIMESTAMP NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (event_id) REFERENCES fasting_events(id) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX fasting_pauses_one_open_per_event
+    ON fasting_pauses (event_id)
+    WHERE resume_time IS NULL;
@@ -0,0 +1,20 @@
+-- SQLite cannot drop a column used in a foreign key, so rebuild fasting_events without it.
+CREATE TABLE fasting_events_old (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    user_id INTEGER NOT NULL,
+    start_time TIMESTAMP NOT NULL,
+    stop_time TIMESTAMP NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    goal_id INTEGER NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (goal_id) REFERENCES fasting_goals(id) ON DELETE SET NULL
+);
+INSERT INTO fasting_events_old (id, user_id, start_time, stop_time, created_at, goal_id)
+    SELECT id, user_id, start_time, stop_time, created_at, goal_id FROM fasting_events;
+DROP TABLE fasting_events;
+ALTER TABLE fasting_events_old RENAME TO fasting_events;
+CREATE UNIQUE INDEX fasting_events_one_open_per_user
+    ON fasting_events (user_id)
+    WHERE stop_time IS NULL;
+
+DROP TABLE IF EXISTS fasting_protocols;
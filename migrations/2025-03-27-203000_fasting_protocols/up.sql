@@ -0,0 +1,21 @@
+CREATE TABLE fasting_protocols (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    user_id INTEGER NULL, -- NULL for built-in protocols
+    name TEXT NOT NULL,
+    fasting_hours INTEGER NOT NULL,
+    eating_hours INTEGER NOT NULL,
+    description TEXT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+INSERT INTO fasting_protocols (user_id, name, fasting_hours, eating_hours, description) VALUES
+    (NULL, '16:8', 16, 8, 'Fast for 16 hours and eat within an 8 hour window.'),
+    (NULL, '18:6', 18, 6, 'Fast for 18 hours and eat within a 6 hour window.'),
+    (NULL, 'OMAD', 23, 1, 'One meal a day: a single 1 hour eating window.'),
+    (NULL, '5:2', 24, 60, 'Two non-consecutive 24 hour low-calorie days per week.'),
+    (NULL, 'ADF', 36, 12, 'Alternate-day fasting: a 36 hour fast followed by a 12 hour eating window.'),
+    (NULL, 'Extended', 72, 24, 'A multi-day fast of 72 hours.');
+
+ALTER TABLE fasting_events
+    ADD COLUMN protocol_id INTEGER NULL REFERENCES fasting_protocols(id) ON DELETE SET NULL;
@@ -0,0 +1,11 @@
+CREATE TABLE fasting_schedules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    user_id INTEGER NOT NULL,
+    weekday INTEGER NOT NULL CHECK (weekday BETWEEN 0 AND 6), -- 0 = Monday
+    start_time TIME NOT NULL,
+    end_time TIME NOT NULL, -- falls on the next day when not after start_time
+    goal_id INTEGER NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (goal_id) REFERENCES fasting_goals(id) ON DELETE SET NULL
+);
@@ -0,0 +1,12 @@
+CREATE TABLE fast_journal_entries (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    event_id INTEGER NOT NULL,
+    entry_time TIMESTAMP NOT NULL,
+    note TEXT NULL,
+    mood INTEGER NULL CHECK (mood BETWEEN 1 AND 10),
+    hunger INTEGER NULL CHECK (hunger BETWEEN 1 AND 10),
+    energy INTEGER NULL CHECK (energy BETWEEN 1 AND 10),
+    symptoms TEXT NULL, -- comma-separated tags, e.g. "headache,dizziness"
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (event_id) REFERENCES fasting_events(id) ON DELETE CASCADE
+);
@@ -0,0 +1,3 @@
+ALTER TABLE users DROP COLUMN length_unit;
+ALTER TABLE users DROP COLUMN weight_unit;
+DROP TABLE IF EXISTS body_measurements;
@@ -0,0 +1,13 @@
+CREATE TABLE body_measurements (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    user_id INTEGER NOT NULL,
+    kind TEXT NOT NULL CHECK (kind IN ('weight', 'body_fat', 'waist')),
+    value DOUBLE NOT NULL,
+    unit TEXT NOT NULL, -- kg/lb for weight, % for body fat, cm/in for waist
+    measured_at TIMESTAMP NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+ALTER TABLE users ADD COLUMN weight_unit TEXT NOT NULL DEFAULT 'kg';
+ALTER TABLE users ADD COLUMN length_unit TEXT NOT NULL DEFAULT 'cm';
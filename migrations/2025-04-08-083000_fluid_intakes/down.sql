@@ -0,0 +1,2 @@
+ALTER TABLE users DROP COLUMN daily_hydration_target_ml;
+DROP TABLE IF EXISTS fluid_intakes;
@@ -0,0 +1,13 @@
+CREATE TABLE fluid_intakes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    user_id INTEGER NOT NULL,
+    event_id INTEGER NULL, -- the fast that was active when the intake was logged
+    kind TEXT NOT NULL CHECK (kind IN ('water', 'electrolytes', 'black_coffee', 'tea')),
+    amount_ml INTEGER NOT NULL CHECK (amount_ml > 0),
+    consumed_at TIMESTAMP NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (event_id) REFERENCES fasting_events(id) ON DELETE SET NULL
+);
+
+ALTER TABLE users ADD COLUMN daily_hydration_target_ml INTEGER NULL;
@@ -0,0 +1,14 @@
+CREATE TABLE break_fast_meals (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    event_id INTEGER NOT NULL UNIQUE, -- one break-fast meal per fast
+    description TEXT NOT NULL,
+    calories_min INTEGER NULL CHECK (calories_min >= 0),
+    calories_max INTEGER NULL CHECK (calories_max >= 0),
+    protein_g DOUBLE NULL CHECK (protein_g >= 0),
+    carbs_g DOUBLE NULL CHECK (carbs_g >= 0),
+    fat_g DOUBLE NULL CHECK (fat_g >= 0),
+    eaten_at TIMESTAMP NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (event_id) REFERENCES fasting_events(id) ON DELETE CASCADE,
+    CHECK (calories_min IS NULL OR calories_max IS NULL OR calories_min <= calories_max)
+);
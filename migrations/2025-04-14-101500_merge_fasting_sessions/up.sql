@@ -0,0 +1,14 @@
+-- fasting_sessions was never written by the app; fasting_events is the single source of fasts.
+-- Copy any rows that were added by hand, skipping ones that would overlap an existing fast.
+INSERT OR IGNORE INTO fasting_events (user_id, start_time, stop_time, created_at)
+SELECT s.user_id, s.start_time, s.stop_time, s.created_at
+FROM fasting_sessions s
+WHERE (s.stop_time IS NULL OR s.stop_time > s.start_time)
+  AND NOT EXISTS (
+    SELECT 1 FROM fasting_events e
+    WHERE e.user_id = s.user_id
+      AND (e.stop_time IS NULL OR e.stop_time > s.start_time)
+      AND (s.stop_time IS NULL OR s.stop_time > e.start_time)
+  );
+
+DROP TABLE fasting_sessions;
@@ -0,0 +1,2 @@
+ALTER TABLE fasting_goals DROP COLUMN achieved_at;
+ALTER TABLE fasting_goals DROP COLUMN status;
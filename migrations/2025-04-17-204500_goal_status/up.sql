@@ -0,0 +1,3 @@
+ALTER TABLE fasting_goals ADD COLUMN status TEXT NOT NULL DEFAULT 'pending'
+    CHECK (status IN ('pending', 'in_progress', 'achieved', 'missed'));
+ALTER TABLE fasting_goals ADD COLUMN achieved_at TIMESTAMP NULL;
@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS fasting_goals_recurring_period;
+ALTER TABLE fasting_goals DROP COLUMN period_start;
+ALTER TABLE fasting_goals DROP COLUMN recurring_goal_id;
+ALTER TABLE fasting_goals DROP COLUMN archived_at;
+DROP TABLE IF EXISTS recurring_goals;
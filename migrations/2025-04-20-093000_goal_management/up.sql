@@ -0,0 +1,20 @@
+CREATE TABLE recurring_goals (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    user_id INTEGER NOT NULL,
+    goal_duration INTEGER NOT NULL CHECK (goal_duration > 0),
+    period TEXT NOT NULL CHECK (period IN ('daily', 'weekly')),
+    weekdays INTEGER NOT NULL DEFAULT 127, -- bit 0 = Monday; only used by daily goals
+    starts_on DATE NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+ALTER TABLE fasting_goals ADD COLUMN archived_at TIMESTAMP NULL;
+ALTER TABLE fasting_goals ADD COLUMN recurring_goal_id INTEGER NULL
+    REFERENCES recurring_goals(id) ON DELETE SET NULL;
+ALTER TABLE fasting_goals ADD COLUMN period_start DATE NULL;
+
+-- One generated goal per recurring goal and period
+CREATE UNIQUE INDEX fasting_goals_recurring_period
+    ON fasting_goals (recurring_goal_id, period_start)
+    WHERE recurring_goal_id IS NOT NULL;
@@ -0,0 +1,6 @@
+DROP INDEX IF EXISTS fasting_goals_enrollment_day;
+ALTER TABLE fasting_goals DROP COLUMN enrollment_id;
+DROP INDEX IF EXISTS program_enrollments_one_active;
+DROP TABLE IF EXISTS program_enrollments;
+DROP TABLE IF EXISTS program_days;
+DROP TABLE IF EXISTS fasting_programs;
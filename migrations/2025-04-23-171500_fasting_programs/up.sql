@@ -0,0 +1,43 @@
+CREATE TABLE fasting_programs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    user_id INTEGER NULL, -- NULL for built-in programs
+    slug TEXT NULL UNIQUE, -- Identifies built-in programs shipped in programs/*.json
+    name TEXT NOT NULL,
+    description TEXT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE TABLE program_days (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    program_id INTEGER NOT NULL,
+    day_number INTEGER NOT NULL CHECK (day_number >= 1),
+    target_hours INTEGER NOT NULL CHECK (target_hours >= 0), -- 0 marks a rest day
+    FOREIGN KEY (program_id) REFERENCES fasting_programs(id) ON DELETE CASCADE,
+    UNIQUE (program_id, day_number)
+);
+
+CREATE TABLE program_enrollments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    user_id INTEGER NOT NULL,
+    program_id INTEGER NOT NULL,
+    start_date DATE NOT NULL,
+    status TEXT NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'completed', 'dropped')),
+    ended_at TIMESTAMP NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (program_id) REFERENCES fasting_programs(id) ON DELETE CASCADE
+);
+
+-- A user follows at most one program at a time
+CREATE UNIQUE INDEX program_enrollments_one_active
+    ON program_enrollments (user_id)
+    WHERE status = 'active';
+
+ALTER TABLE fasting_goals ADD COLUMN enrollment_id INTEGER NULL
+    REFERENCES program_enrollments(id) ON DELETE SET NULL;
+
+-- One generated goal per enrollment and program day
+CREATE UNIQUE INDEX fasting_goals_enrollment_day
+    ON fasting_goals (enrollment_id, period_start)
+    WHERE enrollment_id IS NOT NULL;
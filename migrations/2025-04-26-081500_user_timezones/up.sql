@@ -0,0 +1,3 @@
+-- IANA timezone name used to display times and find local day boundaries.
+-- All TIMESTAMP columns hold UTC instants; only presentation is local.
+ALTER TABLE users ADD COLUMN timezone TEXT NOT NULL DEFAULT 'UTC';
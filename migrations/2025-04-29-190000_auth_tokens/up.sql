@@ -0,0 +1,16 @@
+-- One row per signed-in session; the refresh token is replaced on every use
+CREATE TABLE auth_tokens (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    user_id INTEGER NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE, -- SHA-256 of the current refresh token
+    previous_token_hash TEXT NULL, -- The token it replaced; presenting it again revokes the session
+    device_name TEXT NULL,
+    created_at TIMESTAMP NOT NULL,
+    last_used_at TIMESTAMP NOT NULL,
+    expires_at TIMESTAMP NOT NULL,
+    revoked_at TIMESTAMP NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX auth_tokens_user ON auth_tokens (user_id);
+CREATE INDEX auth_tokens_previous_hash ON auth_tokens (previous_token_hash);
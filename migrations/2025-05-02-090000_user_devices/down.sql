@@ -0,0 +1,4 @@
+ALTER TABLE users ADD COLUMN device_id TEXT NULL;
+ALTER TABLE auth_tokens DROP COLUMN device_id;
+DROP TABLE device_pairing_codes;
+DROP TABLE user_devices;
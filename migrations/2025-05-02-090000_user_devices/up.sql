@@ -0,0 +1,33 @@
+-- Devices signed in with a credential obtained by redeeming a pairing code
+CREATE TABLE user_devices (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    user_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    credential_hash TEXT NOT NULL UNIQUE, -- SHA-256 of the device credential
+    paired_at TIMESTAMP NOT NULL,
+    last_seen_at TIMESTAMP NULL,
+    revoked_at TIMESTAMP NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX user_devices_user ON user_devices (user_id);
+
+-- Short-lived, single-use codes shown on a signed-in device to pair a new one
+CREATE TABLE device_pairing_codes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    user_id INTEGER NOT NULL,
+    code_hash TEXT NOT NULL UNIQUE, -- SHA-256 of the normalized code
+    created_at TIMESTAMP NOT NULL,
+    expires_at TIMESTAMP NOT NULL,
+    redeemed_at TIMESTAMP NULL,
+    device_id INTEGER NULL, -- The device paired with the code
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (device_id) REFERENCES user_devices(id) ON DELETE SET NULL
+);
+
+-- Sessions started with a device credential end when the device is revoked
+ALTER TABLE auth_tokens ADD COLUMN device_id INTEGER NULL
+    REFERENCES user_devices(id) ON DELETE SET NULL;
+
+-- Device IDs were stored in plaintext and accepted without proof; linked devices must pair again
+ALTER TABLE users DROP COLUMN device_id;
@@ -0,0 +1,2 @@
+DROP TABLE lockout_audit_log;
+DROP TABLE login_failures;
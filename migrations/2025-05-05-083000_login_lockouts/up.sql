@@ -0,0 +1,25 @@
+-- Failed sign-in attempts since the last success, per username and per device name
+CREATE TABLE login_failures (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    scope TEXT NOT NULL CHECK (scope IN ('username', 'device')),
+    subject TEXT NOT NULL,
+    failed_attempts INTEGER NOT NULL DEFAULT 0, -- Failures since the last lockout ended
+    lockouts INTEGER NOT NULL DEFAULT 0, -- Lockouts so far; each one lasts twice as long as the last
+    last_failed_at TIMESTAMP NOT NULL,
+    locked_until TIMESTAMP NULL,
+    UNIQUE (scope, subject)
+);
+
+-- Every lockout and every admin unlock
+CREATE TABLE lockout_audit_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    scope TEXT NOT NULL CHECK (scope IN ('username', 'device')),
+    subject TEXT NOT NULL,
+    event TEXT NOT NULL CHECK (event IN ('locked', 'unlocked')),
+    failed_attempts INTEGER NOT NULL,
+    locked_until TIMESTAMP NULL,
+    actor TEXT NULL, -- Who unlocked it; NULL for automatic lockouts
+    created_at TIMESTAMP NOT NULL
+);
+
+CREATE INDEX lockout_audit_log_subject ON lockout_audit_log (scope, subject);
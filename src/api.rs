@@ -0,0 +1,1019 @@
+use crate::auth::{
+    authenticate, list_active_sessions, refresh_tokens, revoke_all_sessions, revoke_session, sign_in,
+    sign_in_with_device, AuthConfig, SessionInfo, TokenPair,
+};
+use crate::clock::Clock;
+use crate::db::DbPool;
+use crate::errors::FastingAppError;
+use crate::handlers::analytics::{
+    aggregate_fasting_by_period, calculate_average_fasting_duration, calculate_fasting_time_between,
+    calculate_streaks, calculate_total_fasting_time, calculate_weight_change, find_large_refeeds,
+    get_fasting_history, journal_scores_by_fasting_hour, summarize_break_fast_patterns,
+    summarize_fasts_by_protocol, BreakFastPattern, BucketSize, HourlyJournalStats, LargeRefeedWarning,
+    PeriodStats, ProtocolSummary, StreakReport, WeightChangeReport, DEFAULT_STREAK_THRESHOLD_MINUTES,
+};
+use crate::handlers::fasting::{get_current_fasting_status, start_fasting, stop_fasting, FastingStatus};
+use crate::handlers::goals::{
+    create_goal, delete_goal, evaluate_goals, find_user_goal, list_goals, set_goal_archived, update_goal,
+    GoalEvaluation,
+};
+use crate::handlers::meals::BreakFastMealInput;
+use crate::handlers::timezone::get_user_timezone;
+use crate::models::{FastingEvent, FastingGoal, User};
+use crate::users::create::create_user;
+use crate::users::password::{change_password, PasswordViolation};
+use crate::users::devices::{
+    create_pairing_code, list_devices, redeem_pairing_code, revoke_device, DeviceCredential, DeviceInfo, PairingCode,
+};
+use chrono::{NaiveDate, NaiveDateTime};
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use diesel::SqliteConnection;
+use rocket::http::{Header, Status};
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket::response::{self, Responder, Response};
+use rocket::serde::json::Json;
+use rocket::{catch, catchers, delete, get, post, put, routes, Build, Rocket, State};
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+/// State shared by every request: the connection pool, the token settings and the clock
+/// handlers read "now" from.
+pub struct ApiState {
+    pool: DbPool,
+    auth: Arc<AuthConfig>,
+    clock: Arc<dyn Clock + Send + Sync>,
+}
+
+impl ApiState {
+    /// ✅ Creates the API state from a connection pool, token settings and a clock.
+    pub fn new(pool: DbPool, auth: AuthConfig, clock: Arc<dyn Clock + Send + Sync>) -> Self {
+        ApiState { pool, auth: Arc::new(auth), clock }
+    }
+
+    /// Runs blocking handler code on a pooled connection, off the async executor.
+    async fn run<T, F>(&self, work: F) -> Result<T, FastingAppError>
+    where
+        F: FnOnce(&mut SqliteConnection, &dyn Clock) -> Result<T, FastingAppError> + Send + 'static,
+        T: Send + 'static,
+    {
+        let pool = self.pool.clone();
+        let clock = Arc::clone(&self.clock);
+
+        rocket::tokio::task::spawn_blocking(move || {
+            let mut conn = pool
+                .get()
+                .map_err(|err| FastingAppError::ConnectionError(format!("No pooled connection: {}", err)))?;
+            work(&mut conn, clock.as_ref())
+        })
+        .await
+        .map_err(|err| FastingAppError::Custom(format!("Request worker failed: {}", err)))?
+    }
+}
+
+/// ✅ Builds the Rocket instance with every API route mounted under `/api`.
+///
+/// - Every route except register, login and refresh needs an `Authorization: Bearer` access token.
+/// - Times in requests and responses are UTC, formatted like `2025-03-01T18:30:00`.
+/// - Errors are returned as `{"error": ..., "message": ...}` with a matching status code.
+/// - `X-Real-IP` is ignored; clients are identified by the address they connect from.
+pub fn build_rocket(state: ApiState) -> Rocket<Build> {
+    // A forwarded-address header is set by the client, so trusting it would let anyone dodge the login lockout
+    rocket::custom(rocket::Config::figment().merge(("ip_header", false)))
+        .manage(state)
+        .mount(
+            "/api",
+            routes![
+                register,
+                login,
+                refresh,
+                logout,
+                get_sessions,
+                delete_session,
+                delete_all_sessions,
+                put_password,
+                post_pairing_code,
+                pair_device,
+                device_login,
+                get_devices,
+                delete_device,
+                start_fast,
+                stop_fast,
+                fast_status,
+                fast_history,
+                get_goals,
+                post_goal,
+                get_goal,
+                put_goal,
+                delete_goal_route,
+                put_goal_archived,
+                get_goal_evaluations,
+                get_fasting_summary,
+                get_fasting_time,
+                get_protocol_summaries,
+                get_journal_scores,
+                get_break_fast_patterns,
+                get_large_refeeds,
+                get_streaks,
+                get_periods,
+                get_weight_change,
+            ],
+        )
+        .register("/", catchers![default_catcher])
+}
+
+/// ✅ The HTTP status a handler error is reported with.
+pub fn error_status(error: &FastingAppError) -> Status {
+    use FastingAppError::*;
+
+    match error {
+        DatabaseError(DieselError::NotFound) => Status::NotFound,
+        DatabaseError(DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => Status::Conflict,
+        DatabaseError(_) | PasswordHashError(_) | Custom(_) | FileError(_) | SerializationError(_) => {
+            Status::InternalServerError
+        }
+        ConnectionError(_) => Status::ServiceUnavailable,
+        AccountLocked { .. } => Status::TooManyRequests,
+        InvalidCredentials(_) | InvalidToken(_) => Status::Unauthorized,
+        GoalAccessDenied(_) => Status::Forbidden,
+        ExistingSessionError(_) | SessionError(_) | OverlappingFast(_) => Status::Conflict,
+        EventNotFound(_) | ProtocolNotFound(_) | ScheduleNotFound(_) | MeasurementNotFound(_)
+        | GoalNotFound(_) | RecurringGoalNotFound(_) | ProgramNotFound(_) | EnrollmentNotFound(_)
+        | AuthSessionNotFound(_) | DeviceNotFound(_) => {
+            Status::NotFound
+        }
+        InvalidRequest(_) | InvalidTimeRange { .. } | FutureTimestamp(_) | GoalDeadlinePassed { .. }
+        | InvalidTimezone(_) | NonexistentLocalTime(..) | InvalidPairingCode | WeakPassword(_) => {
+            Status::UnprocessableEntity
+        }
+    }
+}
+
+/// JSON body of every error response.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorBody {
+    /// The status reason, e.g. "Not Found".
+    pub error: String,
+    pub message: String,
+    /// Every password rule a rejected password broke.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub violations: Vec<PasswordViolation>,
+}
+
+/// An error response: a status code with a JSON body.
+#[derive(Debug)]
+pub struct ApiError {
+    status: Status,
+    message: String,
+    /// Sent as `Retry-After` when the client is locked out.
+    retry_after: Option<NaiveDateTime>,
+    violations: Vec<PasswordViolation>,
+}
+
+impl ApiError {
+    fn new(status: Status, message: impl Into<String>) -> Self {
+        ApiError { status, message: message.into(), retry_after: None, violations: Vec::new() }
+    }
+}
+
+impl From<FastingAppError> for ApiError {
+    fn from(error: FastingAppError) -> Self {
+        let status = error_status(&error);
+        if status.class().is_server_error() {
+            log::error!("API request failed: {:?}", error);
+        }
+        let mut response = ApiError::new(status, error.user_friendly_message());
+        match error {
+            FastingAppError::AccountLocked { retry_after } => response.retry_after = Some(retry_after),
+            FastingAppError::WeakPassword(violations) => response.violations = violations,
+            _ => {}
+        }
+        response
+    }
+}
+
+impl<'r> Responder<'r, 'static> for ApiError {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        let body = ErrorBody {
+            error: self.status.reason_lossy().to_string(),
+            message: self.message,
+            violations: self.violations,
+        };
+        let mut response = Response::build_from(Json(body).respond_to(request)?);
+        response.status(self.status);
+        if self.status == Status::Unauthorized {
+            response.header(Header::new("WWW-Authenticate", "Bearer realm=\"fasting\""));
+        }
+        if let Some(retry_after) = self.retry_after {
+            let http_date = retry_after.and_utc().format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+            response.header(Header::new("Retry-After", http_date));
+        }
+        response.ok()
+    }
+}
+
+/// Turns failed guards, unknown routes and malformed bodies into JSON errors.
+#[catch(default)]
+fn default_catcher(status: Status, _request: &Request<'_>) -> ApiError {
+    let message = match status.code {
+        401 => "Sign in and send your access token as a Bearer token.",
+        404 => "There is nothing at this address.",
+        400 | 422 => "The request body is not valid.",
+        _ => "The request could not be completed.",
+    };
+    ApiError::new(status, message)
+}
+
+type ApiResult<T> = Result<T, ApiError>;
+
+/// The user and session whose access token came with the request.
+pub struct AuthUser {
+    pub user: User,
+    pub session_id: i32,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AuthUser {
+    type Error = FastingAppError;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let Some(token) = request
+            .headers()
+            .get_one("Authorization")
+            .and_then(|header| header.strip_prefix("Bearer "))
+            .map(|token| token.trim().to_string())
+        else {
+            return Outcome::Error((
+                Status::Unauthorized,
+                FastingAppError::InvalidToken("Missing Bearer token.".to_string()),
+            ));
+        };
+        let Some(state) = request.rocket().state::<ApiState>() else {
+            return Outcome::Error((
+                Status::InternalServerError,
+                FastingAppError::Custom("API state is not managed.".to_string()),
+            ));
+        };
+
+        let config = Arc::clone(&state.auth);
+        match state.run(move |conn, clock| authenticate(conn, &config, &token, clock)).await {
+            Ok((user, claims)) => Outcome::Success(AuthUser { user, session_id: claims.sid }),
+            Err(err) => Outcome::Error((error_status(&err), err)),
+        }
+    }
+}
+
+/// Username and password, for registering.
+#[derive(Debug, Deserialize)]
+pub struct Credentials {
+    pub username: String,
+    pub password: String,
+}
+
+/// The current password, to confirm it is the user, and the new one.
+#[derive(Debug, Deserialize)]
+pub struct ChangePasswordRequest {
+    pub current_password: String,
+    pub new_password: String,
+}
+
+/// Username and password, plus a name for the device signing in.
+#[derive(Debug, Deserialize)]
+pub struct LoginRequest {
+    pub username: String,
+    pub password: String,
+    pub device_name: Option<String>,
+}
+
+/// The signed-in user and their new session's tokens.
+#[derive(Debug, Serialize)]
+pub struct LoginResponse {
+    pub user: UserProfile,
+    pub tokens: TokenPair,
+}
+
+/// A refresh token to exchange for new tokens.
+#[derive(Debug, Deserialize)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
+/// How many sessions were signed out.
+#[derive(Debug, Serialize)]
+pub struct RevokedSessions {
+    pub revoked: usize,
+}
+
+/// A pairing code from a signed-in device and a name for the new device.
+#[derive(Debug, Deserialize)]
+pub struct PairDeviceRequest {
+    pub code: String,
+    pub device_name: String,
+}
+
+/// A paired device's credential.
+#[derive(Debug, Deserialize)]
+pub struct DeviceLoginRequest {
+    pub credential: String,
+}
+
+/// A user's public profile.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UserProfile {
+    pub id: i32,
+    pub username: String,
+    pub created_at: Option<NaiveDateTime>,
+    pub timezone: String,
+    pub weight_unit: String,
+    pub length_unit: String,
+}
+
+impl From<User> for UserProfile {
+    fn from(user: User) -> Self {
+        UserProfile {
+            id: user.id,
+            username: user.username,
+            created_at: user.created_at,
+            timezone: user.timezone,
+            weight_unit: user.weight_unit,
+            length_unit: user.length_unit,
+        }
+    }
+}
+
+/// Creates an account; the username must not be taken.
+#[post("/register", data = "<credentials>")]
+async fn register(state: &State<ApiState>, credentials: Json<Credentials>) -> ApiResult<(Status, Json<UserProfile>)> {
+    let Credentials { username, password } = credentials.into_inner();
+    if username.trim().is_empty() {
+        return Err(ApiError::new(Status::UnprocessableEntity, "A username is required."));
+    }
+
+    let policy = state.auth.password_policy.clone();
+    let user = state
+        .run(move |conn, _| create_user(conn, &username, &password, &policy))
+        .await
+        .map_err(|err| match err {
+            FastingAppError::DatabaseError(DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => {
+                ApiError::new(Status::Conflict, "That username is already taken.")
+            }
+            other => ApiError::from(other),
+        })?;
+    Ok((Status::Created, Json(user.into())))
+}
+
+/// Checks a username and password and starts a session.
+///
+/// - Failed attempts also count against the caller's IP address, so one client cannot try many usernames.
+#[post("/login", data = "<request>")]
+async fn login(
+    state: &State<ApiState>,
+    remote: Option<SocketAddr>,
+    request: Json<LoginRequest>,
+) -> ApiResult<Json<LoginResponse>> {
+    let LoginRequest { username, password, device_name } = request.into_inner();
+    let config = Arc::clone(&state.auth);
+    let client_address = remote.map(|address| address.ip().to_string());
+    let (user, tokens) = state
+        .run(move |conn, clock| {
+            let (device, address) = (device_name.as_deref(), client_address.as_deref());
+            sign_in(conn, &config, &username, &password, device, address, clock)
+        })
+        .await?;
+    Ok(Json(LoginResponse { user: user.into(), tokens }))
+}
+
+/// Exchanges a refresh token for new tokens; the old refresh token stops working.
+#[post("/refresh", data = "<request>")]
+async fn refresh(state: &State<ApiState>, request: Json<RefreshRequest>) -> ApiResult<Json<TokenPair>> {
+    let RefreshRequest { refresh_token } = request.into_inner();
+    let config = Arc::clone(&state.auth);
+    let tokens = state
+        .run(move |conn, clock| refresh_tokens(conn, &config, &refresh_token, clock))
+        .await?;
+    Ok(Json(tokens))
+}
+
+/// Signs out the session the request was made with.
+#[post("/logout")]
+async fn logout(state: &State<ApiState>, auth: AuthUser) -> ApiResult<Status> {
+    let (user_id, session_id) = (auth.user.id, auth.session_id);
+    state
+        .run(move |conn, clock| revoke_session(conn, user_id, session_id, clock))
+        .await?;
+    Ok(Status::NoContent)
+}
+
+/// The user's active sessions, most recently used first.
+#[get("/sessions")]
+async fn get_sessions(state: &State<ApiState>, auth: AuthUser) -> ApiResult<Json<Vec<SessionInfo>>> {
+    let user_id = auth.user.id;
+    let sessions = state
+        .run(move |conn, clock| list_active_sessions(conn, user_id, clock))
+        .await?;
+    Ok(Json(sessions))
+}
+
+/// Signs out one of the user's sessions.
+#[delete("/sessions/<session_id>")]
+async fn delete_session(state: &State<ApiState>, auth: AuthUser, session_id: i32) -> ApiResult<Status> {
+    let user_id = auth.user.id;
+    state
+        .run(move |conn, clock| revoke_session(conn, user_id, session_id, clock))
+        .await?;
+    Ok(Status::NoContent)
+}
+
+/// Signs out every session of the user, including this one.
+#[delete("/sessions")]
+async fn delete_all_sessions(state: &State<ApiState>, auth: AuthUser) -> ApiResult<Json<RevokedSessions>> {
+    let user_id = auth.user.id;
+    let revoked = state
+        .run(move |conn, clock| revoke_all_sessions(conn, user_id, clock))
+        .await?;
+    Ok(Json(RevokedSessions { revoked }))
+}
+
+/// Changes the password; every session and paired device is signed out, including this one.
+#[put("/account/password", data = "<request>")]
+async fn put_password(
+    state: &State<ApiState>,
+    auth: AuthUser,
+    request: Json<ChangePasswordRequest>,
+) -> ApiResult<Status> {
+    let ChangePasswordRequest { current_password, new_password } = request.into_inner();
+    let user_id = auth.user.id;
+    let config = Arc::clone(&state.auth);
+    state
+        .run(move |conn, clock| {
+            let (policy, lockout) = (&config.password_policy, &config.lockout);
+            change_password(conn, policy, lockout, user_id, &current_password, &new_password, clock)
+        })
+        .await?;
+    Ok(Status::NoContent)
+}
+
+/// Creates a short-lived code for pairing a new device with this account.
+#[post("/devices/pairing-codes")]
+async fn post_pairing_code(state: &State<ApiState>, auth: AuthUser) -> ApiResult<(Status, Json<PairingCode>)> {
+    let user_id = auth.user.id;
+    let pairing = state
+        .run(move |conn, clock| create_pairing_code(conn, user_id, clock))
+        .await?;
+    Ok((Status::Created, Json(pairing)))
+}
+
+/// Redeems a pairing code for a device credential; the credential is only returned once.
+#[post("/devices/pair", data = "<request>")]
+async fn pair_device(
+    state: &State<ApiState>,
+    request: Json<PairDeviceRequest>,
+) -> ApiResult<(Status, Json<DeviceCredential>)> {
+    let PairDeviceRequest { code, device_name } = request.into_inner();
+    let paired = state
+        .run(move |conn, clock| redeem_pairing_code(conn, &code, &device_name, clock))
+        .await?;
+    Ok((Status::Created, Json(paired)))
+}
+
+/// Checks a device credential and starts a session for that device.
+#[post("/devices/login", data = "<request>")]
+async fn device_login(state: &State<ApiState>, request: Json<DeviceLoginRequest>) -> ApiResult<Json<LoginResponse>> {
+    let DeviceLoginRequest { credential } = request.into_inner();
+    let config = Arc::clone(&state.auth);
+    let (user, tokens) = state
+        .run(move |conn, clock| sign_in_with_device(conn, &config, &credential, clock))
+        .await?;
+    Ok(Json(LoginResponse { user: user.into(), tokens }))
+}
+
+/// The user's paired devices that have not been revoked.
+#[get("/devices")]
+async fn get_devices(state: &State<ApiState>, auth: AuthUser) -> ApiResult<Json<Vec<DeviceInfo>>> {
+    let user_id = auth.user.id;
+    let devices = state.run(move |conn, _| list_devices(conn, user_id)).await?;
+    Ok(Json(devices))
+}
+
+/// Revokes one of the user's devices and signs out its sessions.
+#[delete("/devices/<device_id>")]
+async fn delete_device(state: &State<ApiState>, auth: AuthUser, device_id: i32) -> ApiResult<Status> {
+    let user_id = auth.user.id;
+    state
+        .run(move |conn, clock| revoke_device(conn, user_id, device_id, clock))
+        .await?;
+    Ok(Status::NoContent)
+}
+
+/// Options for starting a fast; the start time defaults to now.
+#[derive(Debug, Default, Deserialize)]
+pub struct StartFastRequest {
+    pub start_time: Option<NaiveDateTime>,
+    pub goal_id: Option<i32>,
+    pub protocol_id: Option<i32>,
+}
+
+/// Options for ending a fast; the stop time defaults to now.
+#[derive(Debug, Default, Deserialize)]
+pub struct StopFastRequest {
+    pub stop_time: Option<NaiveDateTime>,
+    /// The meal the fast was broken with.
+    pub meal: Option<BreakFastMealInput>,
+}
+
+/// A fasting event with its duration so far, excluding pauses.
+#[derive(Debug, Serialize)]
+pub struct FastRecord {
+    #[serde(flatten)]
+    pub event: FastingEvent,
+    pub minutes: i64,
+}
+
+/// Starts a fast and returns its status.
+#[post("/fasts/start", data = "<request>")]
+async fn start_fast(
+    state: &State<ApiState>,
+    auth: AuthUser,
+    request: Json<StartFastRequest>,
+) -> ApiResult<(Status, Json<Option<FastingStatus>>)> {
+    let user_id = auth.user.id;
+    let request = request.into_inner();
+
+    let status = state
+        .run(move |conn, clock| {
+            let start = request.start_time.unwrap_or_else(|| clock.now());
+            start_fasting(conn, user_id, start, request.goal_id, request.protocol_id, clock)?;
+            get_current_fasting_status(conn, user_id, clock)
+        })
+        .await?;
+    Ok((Status::Created, Json(status)))
+}
+
+/// Ends the ongoing fast, optionally logging the meal that broke it.
+#[post("/fasts/stop", data = "<request>")]
+async fn stop_fast(state: &State<ApiState>, auth: AuthUser, request: Json<StopFastRequest>) -> ApiResult<Status> {
+    let user_id = auth.user.id;
+    let request = request.into_inner();
+
+    state
+        .run(move |conn, clock| {
+            let stop = request.stop_time.unwrap_or_else(|| clock.now());
+            stop_fasting(conn, user_id, stop, request.meal.as_ref(), clock)
+        })
+        .await?;
+    Ok(Status::NoContent)
+}
+
+/// The ongoing fast, or `null` when the user is not fasting.
+#[get("/fasts/status")]
+async fn fast_status(state: &State<ApiState>, auth: AuthUser) -> ApiResult<Json<Option<FastingStatus>>> {
+    let user_id = auth.user.id;
+    let status = state
+        .run(move |conn, clock| get_current_fasting_status(conn, user_id, clock))
+        .await?;
+    Ok(Json(status))
+}
+
+/// Every fast the user has logged.
+#[get("/fasts")]
+async fn fast_history(state: &State<ApiState>, auth: AuthUser) -> ApiResult<Json<Vec<FastRecord>>> {
+    let user_id = auth.user.id;
+    let history = state
+        .run(move |conn, clock| get_fasting_history(conn, user_id, clock))
+        .await?;
+    Ok(Json(
+        history
+            .into_iter()
+            .map(|(event, minutes)| FastRecord { event, minutes })
+            .collect(),
+    ))
+}
+
+/// Duration in hours and deadline of a one-off goal.
+#[derive(Debug, Deserialize)]
+pub struct GoalRequest {
+    pub hours: i32,
+    pub deadline: NaiveDateTime,
+}
+
+/// Whether a goal should be archived or restored.
+#[derive(Debug, Deserialize)]
+pub struct ArchiveRequest {
+    pub archived: bool,
+}
+
+/// The user's goals by deadline; archived goals only on request.
+#[get("/goals?<include_archived>")]
+async fn get_goals(
+    state: &State<ApiState>,
+    auth: AuthUser,
+    include_archived: Option<bool>,
+) -> ApiResult<Json<Vec<FastingGoal>>> {
+    let user_id = auth.user.id;
+    let goals = state
+        .run(move |conn, clock| list_goals(conn, user_id, include_archived.unwrap_or(false), clock))
+        .await?;
+    Ok(Json(goals))
+}
+
+/// Creates a one-off goal.
+#[post("/goals", data = "<request>")]
+async fn post_goal(
+    state: &State<ApiState>,
+    auth: AuthUser,
+    request: Json<GoalRequest>,
+) -> ApiResult<(Status, Json<FastingGoal>)> {
+    let user_id = auth.user.id;
+    let GoalRequest { hours, deadline } = request.into_inner();
+    let goal = state
+        .run(move |conn, clock| create_goal(conn, user_id, hours, deadline, clock))
+        .await?;
+    Ok((Status::Created, Json(goal)))
+}
+
+/// One of the user's goals.
+#[get("/goals/<goal_id>")]
+async fn get_goal(state: &State<ApiState>, auth: AuthUser, goal_id: i32) -> ApiResult<Json<FastingGoal>> {
+    let user_id = auth.user.id;
+    let goal = state.run(move |conn, _| find_user_goal(conn, user_id, goal_id)).await?;
+    Ok(Json(goal))
+}
+
+/// Changes a goal's duration and deadline.
+#[put("/goals/<goal_id>", data = "<request>")]
+async fn put_goal(
+    state: &State<ApiState>,
+    auth: AuthUser,
+    goal_id: i32,
+    request: Json<GoalRequest>,
+) -> ApiResult<Json<FastingGoal>> {
+    let user_id = auth.user.id;
+    let GoalRequest { hours, deadline } = request.into_inner();
+    let goal = state
+        .run(move |conn, _| {
+            update_goal(conn, user_id, goal_id, hours, deadline)?;
+            find_user_goal(conn, user_id, goal_id)
+        })
+        .await?;
+    Ok(Json(goal))
+}
+
+/// Deletes a goal; linked fasts keep their history.
+#[delete("/goals/<goal_id>")]
+async fn delete_goal_route(state: &State<ApiState>, auth: AuthUser, goal_id: i32) -> ApiResult<Status> {
+    let user_id = auth.user.id;
+    state.run(move |conn, _| delete_goal(conn, user_id, goal_id)).await?;
+    Ok(Status::NoContent)
+}
+
+/// Archives or restores a goal.
+#[put("/goals/<goal_id>/archived", data = "<request>")]
+async fn put_goal_archived(
+    state: &State<ApiState>,
+    auth: AuthUser,
+    goal_id: i32,
+    request: Json<ArchiveRequest>,
+) -> ApiResult<Status> {
+    let user_id = auth.user.id;
+    let archived = request.archived;
+    state
+        .run(move |conn, clock| set_goal_archived(conn, user_id, goal_id, archived, clock))
+        .await?;
+    Ok(Status::NoContent)
+}
+
+/// Every goal's status, evaluated against the fasts that count toward it.
+#[get("/goals/evaluations")]
+async fn get_goal_evaluations(state: &State<ApiState>, auth: AuthUser) -> ApiResult<Json<Vec<GoalEvaluation>>> {
+    let user_id = auth.user.id;
+    let evaluations = state.run(move |conn, clock| evaluate_goals(conn, user_id, clock)).await?;
+    Ok(Json(evaluations))
+}
+
+/// Totals over the user's completed fasts.
+#[derive(Debug, Serialize)]
+pub struct FastingSummary {
+    pub average_minutes: Option<i64>,
+    pub total_minutes: i64,
+}
+
+/// Fasting minutes within a period.
+#[derive(Debug, Serialize)]
+pub struct FastingTime {
+    pub from: NaiveDateTime,
+    pub to: NaiveDateTime,
+    pub minutes: i64,
+}
+
+/// Parses a UTC time from a query parameter, e.g. `2025-03-01T18:30:00`.
+fn parse_query_time(name: &str, value: &str) -> Result<NaiveDateTime, FastingAppError> {
+    value.parse().map_err(|_| {
+        FastingAppError::InvalidRequest(format!("'{}' must be a time like 2025-03-01T18:30:00, not '{}'.", name, value))
+    })
+}
+
+/// Parses a date from a query parameter, e.g. `2025-03-01`.
+fn parse_query_date(name: &str, value: &str) -> Result<NaiveDate, FastingAppError> {
+    value.parse().map_err(|_| {
+        FastingAppError::InvalidRequest(format!("'{}' must be a date like 2025-03-01, not '{}'.", name, value))
+    })
+}
+
+/// Average and total duration of completed fasts.
+#[get("/analytics/summary")]
+async fn get_fasting_summary(state: &State<ApiState>, auth: AuthUser) -> ApiResult<Json<FastingSummary>> {
+    let user_id = auth.user.id;
+    let summary = state
+        .run(move |conn, _| {
+            Ok(FastingSummary {
+                average_minutes: calculate_average_fasting_duration(conn, user_id)?,
+                total_minutes: calculate_total_fasting_time(conn, user_id)?,
+            })
+        })
+        .await?;
+    Ok(Json(summary))
+}
+
+/// Fasting minutes between two UTC times.
+#[get("/analytics/fasting-time?<from>&<to>")]
+async fn get_fasting_time(
+    state: &State<ApiState>,
+    auth: AuthUser,
+    from: &str,
+    to: &str,
+) -> ApiResult<Json<FastingTime>> {
+    let user_id = auth.user.id;
+    let (from, to) = (parse_query_time("from", from)?, parse_query_time("to", to)?);
+    let minutes = state
+        .run(move |conn, _| calculate_fasting_time_between(conn, user_id, from, to))
+        .await?;
+    Ok(Json(FastingTime { from, to, minutes }))
+}
+
+/// Completed-fast statistics per protocol.
+#[get("/analytics/protocols")]
+async fn get_protocol_summaries(state: &State<ApiState>, auth: AuthUser) -> ApiResult<Json<Vec<ProtocolSummary>>> {
+    let user_id = auth.user.id;
+    let summaries = state.run(move |conn, _| summarize_fasts_by_protocol(conn, user_id)).await?;
+    Ok(Json(summaries))
+}
+
+/// Average journal scores per elapsed hour of fasting.
+#[get("/analytics/journal-hours")]
+async fn get_journal_scores(state: &State<ApiState>, auth: AuthUser) -> ApiResult<Json<Vec<HourlyJournalStats>>> {
+    let user_id = auth.user.id;
+    let stats = state.run(move |conn, _| journal_scores_by_fasting_hour(conn, user_id)).await?;
+    Ok(Json(stats))
+}
+
+/// The meals the user breaks fasts with, most common first.
+#[get("/analytics/break-fast-patterns")]
+async fn get_break_fast_patterns(
+    state: &State<ApiState>,
+    auth: AuthUser,
+) -> ApiResult<Json<Vec<BreakFastPattern>>> {
+    let user_id = auth.user.id;
+    let patterns = state.run(move |conn, _| summarize_break_fast_patterns(conn, user_id)).await?;
+    Ok(Json(patterns))
+}
+
+/// Large meals eaten straight after extended fasts.
+#[get("/analytics/refeeds")]
+async fn get_large_refeeds(state: &State<ApiState>, auth: AuthUser) -> ApiResult<Json<Vec<LargeRefeedWarning>>> {
+    let user_id = auth.user.id;
+    let warnings = state.run(move |conn, _| find_large_refeeds(conn, user_id)).await?;
+    Ok(Json(warnings))
+}
+
+/// Streaks and personal records, by the user's local days.
+#[get("/analytics/streaks?<threshold_minutes>")]
+async fn get_streaks(
+    state: &State<ApiState>,
+    auth: AuthUser,
+    threshold_minutes: Option<i64>,
+) -> ApiResult<Json<StreakReport>> {
+    let user_id = auth.user.id;
+    let threshold = threshold_minutes.unwrap_or(DEFAULT_STREAK_THRESHOLD_MINUTES);
+    let report = state
+        .run(move |conn, clock| {
+            let tz = get_user_timezone(conn, user_id)?;
+            calculate_streaks(conn, user_id, threshold, &tz, clock)
+        })
+        .await?;
+    Ok(Json(report))
+}
+
+/// Completed fasts aggregated per day, week or month of the user's local dates.
+#[get("/analytics/periods?<from>&<to>&<bucket>")]
+async fn get_periods(
+    state: &State<ApiState>,
+    auth: AuthUser,
+    from: &str,
+    to: &str,
+    bucket: &str,
+) -> ApiResult<Json<Vec<PeriodStats>>> {
+    let user_id = auth.user.id;
+    let (from, to) = (parse_query_date("from", from)?, parse_query_date("to", to)?);
+    let bucket_size: BucketSize = bucket.parse()?;
+    let periods = state
+        .run(move |conn, _| {
+            let tz = get_user_timezone(conn, user_id)?;
+            aggregate_fasting_by_period(conn, user_id, from, to, bucket_size, &tz)
+        })
+        .await?;
+    Ok(Json(periods))
+}
+
+/// Weight change between two UTC times; `null` with fewer than two weigh-ins.
+#[get("/analytics/weight-change?<from>&<to>")]
+async fn get_weight_change(
+    state: &State<ApiState>,
+    auth: AuthUser,
+    from: &str,
+    to: &str,
+) -> ApiResult<Json<Option<WeightChangeReport>>> {
+    let user_id = auth.user.id;
+    let (from, to) = (parse_query_time("from", from)?, parse_query_time("to", to)?);
+    let report = state
+        .run(move |conn, _| calculate_weight_change(conn, user_id, from, to))
+        .await?;
+    Ok(Json(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FakeClock;
+    use crate::db::apply_test_migrations;
+    use diesel::r2d2::{ConnectionManager, Pool};
+    use rocket::http::ContentType;
+    use rocket::local::blocking::Client;
+
+    /// An API over a fresh in-memory database; one pooled connection keeps it alive.
+    fn client_at(now: NaiveDateTime) -> Client {
+        let pool = Pool::builder()
+            .max_size(1)
+            .build(ConnectionManager::<SqliteConnection>::new(":memory:"))
+            .unwrap();
+        apply_test_migrations(&mut pool.get().unwrap());
+        let auth = AuthConfig::new("a test secret that is long enough!").unwrap();
+        let state = ApiState::new(pool, auth, Arc::new(FakeClock::new(now)));
+        Client::tracked(build_rocket(state)).expect("the API builds")
+    }
+
+    fn bearer(access_token: &str) -> Header<'static> {
+        Header::new("Authorization", format!("Bearer {}", access_token))
+    }
+
+    /// Adds `username` with a cheap bcrypt hash, so tests don't spend seconds hashing, and signs in.
+    fn sign_in_as(client: &Client, username: &str, password: &str) -> TokenPair {
+        use crate::schema::users::dsl::{hashed_password, id, users};
+        use diesel::prelude::*;
+
+        {
+            let mut conn = client.rocket().state::<ApiState>().unwrap().pool.get().unwrap();
+            let user_id = crate::db::insert_test_user(&mut conn, username);
+            diesel::update(users.filter(id.eq(user_id)))
+                .set(hashed_password.eq(bcrypt::hash(password, 4).unwrap()))
+                .execute(&mut conn)
+                .unwrap();
+        }
+
+        let response = client
+            .post("/api/login")
+            .header(ContentType::JSON)
+            .body(serde_json::json!({ "username": username, "password": password }).to_string())
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let body: serde_json::Value = response.into_json().unwrap();
+        serde_json::from_value(body["tokens"].clone()).unwrap()
+    }
+
+    fn at(hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 3, 1).unwrap().and_hms_opt(hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_errors_map_to_status_codes() {
+        assert_eq!(error_status(&FastingAppError::GoalNotFound(1)), Status::NotFound);
+        assert_eq!(error_status(&FastingAppError::GoalAccessDenied(1)), Status::Forbidden);
+        assert_eq!(error_status(&FastingAppError::ExistingSessionError(1)), Status::Conflict);
+        assert_eq!(error_status(&FastingAppError::invalid_credentials("x")), Status::Unauthorized);
+        assert_eq!(error_status(&FastingAppError::AccountLocked { retry_after: at(1) }), Status::TooManyRequests);
+        assert_eq!(error_status(&FastingAppError::FutureTimestamp(at(1))), Status::UnprocessableEntity);
+        assert_eq!(
+            error_status(&FastingAppError::DatabaseError(DieselError::RollbackTransaction)),
+            Status::InternalServerError
+        );
+    }
+
+    #[test]
+    fn test_locked_out_clients_get_429_with_retry_after() {
+        let client = client_at(at(12));
+        sign_in_as(&client, "alice", "secret");
+        let from_attacker = |username: &str, password: &str| {
+            client
+                .post("/api/login")
+                .remote("203.0.113.7:40000".parse().unwrap())
+                .header(ContentType::JSON)
+                .body(serde_json::json!({ "username": username, "password": password }).to_string())
+                .dispatch()
+        };
+
+        for attempt in 1..5 {
+            assert_eq!(from_attacker(&format!("user{}", attempt), "guess").status(), Status::Unauthorized);
+        }
+        let response = from_attacker("user5", "guess");
+        assert_eq!(response.status(), Status::TooManyRequests);
+        assert_eq!(response.headers().get_one("Retry-After"), Some("Sat, 01 Mar 2025 12:00:30 GMT"));
+
+        // The address stays locked even with the right password
+        assert_eq!(from_attacker("alice", "secret").status(), Status::TooManyRequests);
+    }
+
+    #[test]
+    fn test_forged_forwarding_headers_do_not_dodge_the_lockout() {
+        let client = client_at(at(12));
+        let forged_attempt = |attempt: u8| {
+            client
+                .post("/api/login")
+                .remote("203.0.113.7:40000".parse().unwrap())
+                .header(Header::new("X-Real-IP", format!("198.51.100.{}", attempt)))
+                .header(ContentType::JSON)
+                .body(serde_json::json!({ "username": format!("user{}", attempt), "password": "guess" }).to_string())
+                .dispatch()
+                .status()
+        };
+
+        for attempt in 1..5 {
+            assert_eq!(forged_attempt(attempt), Status::Unauthorized);
+        }
+        assert_eq!(forged_attempt(5), Status::TooManyRequests);
+    }
+
+    #[test]
+    fn test_requests_without_a_valid_token_are_rejected() {
+        let client = client_at(at(12));
+        let tokens = sign_in_as(&client, "alice", "secret");
+
+        let response = client.get("/api/fasts/status").dispatch();
+        assert_eq!(response.status(), Status::Unauthorized);
+        assert!(response.headers().get_one("WWW-Authenticate").is_some());
+
+        let response = client
+            .post("/api/login")
+            .header(ContentType::JSON)
+            .body(r#"{"username": "alice", "password": "wrong"}"#)
+            .dispatch();
+        assert_eq!(response.status(), Status::Unauthorized);
+        let body: ErrorBody = response.into_json().unwrap();
+        assert_eq!(body.error, "Unauthorized");
+
+        let response = client.get("/api/fasts/status").header(bearer(&tokens.access_token)).dispatch();
+        assert_eq!(response.status(), Status::Ok);
+
+        let response = client.post("/api/logout").header(bearer(&tokens.access_token)).dispatch();
+        assert_eq!(response.status(), Status::NoContent);
+        let response = client.get("/api/fasts/status").header(bearer(&tokens.access_token)).dispatch();
+        assert_eq!(response.status(), Status::Unauthorized);
+    }
+
+    #[test]
+    fn test_fast_lifecycle_and_error_bodies() {
+        let client = client_at(at(12));
+        let tokens = sign_in_as(&client, "alice", "secret");
+        let auth = || bearer(&tokens.access_token);
+
+        let response = client
+            .post("/api/fasts/start")
+            .header(auth())
+            .header(ContentType::JSON)
+            .body(r#"{"start_time": "2025-03-01T04:00:00"}"#)
+            .dispatch();
+        assert_eq!(response.status(), Status::Created);
+        let status: serde_json::Value = response.into_json().unwrap();
+        assert_eq!(status["elapsed_minutes"], 8 * 60);
+
+        let response = client.post("/api/fasts/start").header(auth()).header(ContentType::JSON).body("{}").dispatch();
+        assert_eq!(response.status(), Status::Conflict);
+        let body: ErrorBody = response.into_json().unwrap();
+        assert_eq!(body.message, FastingAppError::ExistingSessionError(1).user_friendly_message());
+
+        let response = client
+            .post("/api/fasts/stop")
+            .header(auth())
+            .header(ContentType::JSON)
+            .body(r#"{"stop_time": "2025-03-01T13:00:00"}"#)
+            .dispatch();
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+
+        let response = client.post("/api/fasts/stop").header(auth()).header(ContentType::JSON).body("{}").dispatch();
+        assert_eq!(response.status(), Status::NoContent);
+
+        let response = client.get("/api/fasts/status").header(auth()).dispatch();
+        assert_eq!(response.into_string().unwrap(), "null");
+
+        let response = client.get("/api/goals/42").header(auth()).dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+    }
+}
@@ -0,0 +1,636 @@
+use crate::clock::Clock;
+use crate::errors::FastingAppError;
+use crate::models::{AuthToken, NewAuthToken, User};
+use crate::schema::auth_tokens::dsl::{
+    auth_tokens, expires_at, id as token_id, last_used_at, previous_token_hash, revoked_at, token_hash,
+    user_id as token_user_id,
+};
+use crate::users::devices::login_with_device;
+use crate::users::find::find_user_by_id;
+use crate::users::lockout::{
+    check_login_allowed, clear_failed_logins, record_failed_login, LockoutPolicy, LockoutScope,
+};
+use crate::users::login::login_user;
+use crate::users::password::PasswordPolicy;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL;
+use base64::Engine;
+use chrono::{DateTime, Duration, NaiveDateTime};
+use diesel::prelude::*;
+use diesel::SqliteConnection;
+use dotenv::dotenv;
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::env;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Access tokens are short-lived; clients refresh them with their refresh token (15 minutes).
+pub const ACCESS_TOKEN_TTL_MINUTES: i64 = 15;
+
+/// A session ends if its refresh token goes unused this long (30 days).
+pub const REFRESH_TOKEN_TTL_DAYS: i64 = 30;
+
+/// Shortest accepted signing secret, in bytes.
+pub const MIN_SECRET_BYTES: usize = 32;
+
+/// The signing secret, token lifetimes, failed sign-in lockout policy and password policy.
+#[derive(Clone)]
+pub struct AuthConfig {
+    secret: Vec<u8>,
+    pub access_token_ttl: Duration,
+    pub refresh_token_ttl: Duration,
+    pub lockout: LockoutPolicy,
+    pub password_policy: PasswordPolicy,
+}
+
+impl AuthConfig {
+    /// ✅ Creates a config with the default lifetimes and policies.
+    ///
+    /// - Returns `InvalidRequest` if the secret is shorter than `MIN_SECRET_BYTES`.
+    pub fn new(secret: impl Into<Vec<u8>>) -> Result<Self, FastingAppError> {
+        let secret = secret.into();
+        if secret.len() < MIN_SECRET_BYTES {
+            return Err(FastingAppError::InvalidRequest(format!(
+                "AUTH_SECRET must be at least {} bytes long",
+                MIN_SECRET_BYTES
+            )));
+        }
+        Ok(AuthConfig {
+            secret,
+            access_token_ttl: Duration::minutes(ACCESS_TOKEN_TTL_MINUTES),
+            refresh_token_ttl: Duration::days(REFRESH_TOKEN_TTL_DAYS),
+            lockout: LockoutPolicy::default(),
+            password_policy: PasswordPolicy::default(),
+        })
+    }
+
+    /// ✅ Reads the signing secret from the `AUTH_SECRET` environment variable, the lockout
+    /// policy from the `LOGIN_LOCKOUT_*` variables and the password policy from `PASSWORD_*`.
+    pub fn from_env() -> Result<Self, FastingAppError> {
+        dotenv().ok();
+        let secret = env::var("AUTH_SECRET")
+            .map_err(|_| FastingAppError::InvalidRequest("AUTH_SECRET must be set".to_string()))?;
+        Ok(AuthConfig {
+            lockout: LockoutPolicy::from_env()?,
+            password_policy: PasswordPolicy::from_env()?,
+            ..AuthConfig::new(secret)?
+        })
+    }
+}
+
+/// What an access token vouches for.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessClaims {
+    /// The user ID.
+    pub sub: i32,
+    /// The session the token was issued for.
+    pub sid: i32,
+    /// Expiry, in seconds since the Unix epoch.
+    pub exp: i64,
+}
+
+/// Tokens handed to a client when it signs in or refreshes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenPair {
+    pub session_id: i32,
+    pub access_token: String,
+    pub access_expires_at: NaiveDateTime,
+    /// Can be used once; refreshing returns a new one.
+    pub refresh_token: String,
+    pub refresh_expires_at: NaiveDateTime,
+}
+
+/// An active session as shown to its user.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionInfo {
+    pub id: i32,
+    pub device_name: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub last_used_at: NaiveDateTime,
+    pub expires_at: NaiveDateTime,
+}
+
+impl From<AuthToken> for SessionInfo {
+    fn from(session: AuthToken) -> Self {
+        SessionInfo {
+            id: session.id,
+            device_name: session.device_name,
+            created_at: session.created_at,
+            last_used_at: session.last_used_at,
+            expires_at: session.expires_at,
+        }
+    }
+}
+
+/// ✅ Checks a username and password and starts a session for `device_name`.
+///
+/// - Failed attempts are counted per username and per `client_address` (the caller's IP address,
+///   if known); too many in a row lock them out for a while, see `AuthConfig::lockout`.
+/// - `device_name` only labels the session, since any client can claim any name.
+/// - Returns `AccountLocked` while either is locked, without checking the password.
+pub fn sign_in(
+    conn: &mut SqliteConnection,
+    config: &AuthConfig,
+    username_input: &str,
+    password_input: &str,
+    device_name: Option<&str>,
+    client_address: Option<&str>,
+    clock: &dyn Clock,
+) -> Result<(User, TokenPair), FastingAppError> {
+    let user = check_password(conn, &config.lockout, username_input, password_input, client_address, clock)?;
+    let tokens = issue_tokens(conn, config, user.id, device_name, clock)?;
+    Ok((user, tokens))
+}
+
+/// ✅ Like `sign_in`, for clients on this machine such as the command line; returns the session ID.
+///
+/// - Needs no `AUTH_SECRET`: no access token is signed, so the session can only be listed and signed out.
+/// - Failed attempts are counted against the username only.
+pub fn sign_in_local(
+    conn: &mut SqliteConnection,
+    lockout: &LockoutPolicy,
+    username_input: &str,
+    password_input: &str,
+    device_name: Option<&str>,
+    clock: &dyn Clock,
+) -> Result<(User, i32), FastingAppError> {
+    let user = check_password(conn, lockout, username_input, password_input, None, clock)?;
+    let refresh_ttl = Duration::days(REFRESH_TOKEN_TTL_DAYS);
+    let (session_id, _) = insert_session(conn, refresh_ttl, user.id, device_name, None, clock)?;
+    Ok((user, session_id))
+}
+
+/// Checks a password, counting failures toward the lockout policy.
+fn check_password(
+    conn: &mut SqliteConnection,
+    lockout: &LockoutPolicy,
+    username_input: &str,
+    password_input: &str,
+    client_address: Option<&str>,
+    clock: &dyn Clock,
+) -> Result<User, FastingAppError> {
+    let mut subjects = vec![(LockoutScope::Username, username_input)];
+    if let Some(address) = client_address {
+        subjects.push((LockoutScope::Device, address));
+    }
+    check_login_allowed(conn, &subjects, clock)?;
+
+    let user = match login_user(conn, username_input, password_input) {
+        Ok(user) => user,
+        Err(FastingAppError::InvalidCredentials(identifier)) => {
+            return Err(match record_failed_login(conn, lockout, &subjects, clock)? {
+                Some(retry_after) => FastingAppError::AccountLocked { retry_after },
+                None => FastingAppError::InvalidCredentials(identifier),
+            });
+        }
+        Err(e) => return Err(e),
+    };
+    clear_failed_logins(conn, &subjects)?;
+    Ok(user)
+}
+
+/// ✅ Signs in with a paired device's credential.
+///
+/// - The session is tied to the device, so revoking the device also ends it.
+pub fn sign_in_with_device(
+    conn: &mut SqliteConnection,
+    config: &AuthConfig,
+    device_credential: &str,
+    clock: &dyn Clock,
+) -> Result<(User, TokenPair), FastingAppError> {
+    let (user, device) = login_with_device(conn, device_credential, clock)?;
+    let tokens = start_session(conn, config, user.id, Some(&device.name), Some(device.id), clock)?;
+    Ok((user, tokens))
+}
+
+/// ✅ Like `sign_in_with_device`, for clients on this machine; returns the session ID.
+pub fn sign_in_local_with_device(
+    conn: &mut SqliteConnection,
+    device_credential: &str,
+    clock: &dyn Clock,
+) -> Result<(User, i32), FastingAppError> {
+    let (user, device) = login_with_device(conn, device_credential, clock)?;
+    let refresh_ttl = Duration::days(REFRESH_TOKEN_TTL_DAYS);
+    let (session_id, _) = insert_session(conn, refresh_ttl, user.id, Some(&device.name), Some(device.id), clock)?;
+    Ok((user, session_id))
+}
+
+/// ✅ Starts a session for a user who has already been authenticated.
+///
+/// - Only a hash of the refresh token is stored.
+pub fn issue_tokens(
+    conn: &mut SqliteConnection,
+    config: &AuthConfig,
+    user_id_input: i32,
+    device_name: Option<&str>,
+    clock: &dyn Clock,
+) -> Result<TokenPair, FastingAppError> {
+    start_session(conn, config, user_id_input, device_name, None, clock)
+}
+
+/// Stores a new session, optionally tied to a paired device, and signs its first tokens.
+fn start_session(
+    conn: &mut SqliteConnection,
+    config: &AuthConfig,
+    user_id_input: i32,
+    device_name: Option<&str>,
+    device_id: Option<i32>,
+    clock: &dyn Clock,
+) -> Result<TokenPair, FastingAppError> {
+    let now = clock.now();
+    let (session_id, refresh_token) =
+        insert_session(conn, config.refresh_token_ttl, user_id_input, device_name, device_id, clock)?;
+    token_pair(config, user_id_input, session_id, refresh_token, now + config.refresh_token_ttl, now)
+}
+
+/// Stores a new session and returns its ID and first refresh token; only the token's hash is kept.
+fn insert_session(
+    conn: &mut SqliteConnection,
+    refresh_token_ttl: Duration,
+    user_id_input: i32,
+    device_name: Option<&str>,
+    device_id: Option<i32>,
+    clock: &dyn Clock,
+) -> Result<(i32, String), FastingAppError> {
+    let now = clock.now();
+    let refresh_token = generate_token();
+    let new_session = NewAuthToken {
+        user_id: user_id_input,
+        token_hash: hash_token(&refresh_token),
+        device_name: device_name.map(str::trim).filter(|name| !name.is_empty()).map(str::to_string),
+        created_at: now,
+        last_used_at: now,
+        expires_at: now + refresh_token_ttl,
+        device_id,
+    };
+
+    let session_id = diesel::insert_into(auth_tokens)
+        .values(&new_session)
+        .returning(token_id)
+        .get_result::<i32>(conn)
+        .map_err(FastingAppError::DatabaseError)?;
+    Ok((session_id, refresh_token))
+}
+
+/// ✅ Checks an access token's signature and expiry, without touching the database.
+pub fn verify_access_token(
+    config: &AuthConfig,
+    access_token: &str,
+    clock: &dyn Clock,
+) -> Result<AccessClaims, FastingAppError> {
+    let invalid = || FastingAppError::InvalidToken("The access token is not valid.".to_string());
+
+    let (payload, signature) = access_token.split_once('.').ok_or_else(invalid)?;
+    let signature = BASE64_URL.decode(signature).map_err(|_| invalid())?;
+    let mut mac = signer(config);
+    mac.update(payload.as_bytes());
+    mac.verify_slice(&signature).map_err(|_| invalid())?;
+
+    let payload = BASE64_URL.decode(payload).map_err(|_| invalid())?;
+    let claims: AccessClaims = serde_json::from_slice(&payload).map_err(|_| invalid())?;
+    if claims.exp <= clock.now().and_utc().timestamp() {
+        return Err(FastingAppError::InvalidToken("The access token has expired.".to_string()));
+    }
+    Ok(claims)
+}
+
+/// ✅ Resolves an access token to its user.
+///
+/// - The token's session must still be active, so revoking a session also ends its access tokens.
+/// - Records the session's last use.
+pub fn authenticate(
+    conn: &mut SqliteConnection,
+    config: &AuthConfig,
+    access_token: &str,
+    clock: &dyn Clock,
+) -> Result<(User, AccessClaims), FastingAppError> {
+    let claims = verify_access_token(config, access_token, clock)?;
+    let now = clock.now();
+
+    let touched = diesel::update(
+        auth_tokens
+            .filter(token_id.eq(claims.sid))
+            .filter(token_user_id.eq(claims.sub))
+            .filter(revoked_at.is_null())
+            .filter(expires_at.gt(now)),
+    )
+    .set(last_used_at.eq(now))
+    .execute(conn)
+    .map_err(FastingAppError::DatabaseError)?;
+    if touched == 0 {
+        return Err(FastingAppError::InvalidToken("This session has been signed out.".to_string()));
+    }
+
+    let user = find_user_by_id(conn, claims.sub, clock)?;
+    Ok((user, claims))
+}
+
+/// ✅ Exchanges a refresh token for a new token pair.
+///
+/// - The presented token stops working; the session's expiry moves forward from now.
+/// - Presenting a token that was already exchanged signs the whole session out,
+///   since only a copied token can be used twice.
+pub fn refresh_tokens(
+    conn: &mut SqliteConnection,
+    config: &AuthConfig,
+    refresh_token: &str,
+    clock: &dyn Clock,
+) -> Result<TokenPair, FastingAppError> {
+    let presented_hash = hash_token(refresh_token);
+    let now = clock.now();
+
+    let session = auth_tokens
+        .filter(token_hash.eq(&presented_hash))
+        .select(AuthToken::as_select())
+        .first::<AuthToken>(conn)
+        .optional()
+        .map_err(FastingAppError::DatabaseError)?;
+
+    let Some(session) = session else {
+        let revoked = diesel::update(
+            auth_tokens
+                .filter(previous_token_hash.eq(&presented_hash))
+                .filter(revoked_at.is_null()),
+        )
+        .set(revoked_at.eq(Some(now)))
+        .execute(conn)
+        .map_err(FastingAppError::DatabaseError)?;
+
+        return Err(FastingAppError::InvalidToken(if revoked > 0 {
+            "This refresh token was already used, so its session has been signed out.".to_string()
+        } else {
+            "The refresh token is not valid.".to_string()
+        }));
+    };
+    if session.revoked_at.is_some() {
+        return Err(FastingAppError::InvalidToken("This session has been signed out.".to_string()));
+    }
+    if session.expires_at <= now {
+        return Err(FastingAppError::InvalidToken("This session has expired.".to_string()));
+    }
+
+    let new_refresh_token = generate_token();
+    let new_expiry = now + config.refresh_token_ttl;
+    // Matching on the old hash means only one of two concurrent refreshes can win
+    let rotated = diesel::update(
+        auth_tokens
+            .filter(token_id.eq(session.id))
+            .filter(token_hash.eq(&presented_hash)),
+    )
+    .set((
+        token_hash.eq(hash_token(&new_refresh_token)),
+        previous_token_hash.eq(Some(&presented_hash)),
+        last_used_at.eq(now),
+        expires_at.eq(new_expiry),
+    ))
+    .execute(conn)
+    .map_err(FastingAppError::DatabaseError)?;
+    if rotated == 0 {
+        return Err(FastingAppError::InvalidToken("The refresh token was already used.".to_string()));
+    }
+
+    token_pair(config, session.user_id, session.id, new_refresh_token, new_expiry, now)
+}
+
+/// ✅ Signs out one of the user's sessions.
+///
+/// - Returns `AuthSessionNotFound` for unknown, other users' or already revoked sessions.
+pub fn revoke_session(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    session_id: i32,
+    clock: &dyn Clock,
+) -> Result<(), FastingAppError> {
+    let revoked = diesel::update(
+        auth_tokens
+            .filter(token_id.eq(session_id))
+            .filter(token_user_id.eq(user_id_input))
+            .filter(revoked_at.is_null()),
+    )
+    .set(revoked_at.eq(Some(clock.now())))
+    .execute(conn)
+    .map_err(FastingAppError::DatabaseError)?;
+
+    if revoked == 0 {
+        return Err(FastingAppError::AuthSessionNotFound(session_id));
+    }
+    Ok(())
+}
+
+/// ✅ Signs out every session of the user and returns how many were active.
+pub fn revoke_all_sessions(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    clock: &dyn Clock,
+) -> Result<usize, FastingAppError> {
+    let now = clock.now();
+    diesel::update(
+        auth_tokens
+            .filter(token_user_id.eq(user_id_input))
+            .filter(revoked_at.is_null())
+            .filter(expires_at.gt(now)),
+    )
+    .set(revoked_at.eq(Some(now)))
+    .execute(conn)
+    .map_err(FastingAppError::DatabaseError)
+}
+
+/// ✅ Lists the user's sessions that are neither revoked nor expired, most recently used first.
+pub fn list_active_sessions(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    clock: &dyn Clock,
+) -> Result<Vec<SessionInfo>, FastingAppError> {
+    let sessions = auth_tokens
+        .filter(token_user_id.eq(user_id_input))
+        .filter(revoked_at.is_null())
+        .filter(expires_at.gt(clock.now()))
+        .order((last_used_at.desc(), token_id.desc()))
+        .select(AuthToken::as_select())
+        .load::<AuthToken>(conn)
+        .map_err(FastingAppError::DatabaseError)?;
+
+    Ok(sessions.into_iter().map(SessionInfo::from).collect())
+}
+
+/// Signs a new access token and bundles it with the session's refresh token.
+fn token_pair(
+    config: &AuthConfig,
+    user_id_input: i32,
+    session_id: i32,
+    refresh_token: String,
+    refresh_expires_at: NaiveDateTime,
+    now: NaiveDateTime,
+) -> Result<TokenPair, FastingAppError> {
+    let access_expires_at = now + config.access_token_ttl;
+    let claims = AccessClaims {
+        sub: user_id_input,
+        sid: session_id,
+        exp: access_expires_at.and_utc().timestamp(),
+    };
+
+    let payload = serde_json::to_vec(&claims)
+        .map_err(|err| FastingAppError::SerializationError(err.to_string()))?;
+    let payload = BASE64_URL.encode(payload);
+    let mut mac = signer(config);
+    mac.update(payload.as_bytes());
+    let signature = BASE64_URL.encode(mac.finalize().into_bytes());
+
+    Ok(TokenPair {
+        session_id,
+        access_token: format!("{}.{}", payload, signature),
+        // Claims only hold whole seconds
+        access_expires_at: DateTime::from_timestamp(claims.exp, 0)
+            .map_or(access_expires_at, |at| at.naive_utc()),
+        refresh_token,
+        refresh_expires_at,
+    })
+}
+
+fn signer(config: &AuthConfig) -> HmacSha256 {
+    HmacSha256::new_from_slice(&config.secret).expect("HMAC accepts keys of any length")
+}
+
+/// 32 random bytes, URL-safe encoded; used for refresh tokens and device credentials.
+pub(crate) fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    BASE64_URL.encode(bytes)
+}
+
+/// Tokens are random, so a plain SHA-256 is enough to keep them out of the database.
+pub(crate) fn hash_token(token: &str) -> String {
+    BASE64_URL.encode(Sha256::digest(token.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FakeClock;
+    use crate::db::{insert_test_user, test_connection};
+    use chrono::NaiveDate;
+
+    fn config() -> AuthConfig {
+        AuthConfig::new("a test secret that is long enough!").unwrap()
+    }
+
+    fn clock() -> FakeClock {
+        FakeClock::new(NaiveDate::from_ymd_opt(2025, 3, 1).unwrap().and_hms_opt(12, 0, 0).unwrap())
+    }
+
+    /// Inserts a user who can sign in with `password`, hashed cheaply.
+    fn insert_user_with_password(conn: &mut SqliteConnection, username: &str, password: &str) -> i32 {
+        use crate::schema::users::dsl::{hashed_password, id, users};
+
+        let user = insert_test_user(conn, username);
+        diesel::update(users.filter(id.eq(user)))
+            .set(hashed_password.eq(bcrypt::hash(password, 4).unwrap()))
+            .execute(conn)
+            .unwrap();
+        user
+    }
+
+    #[test]
+    fn test_access_tokens_are_signed_and_expire() {
+        let mut conn = test_connection();
+        let user = insert_test_user(&mut conn, "alice");
+        let clock = clock();
+        let tokens = issue_tokens(&mut conn, &config(), user, Some("phone"), &clock).unwrap();
+
+        let (found, claims) = authenticate(&mut conn, &config(), &tokens.access_token, &clock).unwrap();
+        assert_eq!((found.id, claims.sid), (user, tokens.session_id));
+
+        let other_key = AuthConfig::new("a different secret, also long enough").unwrap();
+        assert!(matches!(
+            verify_access_token(&other_key, &tokens.access_token, &clock),
+            Err(FastingAppError::InvalidToken(_))
+        ));
+
+        clock.advance(Duration::minutes(ACCESS_TOKEN_TTL_MINUTES));
+        assert!(matches!(
+            verify_access_token(&config(), &tokens.access_token, &clock),
+            Err(FastingAppError::InvalidToken(_))
+        ));
+    }
+
+    #[test]
+    fn test_refresh_tokens_rotate_and_reuse_signs_the_session_out() {
+        let mut conn = test_connection();
+        let user = insert_test_user(&mut conn, "alice");
+        let clock = clock();
+        let first = issue_tokens(&mut conn, &config(), user, None, &clock).unwrap();
+
+        clock.advance(Duration::hours(1));
+        let second = refresh_tokens(&mut conn, &config(), &first.refresh_token, &clock).unwrap();
+        assert_eq!(second.session_id, first.session_id);
+        assert_ne!(second.refresh_token, first.refresh_token);
+
+        // The old token comes back: the session is revoked, even for the newest token
+        assert!(refresh_tokens(&mut conn, &config(), &first.refresh_token, &clock).is_err());
+        assert!(refresh_tokens(&mut conn, &config(), &second.refresh_token, &clock).is_err());
+        assert!(authenticate(&mut conn, &config(), &second.access_token, &clock).is_err());
+    }
+
+    #[test]
+    fn test_revoking_sessions() {
+        let mut conn = test_connection();
+        let alice = insert_test_user(&mut conn, "alice");
+        let bob = insert_test_user(&mut conn, "bob");
+        let clock = clock();
+        let phone = issue_tokens(&mut conn, &config(), alice, Some("phone"), &clock).unwrap();
+        issue_tokens(&mut conn, &config(), alice, Some("laptop"), &clock).unwrap();
+        issue_tokens(&mut conn, &config(), bob, None, &clock).unwrap();
+
+        assert!(matches!(
+            revoke_session(&mut conn, bob, phone.session_id, &clock),
+            Err(FastingAppError::AuthSessionNotFound(_))
+        ));
+        revoke_session(&mut conn, alice, phone.session_id, &clock).unwrap();
+        let active = list_active_sessions(&mut conn, alice, &clock).unwrap();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].device_name.as_deref(), Some("laptop"));
+
+        assert_eq!(revoke_all_sessions(&mut conn, alice, &clock).unwrap(), 1);
+        assert!(list_active_sessions(&mut conn, alice, &clock).unwrap().is_empty());
+        assert_eq!(list_active_sessions(&mut conn, bob, &clock).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_local_sign_in_needs_no_signing_secret() {
+        let mut conn = test_connection();
+        let alice = insert_user_with_password(&mut conn, "alice", "correct horse battery");
+        let clock = clock();
+
+        let lockout = LockoutPolicy::default();
+        let (user, session_id) =
+            sign_in_local(&mut conn, &lockout, "alice", "correct horse battery", Some("Command line"), &clock).unwrap();
+        assert_eq!(user.id, alice);
+        let active = list_active_sessions(&mut conn, alice, &clock).unwrap();
+        assert_eq!((active[0].id, active[0].device_name.as_deref()), (session_id, Some("Command line")));
+    }
+
+    #[test]
+    fn test_failed_sign_ins_lock_the_client_address_not_the_device_name() {
+        let mut conn = test_connection();
+        insert_user_with_password(&mut conn, "alice", "correct horse battery");
+        let clock = clock();
+        let config = config();
+
+        let (attacker, elsewhere) = (Some("203.0.113.7"), Some("198.51.100.2"));
+        let password = "correct horse battery";
+
+        for attempt in 0..config.lockout.threshold {
+            let guess = format!("guess {}", attempt);
+            assert!(sign_in(&mut conn, &config, "mallory", &guess, Some("Command line"), attacker, &clock).is_err());
+        }
+
+        // Anyone can send the same device name, so it must not lock out other clients
+        assert!(sign_in(&mut conn, &config, "alice", password, Some("Command line"), elsewhere, &clock).is_ok());
+        assert!(matches!(
+            sign_in(&mut conn, &config, "alice", password, Some("Phone"), attacker, &clock),
+            Err(FastingAppError::AccountLocked { .. })
+        ));
+    }
+}
@@ -0,0 +1,27 @@
+use dotenv::dotenv;
+use fasting_rust::{build_rocket, establish_pool, ApiState, AuthConfig, SystemClock};
+use std::sync::Arc;
+
+#[rocket::main]
+async fn main() {
+    dotenv().ok();
+
+    let pool = match establish_pool() {
+        Ok(pool) => pool,
+        Err(e) => {
+            eprintln!("❌ Failed to open the database: {:?}", e);
+            return;
+        }
+    };
+    let auth = match AuthConfig::from_env() {
+        Ok(auth) => auth,
+        Err(e) => {
+            eprintln!("❌ Failed to load token settings: {:?}", e);
+            return;
+        }
+    };
+
+    if let Err(e) = build_rocket(ApiState::new(pool, auth, Arc::new(SystemClock))).launch().await {
+        eprintln!("❌ The API server stopped: {}", e);
+    }
+}
@@ -0,0 +1,49 @@
+use chrono::{Duration, NaiveDateTime, Utc};
+use std::sync::Mutex;
+
+/// Source of the current time, as a UTC instant.
+///
+/// Handlers take a `&dyn Clock` instead of reading the system time, so tests can
+/// fix "now" and move it forward explicitly.
+pub trait Clock {
+    fn now(&self) -> NaiveDateTime;
+}
+
+/// The real system clock.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> NaiveDateTime {
+        Utc::now().naive_utc()
+    }
+}
+
+/// A clock that stands still until it is set or advanced.
+#[derive(Debug)]
+pub struct FakeClock {
+    now: Mutex<NaiveDateTime>,
+}
+
+impl FakeClock {
+    /// ✅ Creates a clock stopped at `now` (UTC).
+    pub fn new(now: NaiveDateTime) -> Self {
+        FakeClock { now: Mutex::new(now) }
+    }
+
+    /// ✅ Moves the clock to `now` (UTC).
+    pub fn set(&self, now: NaiveDateTime) {
+        *self.now.lock().unwrap() = now;
+    }
+
+    /// ✅ Moves the clock forward by `by`.
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock().unwrap() += by;
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> NaiveDateTime {
+        *self.now.lock().unwrap()
+    }
+}
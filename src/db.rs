@@ -1,3 +1,5 @@
+use diesel::connection::SimpleConnection;
+use diesel::r2d2::{ConnectionManager, CustomizeConnection, Pool};
 use diesel::sqlite::SqliteConnection;
 use diesel::Connection;
 use dotenv::dotenv;
@@ -16,6 +18,87 @@ pub fn establish_connection() -> Result<SqliteConnection, FastingAppError> {
         .map_err(|err| FastingAppError::ConnectionError(format!("Failed to connect: {}", err)))
 }
 
+/// A pool of SQLite connections, shared by the HTTP API's request handlers.
+pub type DbPool = Pool<ConnectionManager<SqliteConnection>>;
+
+/// Build a connection pool for the database at `DATABASE_URL`
+pub fn establish_pool() -> Result<DbPool, FastingAppError> {
+    dotenv().ok();
+    let database_url = env::var("DATABASE_URL").map_err(|_| {
+        FastingAppError::InvalidRequest("DATABASE_URL must be set".to_string())
+    })?;
+
+    Pool::builder()
+        .connection_customizer(Box::new(BusyTimeout))
+        .build(ConnectionManager::<SqliteConnection>::new(database_url))
+        .map_err(|err| FastingAppError::ConnectionError(format!("Failed to build pool: {}", err)))
+}
+
+/// Makes pooled connections wait for each other's write locks instead of failing at once.
+#[derive(Debug)]
+struct BusyTimeout;
+
+impl CustomizeConnection<SqliteConnection, diesel::r2d2::Error> for BusyTimeout {
+    fn on_acquire(&self, conn: &mut SqliteConnection) -> Result<(), diesel::r2d2::Error> {
+        conn.batch_execute("PRAGMA busy_timeout = 5000;")
+            .map_err(diesel::r2d2::Error::QueryError)
+    }
+}
+
+/// Opens an in-memory database with every migration applied, for tests.
+#[cfg(test)]
+pub(crate) fn test_connection() -> SqliteConnection {
+    let mut conn = SqliteConnection::establish(":memory:").expect("in-memory SQLite is available");
+    apply_test_migrations(&mut conn);
+    conn
+}
+
+/// Runs every migration's `up.sql` in order.
+#[cfg(test)]
+pub(crate) fn apply_test_migrations(conn: &mut SqliteConnection) {
+    for migration in test_migrations() {
+        apply_test_migration(conn, &migration);
+    }
+}
+
+/// Lists the migration directories, oldest first.
+#[cfg(test)]
+fn test_migrations() -> Vec<std::path::PathBuf> {
+    use std::fs;
+    use std::path::Path;
+
+    let mut migrations: Vec<_> = fs::read_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join("migrations"))
+        .expect("migrations directory is readable")
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_dir())
+        .collect();
+    migrations.sort();
+    migrations
+}
+
+#[cfg(test)]
+fn apply_test_migration(conn: &mut SqliteConnection, migration: &std::path::Path) {
+    let up = std::fs::read_to_string(migration.join("up.sql")).expect("migration has an up.sql");
+    conn.batch_execute(&up).expect("migration applies cleanly");
+}
+
+/// Inserts a user for tests and returns their ID.
+#[cfg(test)]
+pub(crate) fn insert_test_user(conn: &mut SqliteConnection, username: &str) -> i32 {
+    use crate::models::NewUser;
+    use crate::schema::users::dsl::{id, users};
+    use diesel::prelude::*;
+
+    diesel::insert_into(users)
+        .values(&NewUser {
+            username: username.to_string(),
+            hashed_password: "not-a-real-hash".to_string(),
+        })
+        .returning(id)
+        .get_result(conn)
+        .expect("test user is inserted")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -27,4 +110,67 @@ mod tests {
         let connection = establish_connection();
         assert!(connection.is_ok(), "Expected connection to succeed, but it failed.");
     }
+
+    #[test]
+    fn test_merge_migration_keeps_non_overlapping_sessions() {
+        use diesel::prelude::*;
+        use diesel::sql_types::{Integer, Nullable, Text};
+
+        let mut conn = SqliteConnection::establish(":memory:").unwrap();
+        let (before, after): (Vec<_>, Vec<_>) = test_migrations()
+            .into_iter()
+            .partition(|path| path.file_name().unwrap() < "2025-04-14-101500_merge_fasting_sessions");
+        for migration in &before {
+            apply_test_migration(&mut conn, migration);
+        }
+
+        conn.batch_execute(
+            "INSERT INTO users (id, username, hashed_password) VALUES (1, 'old-timer', 'not-a-real-hash');
+             INSERT INTO fasting_events (user_id, start_time, stop_time)
+                 VALUES (1, '2025-04-01 08:00:00', '2025-04-01 20:00:00');
+             INSERT INTO fasting_sessions (user_id, start_time, stop_time) VALUES
+                 (1, '2025-04-01 18:00:00', '2025-04-02 06:00:00'),
+                 (1, '2025-04-03 08:00:00', '2025-04-03 06:00:00'),
+                 (1, '2025-04-05 08:00:00', '2025-04-06 08:00:00'),
+                 (1, '2025-04-07 08:00:00', NULL),
+                 (1, '2025-04-08 08:00:00', NULL);",
+        )
+        .unwrap();
+
+        let (merge, later) = after.split_first().unwrap();
+        apply_test_migration(&mut conn, merge);
+
+        #[derive(QueryableByName, Debug, PartialEq)]
+        struct Fast {
+            #[diesel(sql_type = Integer)]
+            user_id: i32,
+            #[diesel(sql_type = Text)]
+            start_time: String,
+            #[diesel(sql_type = Nullable<Text>)]
+            stop_time: Option<String>,
+        }
+        let fast = |start: &str, stop: Option<&str>| Fast {
+            user_id: 1,
+            start_time: start.to_string(),
+            stop_time: stop.map(str::to_string),
+        };
+
+        // The overlapping, backwards and second open sessions are dropped
+        let merged = diesel::sql_query("SELECT user_id, start_time, stop_time FROM fasting_events ORDER BY start_time")
+            .load::<Fast>(&mut conn)
+            .unwrap();
+        assert_eq!(
+            merged,
+            vec![
+                fast("2025-04-01 08:00:00", Some("2025-04-01 20:00:00")),
+                fast("2025-04-05 08:00:00", Some("2025-04-06 08:00:00")),
+                fast("2025-04-07 08:00:00", None),
+            ]
+        );
+        assert!(diesel::sql_query("SELECT 1 FROM fasting_sessions").execute(&mut conn).is_err());
+
+        for migration in later {
+            apply_test_migration(&mut conn, migration);
+        }
+    }
 }
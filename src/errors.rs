@@ -1,6 +1,8 @@
+use chrono::NaiveDateTime;
 use thiserror::Error;
 use diesel::result::Error as DieselError;
 use bcrypt::BcryptError;
+use crate::users::password::{describe_violations, PasswordViolation};
 
 /// Centralized error type for the Fasting App.
 #[derive(Debug, Error)]
@@ -36,6 +38,98 @@ pub enum FastingAppError {
     /// Represents an error related to session handling.
     #[error("Session error: {0}")]
     SessionError(String),
+
+    /// Represents a fast whose stop time is not after its start time.
+    #[error("Stop time {stop} must be after start time {start}.")]
+    InvalidTimeRange { start: NaiveDateTime, stop: NaiveDateTime },
+
+    /// Represents a fasting timestamp that lies in the future.
+    #[error("Timestamp {0} is in the future.")]
+    FutureTimestamp(NaiveDateTime),
+
+    /// Represents a fast that would overlap another of the user's fasting events.
+    #[error("The fast overlaps existing fasting event {0}.")]
+    OverlappingFast(i32),
+
+    /// Represents a fasting event that does not exist or belongs to another user.
+    #[error("Fasting event {0} was not found.")]
+    EventNotFound(i32),
+
+    /// Represents a fasting protocol that does not exist or belongs to another user.
+    #[error("Fasting protocol {0} was not found.")]
+    ProtocolNotFound(i32),
+
+    /// Represents a schedule window that does not exist or belongs to another user.
+    #[error("Schedule window {0} was not found.")]
+    ScheduleNotFound(i32),
+
+    /// Represents a body measurement that does not exist or belongs to another user.
+    #[error("Body measurement {0} was not found.")]
+    MeasurementNotFound(i32),
+
+    /// Represents a fasting goal that does not exist.
+    #[error("Fasting goal {0} was not found.")]
+    GoalNotFound(i32),
+
+    /// Represents an attempt to use or change another user's fasting goal.
+    #[error("Fasting goal {0} belongs to another user.")]
+    GoalAccessDenied(i32),
+
+    /// Represents linking a fast to a goal whose deadline has already passed.
+    #[error("Fasting goal {goal_id} expired at {deadline}.")]
+    GoalDeadlinePassed { goal_id: i32, deadline: NaiveDateTime },
+
+    /// Represents a recurring goal that does not exist or belongs to another user.
+    #[error("Recurring goal {0} was not found.")]
+    RecurringGoalNotFound(i32),
+
+    /// Represents a fasting program that does not exist or belongs to another user.
+    #[error("Fasting program {0} was not found.")]
+    ProgramNotFound(i32),
+
+    /// Represents a program enrollment that does not exist or belongs to another user.
+    #[error("Program enrollment {0} was not found.")]
+    EnrollmentNotFound(i32),
+
+    /// Represents a timezone name that is not in the IANA database.
+    #[error("Unknown timezone '{0}'.")]
+    InvalidTimezone(String),
+
+    /// Represents a local time that does not exist, e.g. inside a DST gap.
+    #[error("{0} does not exist in timezone {1}.")]
+    NonexistentLocalTime(NaiveDateTime, String),
+
+    /// Represents an access or refresh token that is malformed, expired or revoked.
+    #[error("Invalid token: {0}")]
+    InvalidToken(String),
+
+    /// Represents a sign-in session that does not exist, is revoked, or belongs to another user.
+    #[error("Session {0} was not found.")]
+    AuthSessionNotFound(i32),
+
+    /// Represents a device pairing code that is unknown, expired or already redeemed.
+    #[error("Invalid or expired pairing code.")]
+    InvalidPairingCode,
+
+    /// Represents a paired device that does not exist, is revoked, or belongs to another user.
+    #[error("Device {0} was not found.")]
+    DeviceNotFound(i32),
+
+    /// Represents a username or device locked after too many failed sign-ins.
+    #[error("Too many failed sign-in attempts; locked until {retry_after}.")]
+    AccountLocked { retry_after: NaiveDateTime },
+
+    /// Represents a new password that breaks one or more password policy rules.
+    #[error("Weak password: {}", describe_violations(.0))]
+    WeakPassword(Vec<PasswordViolation>),
+
+    /// Represents an error reading or writing a file, e.g. during export.
+    #[error("File error: {0}")]
+    FileError(String),
+
+    /// Represents an error serializing data, e.g. to JSON.
+    #[error("Serialization error: {0}")]
+    SerializationError(String),
 }
 
 impl FastingAppError {
@@ -56,6 +150,67 @@ impl FastingAppError {
                 format!("Invalid credentials for '{}'.", identifier)
             }
             FastingAppError::SessionError(msg) => format!("Session error: {}", msg),
+            FastingAppError::InvalidTimeRange { .. } => {
+                "The fast must end after it starts.".to_string()
+            }
+            FastingAppError::FutureTimestamp(_) => "Fasts cannot be logged in the future.".to_string(),
+            FastingAppError::OverlappingFast(_) => {
+                "The fast overlaps one you have already logged.".to_string()
+            }
+            FastingAppError::EventNotFound(event_id) => {
+                format!("Fasting event {} was not found.", event_id)
+            }
+            FastingAppError::ProtocolNotFound(protocol_id) => {
+                format!("Fasting protocol {} was not found.", protocol_id)
+            }
+            FastingAppError::ScheduleNotFound(schedule_id) => {
+                format!("Schedule window {} was not found.", schedule_id)
+            }
+            FastingAppError::MeasurementNotFound(measurement_id) => {
+                format!("Body measurement {} was not found.", measurement_id)
+            }
+            FastingAppError::GoalNotFound(goal_id) => {
+                format!("Fasting goal {} was not found.", goal_id)
+            }
+            FastingAppError::GoalAccessDenied(_) => {
+                "You do not have access to that fasting goal.".to_string()
+            }
+            FastingAppError::GoalDeadlinePassed { goal_id, deadline } => {
+                format!("Fasting goal {} expired at {}; pick a goal that is still open.", goal_id, deadline)
+            }
+            FastingAppError::RecurringGoalNotFound(recurring_goal_id) => {
+                format!("Recurring goal {} was not found.", recurring_goal_id)
+            }
+            FastingAppError::ProgramNotFound(program_id) => {
+                format!("Fasting program {} was not found.", program_id)
+            }
+            FastingAppError::EnrollmentNotFound(enrollment_id) => {
+                format!("Program enrollment {} was not found.", enrollment_id)
+            }
+            FastingAppError::InvalidTimezone(name) => {
+                format!("'{}' is not a known timezone. Use a name like Europe/Berlin.", name)
+            }
+            FastingAppError::NonexistentLocalTime(local, timezone) => {
+                format!("{} is skipped by a clock change in {}. Pick another time.", local, timezone)
+            }
+            FastingAppError::InvalidToken(msg) => format!("{} Please sign in again.", msg),
+            FastingAppError::AuthSessionNotFound(session_id) => {
+                format!("Session {} was not found or is already signed out.", session_id)
+            }
+            FastingAppError::InvalidPairingCode => {
+                "That pairing code is invalid or has expired. Generate a new one and try again.".to_string()
+            }
+            FastingAppError::DeviceNotFound(device_id) => {
+                format!("Device {} was not found or is already revoked.", device_id)
+            }
+            FastingAppError::AccountLocked { retry_after } => {
+                format!("Too many failed sign-in attempts. Try again after {} UTC.", retry_after.format("%Y-%m-%d %H:%M:%S"))
+            }
+            FastingAppError::WeakPassword(violations) => {
+                format!("The password {}.", describe_violations(violations))
+            }
+            FastingAppError::FileError(_) => "Failed to read or write the file.".to_string(),
+            FastingAppError::SerializationError(_) => "Failed to serialize data.".to_string(),
         }
     }
 }
\ No newline at end of file
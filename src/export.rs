@@ -1,24 +1,108 @@
+use std::collections::HashMap;
 use std::fs::File;
-use std::io::{self, Write};
-use serde_json;
+use std::io::Write;
+use std::fmt::Display;
+use chrono::{SecondsFormat, TimeZone};
+use serde::Serialize;
 use crate::models::FastingEvent;
 use crate::errors::FastingAppError;
+use crate::clock::Clock;
+use crate::handlers::hydration::HydrationTotals;
+use crate::handlers::timezone::to_local;
 
-pub fn export_to_csv(events: &[FastingEvent], file_path: &str) -> Result<(), FastingAppError> {
-    let mut file = File::create(file_path).map_err(|_| FastingAppError::FileError)?;
-    writeln!(file, "start_time,stop_time,duration_minutes").map_err(|_| FastingAppError::FileError)?;
-    for event in events {
-        let duration = event
-            .stop_time
-            .unwrap_or(Utc::now().naive_utc())
-            .signed_duration_since(event.start_time)
-            .num_minutes();
-        writeln!(file, "{},{},{}", event.start_time, event.stop_time.unwrap_or(Utc::now().naive_utc()), duration)
-            .map_err(|_| FastingAppError::FileError)?;
+/// One exported fast, including its hydration totals. Times are local RFC 3339 with their offset.
+#[derive(Serialize)]
+struct ExportRow {
+    start_time: String,
+    stop_time: Option<String>,
+    duration_minutes: i64,
+    water_ml: i64,
+    electrolytes_ml: i64,
+    black_coffee_ml: i64,
+    tea_ml: i64,
+    total_fluids_ml: i64,
+}
+
+fn export_rows<Tz: TimeZone>(
+    events: &[FastingEvent],
+    hydration: &HashMap<i32, HydrationTotals>,
+    tz: &Tz,
+    clock: &dyn Clock,
+) -> Vec<ExportRow>
+where
+    Tz::Offset: Display,
+{
+    let now = clock.now();
+    let timestamp = |utc| to_local(tz, utc).to_rfc3339_opts(SecondsFormat::Secs, false);
+    events
+        .iter()
+        .map(|event| {
+            let fluids = hydration.get(&event.id).cloned().unwrap_or_default();
+            ExportRow {
+                start_time: timestamp(event.start_time),
+                stop_time: event.stop_time.map(timestamp),
+                duration_minutes: event
+                    .stop_time
+                    .unwrap_or(now)
+                    .signed_duration_since(event.start_time)
+                    .num_minutes(),
+                water_ml: fluids.water_ml,
+                electrolytes_ml: fluids.electrolytes_ml,
+                black_coffee_ml: fluids.black_coffee_ml,
+                tea_ml: fluids.tea_ml,
+                total_fluids_ml: fluids.total_ml,
+            }
+        })
+        .collect()
+}
+
+/// Writes fasts and their hydration totals to a CSV file. Ongoing fasts have an empty stop time.
+pub fn export_to_csv<Tz: TimeZone>(
+    events: &[FastingEvent],
+    hydration: &HashMap<i32, HydrationTotals>,
+    file_path: &str,
+    tz: &Tz,
+    clock: &dyn Clock,
+) -> Result<(), FastingAppError>
+where
+    Tz::Offset: Display,
+{
+    let file_error = |err: std::io::Error| FastingAppError::FileError(err.to_string());
+
+    let mut file = File::create(file_path).map_err(file_error)?;
+    writeln!(
+        file,
+        "start_time,stop_time,duration_minutes,water_ml,electrolytes_ml,black_coffee_ml,tea_ml,total_fluids_ml"
+    )
+    .map_err(file_error)?;
+    for row in export_rows(events, hydration, tz, clock) {
+        writeln!(
+            file,
+            "{},{},{},{},{},{},{},{}",
+            row.start_time,
+            row.stop_time.unwrap_or_default(),
+            row.duration_minutes,
+            row.water_ml,
+            row.electrolytes_ml,
+            row.black_coffee_ml,
+            row.tea_ml,
+            row.total_fluids_ml
+        )
+        .map_err(file_error)?;
     }
     Ok(())
 }
 
-pub fn export_to_json(events: &[FastingEvent]) -> Result<String, FastingAppError> {
-    serde_json::to_string(events).map_err(|_| FastingAppError::SerializationError)
+/// Serializes fasts and their hydration totals to a JSON array.
+pub fn export_to_json<Tz: TimeZone>(
+    events: &[FastingEvent],
+    hydration: &HashMap<i32, HydrationTotals>,
+    tz: &Tz,
+    clock: &dyn Clock,
+) -> Result<String, FastingAppError>
+where
+    Tz::Offset: Display,
+{
+    serde_json::to_string(&export_rows(events, hydration, tz, clock))
+        .map_err(|err| FastingAppError::SerializationError(err.to_string()))
 }
@@ -1,30 +1,70 @@
+use crate::clock::Clock;
 use crate::errors::FastingAppError;
-use crate::models::{FastingEvent, FastingSession};
+use crate::handlers::fasting::{
+    effective_fasting_minutes, event_target_minutes, get_user_fasting_events, load_pauses_by_event,
+};
+use crate::handlers::body_metrics::{
+    convert_unit, get_unit_preferences, list_measurements, MeasurementKind,
+};
+use crate::handlers::meals::list_break_fast_meals;
+use crate::handlers::protocols::list_protocols;
+use crate::handlers::timezone::{format_local, local_midnight_utc};
+use crate::models::{BreakFastMeal, FastingEvent, FastingPause};
 use crate::schema::fasting_events::dsl::{
     fasting_events, stop_time as event_stop_time, user_id as event_user_id,
 };
-use crate::schema::fasting_sessions::dsl::{fasting_sessions, user_id as session_user_id};
-use chrono::Utc;
+use chrono::{Datelike, Duration, Months, NaiveDate, NaiveDateTime, TimeZone};
 use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Double, Integer, Nullable, Text};
 use diesel::SqliteConnection;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::str::FromStr;
+
+/// Fasts longer than this count as extended when checking refeeds (48 hours).
+pub const EXTENDED_FAST_MINUTES: i64 = 48 * 60;
 
-/// Retrieves and displays the user's fasting history.
-pub fn show_fasting_history(conn: &mut SqliteConnection, user_id: i32) {
-    match get_fasting_sessions(conn, user_id) {
-        Ok(sessions) => {
+/// A break-fast meal at or above this many calories is a large refeed.
+pub const LARGE_REFEED_CALORIES: i32 = 1000;
+
+/// Fasts of at least this length count toward a streak even without a goal (16 hours).
+pub const DEFAULT_STREAK_THRESHOLD_MINUTES: i64 = 16 * 60;
+
+/// Completed-fast statistics for one fasting protocol.
+#[derive(Debug, Serialize)]
+pub struct ProtocolSummary {
+    /// `None` groups fasts started without a protocol.
+    pub protocol_id: Option<i32>,
+    pub protocol_name: String,
+    pub fast_count: usize,
+    pub total_minutes: i64,
+    pub average_minutes: i64,
+    /// Fasts that reached the protocol's fasting window.
+    pub target_met_count: usize,
+}
+
+/// Retrieves and displays the user's fasting history, most recent first.
+///
+/// - Durations exclude paused intervals; ongoing fasts are measured up to `clock.now()`.
+/// - Times are shown in `tz`.
+pub fn show_fasting_history<Tz: TimeZone>(conn: &mut SqliteConnection, user_id: i32, tz: &Tz, clock: &dyn Clock)
+where
+    Tz::Offset: std::fmt::Display,
+{
+    match get_fasting_history(conn, user_id, clock) {
+        Ok(history) => {
             println!("Fasting History:");
-            if sessions.is_empty() {
+            if history.is_empty() {
                 println!("No fasting history found for user ID: {}", user_id);
             } else {
-                for session in sessions {
-                    let end_time = session.stop_time.unwrap_or_else(|| Utc::now().naive_utc());
-                    let duration = end_time - session.start_time;
+                for (event, minutes) in history {
                     println!(
                         "- Start: {}, End: {}, Duration: {} minutes",
-                        session.start_time,
-                        session.stop_time
-                            .map_or_else(|| "Ongoing".to_string(), |end| end.to_string()),
-                        duration.num_minutes()
+                        format_local(tz, event.start_time),
+                        event.stop_time
+                            .map_or_else(|| "Ongoing".to_string(), |end| format_local(tz, end)),
+                        minutes
                     );
                 }
             }
@@ -33,16 +73,25 @@ pub fn show_fasting_history(conn: &mut SqliteConnection, user_id: i32) {
     }
 }
 
-/// Retrieves fasting sessions for a specific user.
-fn get_fasting_sessions(
+/// Retrieves the user's fasting events with their effective durations in minutes.
+pub(crate) fn get_fasting_history(
     conn: &mut SqliteConnection,
     user_id: i32,
-) -> Result<Vec<FastingSession>, FastingAppError> {
-    fasting_sessions
-        .filter(session_user_id.eq(user_id))
-        .select(FastingSession::as_select())
-        .load::<FastingSession>(conn)
-        .map_err(FastingAppError::DatabaseError)
+    clock: &dyn Clock,
+) -> Result<Vec<(FastingEvent, i64)>, FastingAppError> {
+    let events = get_user_fasting_events(conn, user_id)?;
+    let event_ids: Vec<i32> = events.iter().map(|event| event.id).collect();
+    let pauses = load_pauses_by_event(conn, &event_ids)?;
+    let now = clock.now();
+
+    Ok(events
+        .into_iter()
+        .map(|event| {
+            let event_pauses = pauses.get(&event.id).map(Vec::as_slice).unwrap_or(&[]);
+            let minutes = effective_fasting_minutes(&event, event_pauses, now);
+            (event, minutes)
+        })
+        .collect())
 }
 
 /// Calculates the average fasting duration for a specific user.
@@ -56,14 +105,7 @@ pub fn calculate_average_fasting_duration(
         return Ok(None);
     }
 
-    let total_duration: i64 = events
-        .iter()
-        .filter_map(|event| {
-            event
-                .stop_time
-                .map(|stop| stop.signed_duration_since(event.start_time).num_minutes())
-        })
-        .sum();
+    let total_duration = sum_effective_minutes(conn, &events)?;
 
     let event_count = events.len() as i64;
 
@@ -81,17 +123,703 @@ pub fn calculate_total_fasting_time(
     user_id: i32,
 ) -> Result<i64, FastingAppError> {
     let events = get_fasting_events_with_end_time(conn, user_id)?;
+    sum_effective_minutes(conn, &events)
+}
+
+/// Sums the fasting minutes of completed events, excluding paused intervals.
+fn sum_effective_minutes(
+    conn: &mut SqliteConnection,
+    events: &[FastingEvent],
+) -> Result<i64, FastingAppError> {
+    let event_ids: Vec<i32> = events.iter().map(|event| event.id).collect();
+    let pauses = load_pauses_by_event(conn, &event_ids)?;
+
+    Ok(events
+        .iter()
+        .map(|event| {
+            let event_pauses = pauses.get(&event.id).map(Vec::as_slice).unwrap_or(&[]);
+            completed_fasting_minutes(event, event_pauses)
+        })
+        .sum())
+}
+
+/// Minutes fasted in a completed event, excluding paused intervals.
+fn completed_fasting_minutes(event: &FastingEvent, pauses: &[FastingPause]) -> i64 {
+    effective_fasting_minutes(event, pauses, event.stop_time.unwrap_or(event.start_time))
+}
+
+/// Average journal scores for entries logged in one elapsed hour of a fast.
+#[derive(Debug, Serialize)]
+pub struct HourlyJournalStats {
+    /// Whole hours since the fast started (0 = first hour).
+    pub hour: i64,
+    pub entry_count: usize,
+    pub average_hunger: Option<f64>,
+    pub average_mood: Option<f64>,
+    pub average_energy: Option<f64>,
+}
+
+/// Correlates journal scores with the elapsed hour of the fast they were logged in.
+///
+/// - Covers journal entries from all of the user's fasts, ongoing or completed.
+/// - Elapsed time excludes pauses before the entry, like `effective_fasting_minutes`.
+/// - Hours without entries are omitted.
+pub fn journal_scores_by_fasting_hour(
+    conn: &mut SqliteConnection,
+    user_id: i32,
+) -> Result<Vec<HourlyJournalStats>, FastingAppError> {
+    use crate::models::FastJournalEntry;
+    use crate::schema::fast_journal_entries::dsl::fast_journal_entries;
+
+    let entries = fast_journal_entries
+        .inner_join(fasting_events)
+        .filter(event_user_id.eq(user_id))
+        .select((FastJournalEntry::as_select(), FastingEvent::as_select()))
+        .load::<(FastJournalEntry, FastingEvent)>(conn)
+        .map_err(FastingAppError::DatabaseError)?;
+    let event_ids: Vec<i32> = entries.iter().map(|(_, event)| event.id).collect();
+    let pauses = load_pauses_by_event(conn, &event_ids)?;
+
+    let mut by_hour: BTreeMap<i64, Vec<FastJournalEntry>> = BTreeMap::new();
+    for (entry, event) in entries {
+        let event_pauses = pauses.get(&event.id).map(Vec::as_slice).unwrap_or(&[]);
+        let until_entry = FastingEvent {
+            stop_time: Some(entry.entry_time),
+            ..event
+        };
+        let hour = effective_fasting_minutes(&until_entry, event_pauses, entry.entry_time) / 60;
+        by_hour.entry(hour).or_default().push(entry);
+    }
+
+    let average = |scores: Vec<i32>| {
+        (!scores.is_empty())
+            .then(|| f64::from(scores.iter().sum::<i32>()) / scores.len() as f64)
+    };
+
+    Ok(by_hour
+        .into_iter()
+        .map(|(hour, entries)| HourlyJournalStats {
+            hour,
+            entry_count: entries.len(),
+            average_hunger: average(entries.iter().filter_map(|e| e.hunger).collect()),
+            average_mood: average(entries.iter().filter_map(|e| e.mood).collect()),
+            average_energy: average(entries.iter().filter_map(|e| e.energy).collect()),
+        })
+        .collect())
+}
+
+/// Groups the user's completed fasts by protocol.
+///
+/// - Durations exclude paused intervals.
+/// - Fasts without a protocol are reported under "No protocol".
+pub fn summarize_fasts_by_protocol(
+    conn: &mut SqliteConnection,
+    user_id: i32,
+) -> Result<Vec<ProtocolSummary>, FastingAppError> {
+    let events = get_fasting_events_with_end_time(conn, user_id)?;
+    let event_ids: Vec<i32> = events.iter().map(|event| event.id).collect();
+    let pauses = load_pauses_by_event(conn, &event_ids)?;
+    let protocols = list_protocols(conn, user_id)?;
+
+    let mut groups: BTreeMap<Option<i32>, Vec<i64>> = BTreeMap::new();
+    for event in &events {
+        let event_pauses = pauses.get(&event.id).map(Vec::as_slice).unwrap_or(&[]);
+        groups
+            .entry(event.protocol_id)
+            .or_default()
+            .push(completed_fasting_minutes(event, event_pauses));
+    }
+
+    Ok(groups
+        .into_iter()
+        .map(|(protocol_id, durations)| {
+            let protocol = protocol_id
+                .and_then(|pid| protocols.iter().find(|protocol| protocol.id == pid));
+            let total_minutes: i64 = durations.iter().sum();
+            ProtocolSummary {
+                protocol_id,
+                protocol_name: protocol
+                    .map_or_else(|| "No protocol".to_string(), |p| p.name.clone()),
+                fast_count: durations.len(),
+                total_minutes,
+                average_minutes: total_minutes / durations.len() as i64,
+                target_met_count: protocol.map_or(0, |p| {
+                    durations
+                        .iter()
+                        .filter(|&&minutes| minutes >= p.target_fasting_minutes())
+                        .count()
+                }),
+            }
+        })
+        .collect())
+}
+
+/// Calculates the fasting time of completed fasts within `from`..`to`, in minutes.
+///
+/// - Fasts crossing either boundary only count the part inside the period.
+/// - Like `calculate_total_fasting_time`, paused intervals are excluded.
+pub fn calculate_fasting_time_between(
+    conn: &mut SqliteConnection,
+    user_id: i32,
+    from: NaiveDateTime,
+    to: NaiveDateTime,
+) -> Result<i64, FastingAppError> {
+    use crate::schema::fasting_events::dsl::start_time as event_start_time;
+
+    let events = fasting_events
+        .filter(event_user_id.eq(user_id))
+        .filter(event_stop_time.gt(from))
+        .filter(event_start_time.lt(to))
+        .select(FastingEvent::as_select())
+        .load::<FastingEvent>(conn)
+        .map_err(FastingAppError::DatabaseError)?;
+
+    let clipped: Vec<FastingEvent> = events
+        .iter()
+        .map(|event| FastingEvent {
+            start_time: event.start_time.max(from),
+            stop_time: event.stop_time.map(|stop| stop.min(to)),
+            ..*event
+        })
+        .collect();
+
+    sum_effective_minutes(conn, &clipped)
+}
+
+/// Weight change over a period, related to the fasting done in it.
+#[derive(Debug, Serialize)]
+pub struct WeightChangeReport {
+    /// The user's preferred weight unit, used for every value below.
+    pub unit: String,
+    pub start_weight: f64,
+    pub start_measured_at: NaiveDateTime,
+    pub end_weight: f64,
+    pub end_measured_at: NaiveDateTime,
+    /// Negative when weight was lost.
+    pub change: f64,
+    /// Completed fasting hours between the first and last weigh-in.
+    pub fasting_hours: f64,
+    /// Weight change per 100 fasting hours, if any fasting was done.
+    pub change_per_100_fasting_hours: Option<f64>,
+}
+
+/// Calculates the weight change between the first and last weigh-in within `from`..`to`.
+///
+/// - Returns `None` when fewer than two weigh-ins fall in the period.
+/// - Fasting hours come from the same completed-fast totals as `calculate_total_fasting_time`,
+///   restricted to the time between the two weigh-ins.
+pub fn calculate_weight_change(
+    conn: &mut SqliteConnection,
+    user_id: i32,
+    from: NaiveDateTime,
+    to: NaiveDateTime,
+) -> Result<Option<WeightChangeReport>, FastingAppError> {
+    let preferences = get_unit_preferences(conn, user_id)?;
+    let unit = preferences.weight_unit.as_str();
+
+    let weigh_ins: Vec<_> = list_measurements(conn, user_id, Some(MeasurementKind::Weight))?
+        .into_iter()
+        .filter(|m| m.measured_at >= from && m.measured_at <= to)
+        .collect();
+
+    let (first, last) = match weigh_ins.as_slice() {
+        [first, .., last] => (first, last),
+        _ => return Ok(None),
+    };
+
+    let start_weight = convert_unit(first.value, &first.unit, unit)?;
+    let end_weight = convert_unit(last.value, &last.unit, unit)?;
+    let fasting_minutes =
+        calculate_fasting_time_between(conn, user_id, first.measured_at, last.measured_at)?;
+    let fasting_hours = fasting_minutes as f64 / 60.0;
+    let change = end_weight - start_weight;
+
+    Ok(Some(WeightChangeReport {
+        unit: unit.to_string(),
+        start_weight,
+        start_measured_at: first.measured_at,
+        end_weight,
+        end_measured_at: last.measured_at,
+        change,
+        fasting_hours,
+        change_per_100_fasting_hours: (fasting_minutes > 0)
+            .then(|| change / fasting_hours * 100.0),
+    }))
+}
+
+/// How often the user has broken a fast with the same meal.
+#[derive(Debug, Serialize)]
+pub struct BreakFastPattern {
+    pub description: String,
+    pub meal_count: usize,
+    /// Average of the meals' calorie estimates, if any had one.
+    pub average_calories: Option<i32>,
+}
+
+/// A large meal eaten straight after an extended fast.
+#[derive(Debug, Serialize)]
+pub struct LargeRefeedWarning {
+    pub event_id: i32,
+    pub fasted_minutes: i64,
+    pub description: String,
+    pub calories_max: i32,
+}
+
+/// Groups the user's break-fast meals by description, most common first.
+///
+/// - Descriptions are compared case-insensitively and without surrounding whitespace.
+pub fn summarize_break_fast_patterns(
+    conn: &mut SqliteConnection,
+    user_id: i32,
+) -> Result<Vec<BreakFastPattern>, FastingAppError> {
+    let meals = list_break_fast_meals(conn, user_id)?;
+
+    let mut groups: BTreeMap<String, Vec<&BreakFastMeal>> = BTreeMap::new();
+    for meal in &meals {
+        groups
+            .entry(meal.description.trim().to_lowercase())
+            .or_default()
+            .push(meal);
+    }
+
+    let mut patterns: Vec<BreakFastPattern> = groups
+        .into_iter()
+        .map(|(description, group)| {
+            let estimates: Vec<i32> = group.iter().filter_map(|meal| meal.calorie_estimate()).collect();
+            BreakFastPattern {
+                description,
+                meal_count: group.len(),
+                average_calories: (!estimates.is_empty())
+                    .then(|| estimates.iter().sum::<i32>() / estimates.len() as i32),
+            }
+        })
+        .collect();
+
+    patterns.sort_by_key(|pattern| Reverse(pattern.meal_count));
+    Ok(patterns)
+}
+
+/// Flags break-fast meals that may be too large for the fast they ended.
+///
+/// - Only fasts longer than `EXTENDED_FAST_MINUTES`, excluding pauses, are checked.
+/// - Uses the top of the calorie range, so an uncertain estimate errs on the side of a warning.
+pub fn find_large_refeeds(
+    conn: &mut SqliteConnection,
+    user_id: i32,
+) -> Result<Vec<LargeRefeedWarning>, FastingAppError> {
+    let meals = list_break_fast_meals(conn, user_id)?;
+    let events: HashMap<i32, FastingEvent> = get_fasting_events_with_end_time(conn, user_id)?
+        .into_iter()
+        .map(|event| (event.id, event))
+        .collect();
+    let event_ids: Vec<i32> = events.keys().copied().collect();
+    let pauses = load_pauses_by_event(conn, &event_ids)?;
+
+    Ok(meals
+        .into_iter()
+        .filter_map(|meal| {
+            let event = events.get(&meal.event_id)?;
+            let event_pauses = pauses.get(&event.id).map(Vec::as_slice).unwrap_or(&[]);
+            let fasted_minutes = completed_fasting_minutes(event, event_pauses);
+            let calories_max = meal.calories_max.or(meal.calories_min)?;
+            is_large_refeed(fasted_minutes, calories_max).then_some(LargeRefeedWarning {
+                event_id: event.id,
+                fasted_minutes,
+                description: meal.description,
+                calories_max,
+            })
+        })
+        .collect())
+}
 
-    let total_duration: i64 = events
+/// Whether a meal of `calories` after `fasted_minutes` of fasting is a large refeed.
+fn is_large_refeed(fasted_minutes: i64, calories: i32) -> bool {
+    fasted_minutes > EXTENDED_FAST_MINUTES && calories >= LARGE_REFEED_CALORIES
+}
+
+/// Fasting streaks and personal records for a user.
+#[derive(Debug, Serialize)]
+pub struct StreakReport {
+    /// Consecutive days up to today (or yesterday, if today has no fast yet).
+    pub current_streak_days: u32,
+    pub longest_streak_days: u32,
+    /// Local date on which the longest streak ended.
+    pub longest_streak_end: Option<NaiveDate>,
+    pub records: PersonalRecords,
+}
+
+/// The user's best fasting results.
+#[derive(Debug, Serialize)]
+pub struct PersonalRecords {
+    pub longest_fast: Option<LongestFastRecord>,
+    pub best_week: Option<WeeklyFastingRecord>,
+    /// Longest run of fasts with a goal or protocol target that met it.
+    pub most_consecutive_goal_hits: u32,
+}
+
+/// The longest completed fast, excluding pauses.
+#[derive(Debug, Serialize)]
+pub struct LongestFastRecord {
+    pub event_id: i32,
+    pub start_time: NaiveDateTime,
+    pub minutes: i64,
+}
+
+/// The calendar week (Monday to Sunday, local time) with the most fasting.
+#[derive(Debug, Serialize)]
+pub struct WeeklyFastingRecord {
+    pub week_start: NaiveDate,
+    pub minutes: i64,
+}
+
+/// Calculates streaks of days with a qualifying fast, plus personal records.
+///
+/// - A completed fast qualifies if it lasts `threshold_minutes` or meets its goal or protocol target.
+/// - Each fast counts for the local day it ended in, using `tz`.
+/// - Durations exclude paused intervals; ongoing fasts are ignored.
+/// - The current streak runs up to the local day of `clock.now()`.
+pub fn calculate_streaks<Tz: TimeZone>(
+    conn: &mut SqliteConnection,
+    user_id: i32,
+    threshold_minutes: i64,
+    tz: &Tz,
+    clock: &dyn Clock,
+) -> Result<StreakReport, FastingAppError> {
+    let mut events = get_fasting_events_with_end_time(conn, user_id)?;
+    events.sort_by_key(|event| event.stop_time);
+    let event_ids: Vec<i32> = events.iter().map(|event| event.id).collect();
+    let pauses = load_pauses_by_event(conn, &event_ids)?;
+
+    let mut qualifying_days = BTreeSet::new();
+    let mut goal_hits = Vec::new();
+    let mut longest_fast: Option<LongestFastRecord> = None;
+    let mut weekly_minutes: BTreeMap<NaiveDate, i64> = BTreeMap::new();
+
+    for event in &events {
+        let event_pauses = pauses.get(&event.id).map(Vec::as_slice).unwrap_or(&[]);
+        let minutes = completed_fasting_minutes(event, event_pauses);
+        let target = event_target_minutes(conn, event)?;
+        let target_met = target.map(|target| minutes >= target);
+
+        if minutes >= threshold_minutes || target_met == Some(true) {
+            if let Some(stop) = event.stop_time {
+                qualifying_days.insert(tz.from_utc_datetime(&stop).date_naive());
+            }
+        }
+        goal_hits.extend(target_met);
+        if longest_fast.as_ref().is_none_or(|record| minutes > record.minutes) {
+            longest_fast = Some(LongestFastRecord {
+                event_id: event.id,
+                start_time: event.start_time,
+                minutes,
+            });
+        }
+        for (week_start, week_minutes) in minutes_by_local_week(event, event_pauses, tz) {
+            *weekly_minutes.entry(week_start).or_default() += week_minutes;
+        }
+    }
+
+    let today = tz.from_utc_datetime(&clock.now()).date_naive();
+    let (current_streak_days, longest_streak_days, longest_streak_end) =
+        streak_lengths(&qualifying_days, today);
+
+    Ok(StreakReport {
+        current_streak_days,
+        longest_streak_days,
+        longest_streak_end,
+        records: PersonalRecords {
+            longest_fast,
+            best_week: weekly_minutes
+                .into_iter()
+                .filter(|(_, minutes)| *minutes > 0)
+                .max_by_key(|(week_start, minutes)| (*minutes, Reverse(*week_start)))
+                .map(|(week_start, minutes)| WeeklyFastingRecord { week_start, minutes }),
+            most_consecutive_goal_hits: longest_run(&goal_hits),
+        },
+    })
+}
+
+/// Returns the current streak, the longest streak and the day the longest streak ended.
+fn streak_lengths(days: &BTreeSet<NaiveDate>, today: NaiveDate) -> (u32, u32, Option<NaiveDate>) {
+    let mut longest = 0;
+    let mut longest_end = None;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+
+    for &day in days {
+        run = match previous {
+            Some(prev) if day - prev == Duration::days(1) => run + 1,
+            _ => 1,
+        };
+        if run >= longest {
+            longest = run;
+            longest_end = Some(day);
+        }
+        previous = Some(day);
+    }
+
+    let yesterday = today - Duration::days(1);
+    let current = match previous {
+        Some(last) if last == today || last == yesterday => run,
+        _ => 0,
+    };
+    (current, longest, longest_end)
+}
+
+/// Length of the longest run of `true` values.
+fn longest_run(hits: &[bool]) -> u32 {
+    hits.iter()
+        .fold((0, 0), |(best, run), &hit| {
+            let run = if hit { run + 1 } else { 0 };
+            (best.max(run), run)
+        })
+        .0
+}
+
+/// Splits a fast's effective minutes across the local calendar weeks it spans.
+fn minutes_by_local_week<Tz: TimeZone>(
+    event: &FastingEvent,
+    pauses: &[FastingPause],
+    tz: &Tz,
+) -> Vec<(NaiveDate, i64)> {
+    let Some(stop) = event.stop_time else {
+        return Vec::new();
+    };
+
+    let start_date = tz.from_utc_datetime(&event.start_time).date_naive();
+    let mut week_start = start_date - Duration::days(i64::from(start_date.weekday().num_days_from_monday()));
+    let mut weeks = Vec::new();
+
+    loop {
+        let next_week = week_start + Duration::days(7);
+        let window_start = local_midnight_utc(tz, week_start).max(event.start_time);
+        let window_end = local_midnight_utc(tz, next_week).min(stop);
+        if window_start >= stop {
+            break;
+        }
+        if window_end > window_start {
+            let clipped = FastingEvent {
+                start_time: window_start,
+                stop_time: Some(window_end),
+                ..*event
+            };
+            weeks.push((week_start, effective_fasting_minutes(&clipped, pauses, window_end)));
+        }
+        week_start = next_week;
+    }
+    weeks
+}
+
+/// The length of the periods a report is split into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BucketSize {
+    Day,
+    /// Monday to Sunday.
+    Week,
+    Month,
+}
+
+impl FromStr for BucketSize {
+    type Err = FastingAppError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.trim().to_lowercase().as_str() {
+            "day" | "daily" => Ok(BucketSize::Day),
+            "week" | "weekly" => Ok(BucketSize::Week),
+            "month" | "monthly" => Ok(BucketSize::Month),
+            other => Err(FastingAppError::InvalidRequest(format!(
+                "Unknown period '{}'. Use day, week or month.",
+                other
+            ))),
+        }
+    }
+}
+
+/// Fasting statistics for one period of a report.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PeriodStats {
+    pub period_start: NaiveDate,
+    /// First day after the period.
+    pub period_end: NaiveDate,
+    /// Fasts with any fasting time in the period.
+    pub fast_count: i64,
+    pub total_minutes: i64,
+    pub mean_minutes: Option<f64>,
+    pub median_minutes: Option<f64>,
+    pub min_minutes: Option<i64>,
+    pub max_minutes: Option<i64>,
+}
+
+/// One row of the period aggregation query.
+#[derive(QueryableByName)]
+struct PeriodRow {
+    #[diesel(sql_type = Integer)]
+    bucket: i32,
+    #[diesel(sql_type = BigInt)]
+    fast_count: i64,
+    #[diesel(sql_type = BigInt)]
+    total_minutes: i64,
+    #[diesel(sql_type = Double)]
+    mean_minutes: f64,
+    #[diesel(sql_type = Nullable<Double>)]
+    median_minutes: Option<f64>,
+    #[diesel(sql_type = BigInt)]
+    min_minutes: i64,
+    #[diesel(sql_type = BigInt)]
+    max_minutes: i64,
+}
+
+/// Splits completed fasts into buckets and aggregates each one in SQL.
+///
+/// Binds a JSON array of `[bucket, start, end]` UTC boundaries, then the user ID.
+/// Each fast is clipped to every bucket it overlaps and its pauses are subtracted
+/// within the clipped part, so minutes are never counted twice.
+const PERIOD_AGGREGATION_SQL: &str = "
+WITH buckets AS (
+    SELECT json_extract(value, '$[0]') AS bucket,
+           json_extract(value, '$[1]') AS bucket_start,
+           json_extract(value, '$[2]') AS bucket_end
+    FROM json_each(?)
+),
+pieces AS (
+    SELECT b.bucket, e.id AS event_id,
+           MAX(e.start_time, b.bucket_start) AS piece_start,
+           MIN(e.stop_time, b.bucket_end) AS piece_end
+    FROM buckets b
+    JOIN fasting_events e
+      ON e.user_id = ?
+     AND e.stop_time IS NOT NULL
+     AND e.start_time < b.bucket_end
+     AND e.stop_time > b.bucket_start
+),
+piece_minutes AS (
+    SELECT p.bucket,
+           CAST(MAX(0,
+               ROUND((julianday(p.piece_end) - julianday(p.piece_start)) * 86400)
+               - COALESCE((
+                   SELECT SUM(MAX(0, ROUND((
+                       julianday(MIN(COALESCE(fp.resume_time, p.piece_end), p.piece_end))
+                       - julianday(MAX(fp.pause_time, p.piece_start))
+                   ) * 86400)))
+                   FROM fasting_pauses fp
+                   WHERE fp.event_id = p.event_id
+               ), 0)
+           ) / 60 AS INTEGER) AS minutes
+    FROM pieces p
+),
+ranked AS (
+    SELECT bucket, minutes,
+           ROW_NUMBER() OVER (PARTITION BY bucket ORDER BY minutes) AS position,
+           COUNT(*) OVER (PARTITION BY bucket) AS bucket_count
+    FROM piece_minutes
+)
+SELECT bucket,
+       COUNT(*) AS fast_count,
+       SUM(minutes) AS total_minutes,
+       AVG(minutes) AS mean_minutes,
+       AVG(CASE WHEN position IN ((bucket_count + 1) / 2, (bucket_count + 2) / 2) THEN minutes END)
+           AS median_minutes,
+       MIN(minutes) AS min_minutes,
+       MAX(minutes) AS max_minutes
+FROM ranked
+GROUP BY bucket
+ORDER BY bucket
+";
+
+/// Aggregates completed fasts into daily, weekly or monthly periods between `from` and `to`.
+///
+/// - Both dates are inclusive and periods follow local dates in `tz`; weeks and months
+///   are aligned to their calendar start, so the first period may begin before `from`.
+/// - A fast crossing a period boundary counts in each period it touches, with only the
+///   minutes inside that period. Paused time is excluded.
+/// - Periods without fasts are included with a count of zero.
+pub fn aggregate_fasting_by_period<Tz: TimeZone>(
+    conn: &mut SqliteConnection,
+    user_id: i32,
+    from: NaiveDate,
+    to: NaiveDate,
+    bucket_size: BucketSize,
+    tz: &Tz,
+) -> Result<Vec<PeriodStats>, FastingAppError> {
+    if to < from {
+        return Err(FastingAppError::InvalidRequest(
+            "The report must end on or after its start date.".to_string(),
+        ));
+    }
+
+    let periods = period_buckets(from, to, bucket_size);
+    let boundaries: Vec<(usize, String, String)> = periods
         .iter()
-        .filter_map(|event| {
-            event
-                .stop_time
-                .map(|stop| stop.signed_duration_since(event.start_time).num_minutes())
+        .enumerate()
+        .map(|(index, (start, end))| {
+            (
+                index,
+                local_midnight_utc(tz, *start).format("%Y-%m-%d %H:%M:%S").to_string(),
+                local_midnight_utc(tz, *end).format("%Y-%m-%d %H:%M:%S").to_string(),
+            )
         })
-        .sum();
+        .collect();
+    let boundaries_json = serde_json::to_string(&boundaries)
+        .map_err(|err| FastingAppError::SerializationError(err.to_string()))?;
+
+    let rows: HashMap<usize, PeriodRow> = diesel::sql_query(PERIOD_AGGREGATION_SQL)
+        .bind::<Text, _>(boundaries_json)
+        .bind::<Integer, _>(user_id)
+        .load::<PeriodRow>(conn)
+        .map_err(FastingAppError::DatabaseError)?
+        .into_iter()
+        .map(|row| (row.bucket as usize, row))
+        .collect();
 
-    Ok(total_duration)
+    Ok(periods
+        .into_iter()
+        .enumerate()
+        .map(|(index, (period_start, period_end))| match rows.get(&index) {
+            Some(row) => PeriodStats {
+                period_start,
+                period_end,
+                fast_count: row.fast_count,
+                total_minutes: row.total_minutes,
+                mean_minutes: Some(row.mean_minutes),
+                median_minutes: row.median_minutes,
+                min_minutes: Some(row.min_minutes),
+                max_minutes: Some(row.max_minutes),
+            },
+            None => PeriodStats {
+                period_start,
+                period_end,
+                fast_count: 0,
+                total_minutes: 0,
+                mean_minutes: None,
+                median_minutes: None,
+                min_minutes: None,
+                max_minutes: None,
+            },
+        })
+        .collect())
+}
+
+/// Lists the `[start, end)` date ranges of the periods covering `from..=to`.
+fn period_buckets(from: NaiveDate, to: NaiveDate, bucket_size: BucketSize) -> Vec<(NaiveDate, NaiveDate)> {
+    let first = match bucket_size {
+        BucketSize::Day => from,
+        BucketSize::Week => from - Duration::days(i64::from(from.weekday().num_days_from_monday())),
+        BucketSize::Month => from.with_day(1).expect("every month has a first day"),
+    };
+    let next = |start: NaiveDate| match bucket_size {
+        BucketSize::Day => start + Duration::days(1),
+        BucketSize::Week => start + Duration::days(7),
+        BucketSize::Month => start + Months::new(1),
+    };
+
+    let mut buckets = Vec::new();
+    let mut start = first;
+    while start <= to {
+        let end = next(start);
+        buckets.push((start, end));
+        start = end;
+    }
+    buckets
 }
 
 /// Retrieves fasting events with a valid `stop_time`.
@@ -106,3 +834,126 @@ fn get_fasting_events_with_end_time(
         .load::<FastingEvent>(conn)
         .map_err(FastingAppError::DatabaseError)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_streak_lengths_counts_consecutive_days() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 4, d).unwrap();
+        let days: BTreeSet<NaiveDate> = [1, 2, 3, 4, 8, 9].into_iter().map(day).collect();
+
+        assert_eq!(streak_lengths(&days, day(10)), (2, 4, Some(day(4))));
+        assert_eq!(streak_lengths(&days, day(12)).0, 0);
+    }
+
+    #[test]
+    fn test_period_buckets_align_to_calendar() {
+        let date = |m: u32, d: u32| NaiveDate::from_ymd_opt(2025, m, d).unwrap();
+
+        let weeks = period_buckets(date(4, 2), date(4, 14), BucketSize::Week);
+        assert_eq!(weeks.first(), Some(&(date(3, 31), date(4, 7))));
+        assert_eq!(weeks.len(), 3);
+
+        let months = period_buckets(date(1, 31), date(3, 1), BucketSize::Month);
+        assert_eq!(months, vec![(date(1, 1), date(2, 1)), (date(2, 1), date(3, 1)), (date(3, 1), date(4, 1))]);
+    }
+
+    #[test]
+    fn test_longest_run_of_goal_hits() {
+        assert_eq!(longest_run(&[true, true, false, true, true, true, false]), 3);
+        assert_eq!(longest_run(&[]), 0);
+    }
+
+    #[test]
+    fn test_large_refeed_requires_extended_fast() {
+        assert!(is_large_refeed(EXTENDED_FAST_MINUTES + 1, 1200));
+        assert!(!is_large_refeed(EXTENDED_FAST_MINUTES, 1200));
+        assert!(!is_large_refeed(72 * 60, 600));
+    }
+
+    #[test]
+    fn test_period_aggregation_splits_fasts_and_excludes_pauses() {
+        use crate::db::{insert_test_user, test_connection};
+        use crate::models::{NewFastingEvent, NewFastingPause};
+
+        let mut conn = test_connection();
+        let user = insert_test_user(&mut conn, "periods");
+        let at = |day: u32, hour: u32| NaiveDate::from_ymd_opt(2025, 4, day).unwrap().and_hms_opt(hour, 0, 0).unwrap();
+        let mut insert = |start: NaiveDateTime, stop: NaiveDateTime| {
+            diesel::insert_into(fasting_events)
+                .values(&NewFastingEvent {
+                    user_id: user,
+                    start_time: start,
+                    stop_time: Some(stop),
+                    created_at: None,
+                    goal_id: None,
+                    protocol_id: None,
+                })
+                .returning(crate::schema::fasting_events::dsl::id)
+                .get_result::<i32>(&mut conn)
+                .unwrap()
+        };
+        insert(at(1, 0), at(1, 1));
+        insert(at(1, 2), at(1, 6));
+        insert(at(1, 6), at(1, 16));
+        let overnight = insert(at(1, 20), at(2, 8));
+        diesel::insert_into(crate::schema::fasting_pauses::table)
+            .values(&NewFastingPause {
+                event_id: overnight,
+                pause_time: at(1, 22),
+                resume_time: Some(at(1, 23)),
+                created_at: None,
+            })
+            .execute(&mut conn)
+            .unwrap();
+
+        let days = aggregate_fasting_by_period(
+            &mut conn,
+            user,
+            NaiveDate::from_ymd_opt(2025, 4, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 4, 3).unwrap(),
+            BucketSize::Day,
+            &chrono_tz::UTC,
+        )
+        .unwrap();
+
+        // Day 1 holds 60, 240 and 600 minutes plus the overnight fast's 180 (four hours, one paused)
+        let first = &days[0];
+        assert_eq!((first.fast_count, first.total_minutes), (4, 1080));
+        assert_eq!((first.mean_minutes, first.median_minutes), (Some(270.0), Some(210.0)));
+        assert_eq!((first.min_minutes, first.max_minutes), (Some(60), Some(600)));
+
+        let second = &days[1];
+        assert_eq!((second.fast_count, second.total_minutes, second.median_minutes), (1, 480, Some(480.0)));
+        assert_eq!((days[2].fast_count, days[2].median_minutes), (0, None));
+    }
+
+    #[test]
+    fn test_journal_hours_exclude_paused_time() {
+        use crate::clock::FakeClock;
+        use crate::db::{insert_test_user, test_connection};
+        use crate::handlers::fasting::{pause_fasting, resume_fasting, start_fasting};
+        use crate::handlers::journal::{add_journal_entry, JournalEntryInput};
+
+        let mut conn = test_connection();
+        let user = insert_test_user(&mut conn, "journaling");
+        let at = |hour: u32, minute: u32| NaiveDate::from_ymd_opt(2025, 4, 1).unwrap().and_hms_opt(hour, minute, 0).unwrap();
+        let clock = FakeClock::new(at(4, 0));
+
+        start_fasting(&mut conn, user, at(0, 0), None, None, &clock).unwrap();
+        pause_fasting(&mut conn, user, at(1, 0), &clock).unwrap();
+        resume_fasting(&mut conn, user, at(3, 0), &clock).unwrap();
+        for (logged, hunger) in [(at(0, 30), 2), (at(2, 30), 4), (at(3, 30), 6)] {
+            let entry = JournalEntryInput { entry_time: Some(logged), hunger: Some(hunger), ..Default::default() };
+            add_journal_entry(&mut conn, user, None, entry, &clock).unwrap();
+        }
+
+        // 2:30 falls inside the pause and 3:30 is 90 fasted minutes in, so both land in hour 1
+        let hours = journal_scores_by_fasting_hour(&mut conn, user).unwrap();
+        let summary: Vec<(i64, usize, Option<f64>)> =
+            hours.iter().map(|stats| (stats.hour, stats.entry_count, stats.average_hunger)).collect();
+        assert_eq!(summary, vec![(0, 1, Some(2.0)), (1, 2, Some(5.0))]);
+    }
+}
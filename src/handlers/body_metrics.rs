@@ -0,0 +1,372 @@
+use crate::errors::FastingAppError;
+use crate::models::{BodyMeasurement, NewBodyMeasurement};
+use crate::schema::body_measurements::dsl::{
+    body_measurements, id, kind, measured_at, unit, user_id, value,
+};
+use chrono::{NaiveDateTime, Utc};
+use diesel::prelude::*;
+use diesel::SqliteConnection;
+use std::fmt;
+use std::str::FromStr;
+
+const LB_PER_KG: f64 = 2.204_622_621_8;
+const CM_PER_IN: f64 = 2.54;
+
+/// The kinds of body measurement that can be logged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeasurementKind {
+    Weight,
+    BodyFat,
+    Waist,
+}
+
+/// Units for weight measurements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeightUnit {
+    Kg,
+    Lb,
+}
+
+/// Units for length measurements such as waist size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LengthUnit {
+    Cm,
+    In,
+}
+
+/// The units a user prefers to enter and see measurements in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnitPreferences {
+    pub weight_unit: WeightUnit,
+    pub length_unit: LengthUnit,
+}
+
+impl MeasurementKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MeasurementKind::Weight => "weight",
+            MeasurementKind::BodyFat => "body_fat",
+            MeasurementKind::Waist => "waist",
+        }
+    }
+
+    /// ✅ The unit a new measurement is stored in when none is given.
+    pub fn preferred_unit(&self, preferences: &UnitPreferences) -> &'static str {
+        match self {
+            MeasurementKind::Weight => preferences.weight_unit.as_str(),
+            MeasurementKind::BodyFat => "%",
+            MeasurementKind::Waist => preferences.length_unit.as_str(),
+        }
+    }
+
+    /// ✅ Maps a unit entered for this kind to its stored spelling (e.g. "lbs" to "lb").
+    pub fn canonical_unit(&self, candidate: &str) -> Result<&'static str, FastingAppError> {
+        match self {
+            MeasurementKind::Weight => candidate.parse::<WeightUnit>().map(|u| u.as_str()),
+            MeasurementKind::Waist => candidate.parse::<LengthUnit>().map(|u| u.as_str()),
+            MeasurementKind::BodyFat if candidate.trim() == "%" => Ok("%"),
+            MeasurementKind::BodyFat => Err(FastingAppError::InvalidRequest(format!(
+                "'{}' is not a valid unit for body fat. Use %.",
+                candidate
+            ))),
+        }
+    }
+}
+
+impl WeightUnit {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WeightUnit::Kg => "kg",
+            WeightUnit::Lb => "lb",
+        }
+    }
+}
+
+impl LengthUnit {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LengthUnit::Cm => "cm",
+            LengthUnit::In => "in",
+        }
+    }
+}
+
+impl FromStr for MeasurementKind {
+    type Err = FastingAppError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.trim().to_lowercase().as_str() {
+            "weight" => Ok(MeasurementKind::Weight),
+            "body_fat" | "body fat" | "bodyfat" => Ok(MeasurementKind::BodyFat),
+            "waist" => Ok(MeasurementKind::Waist),
+            other => Err(FastingAppError::InvalidRequest(format!(
+                "Unknown measurement kind '{}'. Use weight, body_fat or waist.",
+                other
+            ))),
+        }
+    }
+}
+
+impl FromStr for WeightUnit {
+    type Err = FastingAppError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.trim().to_lowercase().as_str() {
+            "kg" => Ok(WeightUnit::Kg),
+            "lb" | "lbs" => Ok(WeightUnit::Lb),
+            other => Err(FastingAppError::InvalidRequest(format!(
+                "Unknown weight unit '{}'. Use kg or lb.",
+                other
+            ))),
+        }
+    }
+}
+
+impl FromStr for LengthUnit {
+    type Err = FastingAppError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.trim().to_lowercase().as_str() {
+            "cm" => Ok(LengthUnit::Cm),
+            "in" | "inch" | "inches" => Ok(LengthUnit::In),
+            other => Err(FastingAppError::InvalidRequest(format!(
+                "Unknown length unit '{}'. Use cm or in.",
+                other
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for MeasurementKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// ✅ Converts a measurement value between two units of the same dimension.
+///
+/// - Supports kg/lb, cm/in and the identity conversion for any unit.
+pub fn convert_unit(amount: f64, from: &str, to: &str) -> Result<f64, FastingAppError> {
+    match (from, to) {
+        (a, b) if a == b => Ok(amount),
+        ("kg", "lb") => Ok(amount * LB_PER_KG),
+        ("lb", "kg") => Ok(amount / LB_PER_KG),
+        ("cm", "in") => Ok(amount / CM_PER_IN),
+        ("in", "cm") => Ok(amount * CM_PER_IN),
+        _ => Err(FastingAppError::InvalidRequest(format!(
+            "Cannot convert from {} to {}.",
+            from, to
+        ))),
+    }
+}
+
+/// ✅ Reads the user's unit preferences.
+pub fn get_unit_preferences(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+) -> Result<UnitPreferences, FastingAppError> {
+    use crate::schema::users::dsl::{id as users_id, length_unit, users, weight_unit};
+
+    let (weight, length) = users
+        .filter(users_id.eq(user_id_input))
+        .select((weight_unit, length_unit))
+        .first::<(String, String)>(conn)
+        .map_err(FastingAppError::DatabaseError)?;
+
+    Ok(UnitPreferences {
+        weight_unit: weight.parse()?,
+        length_unit: length.parse()?,
+    })
+}
+
+/// ✅ Stores the user's unit preferences.
+pub fn set_unit_preferences(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    preferences: UnitPreferences,
+) -> Result<(), FastingAppError> {
+    use crate::schema::users::dsl::{id as users_id, length_unit, users, weight_unit};
+
+    diesel::update(users.filter(users_id.eq(user_id_input)))
+        .set((
+            weight_unit.eq(preferences.weight_unit.as_str()),
+            length_unit.eq(preferences.length_unit.as_str()),
+        ))
+        .execute(conn)
+        .map(|_| ())
+        .map_err(FastingAppError::DatabaseError)
+}
+
+/// ✅ Logs a body measurement.
+///
+/// - Without a unit, the user's preferred unit for the kind is used.
+/// - The value must be positive and the unit must suit the kind.
+pub fn add_measurement(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    measurement_kind: MeasurementKind,
+    amount: f64,
+    measurement_unit: Option<&str>,
+    taken_at: NaiveDateTime,
+) -> Result<(), FastingAppError> {
+    let stored_unit = match measurement_unit {
+        Some(given) => measurement_kind.canonical_unit(given)?,
+        None => {
+            let preferences = get_unit_preferences(conn, user_id_input)?;
+            measurement_kind.preferred_unit(&preferences)
+        }
+    };
+    validate_measurement(measurement_kind, amount)?;
+
+    let new_measurement = NewBodyMeasurement {
+        user_id: user_id_input,
+        kind: measurement_kind.as_str().to_string(),
+        value: amount,
+        unit: stored_unit.to_string(),
+        measured_at: taken_at,
+        created_at: Some(Utc::now().naive_utc()),
+    };
+
+    diesel::insert_into(body_measurements)
+        .values(&new_measurement)
+        .execute(conn)
+        .map(|_| ())
+        .map_err(FastingAppError::DatabaseError)
+}
+
+/// ✅ Lists the user's measurements, oldest first, optionally of one kind.
+pub fn list_measurements(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    measurement_kind: Option<MeasurementKind>,
+) -> Result<Vec<BodyMeasurement>, FastingAppError> {
+    let mut query = body_measurements
+        .filter(user_id.eq(user_id_input))
+        .order(measured_at.asc())
+        .select(BodyMeasurement::as_select())
+        .into_boxed();
+
+    if let Some(wanted) = measurement_kind {
+        query = query.filter(kind.eq(wanted.as_str()));
+    }
+
+    query
+        .load::<BodyMeasurement>(conn)
+        .map_err(FastingAppError::DatabaseError)
+}
+
+/// ✅ Corrects the value, unit or time of one of the user's measurements.
+pub fn update_measurement(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    measurement_id: i32,
+    amount: f64,
+    measurement_unit: &str,
+    taken_at: NaiveDateTime,
+) -> Result<(), FastingAppError> {
+    let existing = find_measurement(conn, user_id_input, measurement_id)?;
+    let measurement_kind: MeasurementKind = existing.kind.parse()?;
+    let stored_unit = measurement_kind.canonical_unit(measurement_unit)?;
+    validate_measurement(measurement_kind, amount)?;
+
+    diesel::update(body_measurements.filter(id.eq(existing.id)))
+        .set((value.eq(amount), unit.eq(stored_unit), measured_at.eq(taken_at)))
+        .execute(conn)
+        .map(|_| ())
+        .map_err(FastingAppError::DatabaseError)
+}
+
+/// ✅ Deletes one of the user's measurements.
+pub fn delete_measurement(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    measurement_id: i32,
+) -> Result<(), FastingAppError> {
+    let deleted = diesel::delete(
+        body_measurements
+            .filter(id.eq(measurement_id))
+            .filter(user_id.eq(user_id_input)),
+    )
+    .execute(conn)
+    .map_err(FastingAppError::DatabaseError)?;
+
+    if deleted == 0 {
+        return Err(FastingAppError::MeasurementNotFound(measurement_id));
+    }
+    Ok(())
+}
+
+/// Finds a measurement by ID, making sure it belongs to the user.
+fn find_measurement(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    measurement_id: i32,
+) -> Result<BodyMeasurement, FastingAppError> {
+    body_measurements
+        .filter(id.eq(measurement_id))
+        .filter(user_id.eq(user_id_input))
+        .select(BodyMeasurement::as_select())
+        .first::<BodyMeasurement>(conn)
+        .optional()
+        .map_err(FastingAppError::DatabaseError)?
+        .ok_or(FastingAppError::MeasurementNotFound(measurement_id))
+}
+
+/// Checks that a measurement value is positive and, for body fat, a valid percentage.
+fn validate_measurement(measurement_kind: MeasurementKind, amount: f64) -> Result<(), FastingAppError> {
+    if !amount.is_finite() || amount <= 0.0 {
+        return Err(FastingAppError::InvalidRequest(
+            "Measurement values must be positive.".to_string(),
+        ));
+    }
+    if measurement_kind == MeasurementKind::BodyFat && amount > 100.0 {
+        return Err(FastingAppError::InvalidRequest(
+            "Body fat cannot exceed 100%.".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        add_measurement, convert_unit, delete_measurement, list_measurements, update_measurement,
+        MeasurementKind,
+    };
+    use crate::db::{insert_test_user, test_connection};
+    use crate::errors::FastingAppError;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_unit_conversions_round_trip() {
+        let pounds = convert_unit(80.0, "kg", "lb").unwrap();
+        assert!((pounds - 176.37).abs() < 0.01);
+        assert!((convert_unit(pounds, "lb", "kg").unwrap() - 80.0).abs() < 1e-9);
+
+        let inches = convert_unit(81.28, "cm", "in").unwrap();
+        assert!((inches - 32.0).abs() < 1e-9);
+        assert!((convert_unit(inches, "in", "cm").unwrap() - 81.28).abs() < 1e-9);
+
+        assert_eq!(convert_unit(25.0, "%", "%").unwrap(), 25.0);
+        assert!(convert_unit(80.0, "kg", "cm").is_err());
+    }
+
+    #[test]
+    fn test_users_cannot_change_each_others_measurements() {
+        let mut conn = test_connection();
+        let alice = insert_test_user(&mut conn, "alice");
+        let bob = insert_test_user(&mut conn, "bob");
+        let taken_at = NaiveDate::from_ymd_opt(2025, 4, 1).unwrap().and_hms_opt(7, 0, 0).unwrap();
+        add_measurement(&mut conn, alice, MeasurementKind::Weight, 80.0, Some("kg"), taken_at).unwrap();
+        let measurement = list_measurements(&mut conn, alice, None).unwrap().remove(0);
+
+        let update = update_measurement(&mut conn, bob, measurement.id, 60.0, "kg", taken_at);
+        assert!(matches!(update, Err(FastingAppError::MeasurementNotFound(found)) if found == measurement.id));
+        let delete = delete_measurement(&mut conn, bob, measurement.id);
+        assert!(matches!(delete, Err(FastingAppError::MeasurementNotFound(found)) if found == measurement.id));
+
+        let unchanged = list_measurements(&mut conn, alice, None).unwrap();
+        assert_eq!(unchanged.len(), 1);
+        assert_eq!(unchanged[0].value, 80.0);
+    }
+}
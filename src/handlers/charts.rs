@@ -0,0 +1,286 @@
+use crate::clock::Clock;
+use crate::errors::FastingAppError;
+use crate::handlers::analytics::{aggregate_fasting_by_period, get_fasting_history, BucketSize};
+use crate::handlers::timezone::local_today;
+use chrono::{Datelike, Duration, NaiveDate, TimeZone};
+use diesel::SqliteConnection;
+use std::collections::BTreeMap;
+use std::env;
+use terminal_size::{terminal_size, Width};
+
+/// Longest daily chart, in days.
+pub const MAX_CHART_DAYS: u32 = 366;
+
+/// Width used when the terminal width is unknown.
+pub const DEFAULT_CHART_WIDTH: usize = 80;
+
+/// Charts are never drawn narrower than this.
+const MIN_CHART_WIDTH: usize = 40;
+
+/// Eighths of a block, used to draw bars at sub-character resolution.
+const PARTIAL_BLOCKS: [char; 8] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉', '█'];
+
+/// Heatmap cells from no fasting to a full day of fasting.
+const HEAT_LEVELS: [char; 5] = ['·', '░', '▒', '▓', '█'];
+
+/// Histogram buckets are this many hours wide.
+const HISTOGRAM_BIN_HOURS: i64 = 4;
+
+/// Fasts of this many hours or more share the last histogram bucket.
+const HISTOGRAM_MAX_HOURS: i64 = 48;
+
+/// ✅ Width of the terminal on stdout, falling back to the `COLUMNS` environment variable and
+/// then `DEFAULT_CHART_WIDTH` when output is not a terminal.
+pub fn terminal_width() -> usize {
+    terminal_size()
+        .map(|(Width(columns), _)| usize::from(columns))
+        .or_else(|| env::var("COLUMNS").ok().and_then(|columns| columns.trim().parse::<usize>().ok()))
+        .filter(|columns| *columns > 0)
+        .unwrap_or(DEFAULT_CHART_WIDTH)
+}
+
+/// ✅ Renders a bar chart of fasting hours for each of the last `days` local days.
+///
+/// - `days` must be between 1 and `MAX_CHART_DAYS`.
+pub fn daily_fasting_chart<Tz: TimeZone>(
+    conn: &mut SqliteConnection,
+    user_id: i32,
+    days: u32,
+    tz: &Tz,
+    width: usize,
+    clock: &dyn Clock,
+) -> Result<String, FastingAppError> {
+    if days == 0 || days > MAX_CHART_DAYS {
+        return Err(FastingAppError::InvalidRequest(format!(
+            "The chart covers between 1 and {} days.",
+            MAX_CHART_DAYS
+        )));
+    }
+
+    let today = local_today(tz, clock);
+    let from = today - Duration::days(i64::from(days) - 1);
+    let hours: Vec<(NaiveDate, f64)> =
+        aggregate_fasting_by_period(conn, user_id, from, today, BucketSize::Day, tz)?
+            .into_iter()
+            .map(|period| (period.period_start, period.total_minutes as f64 / 60.0))
+            .collect();
+
+    Ok(render_daily_bar_chart(&hours, width))
+}
+
+/// ✅ Renders a calendar heatmap of fasting hours per local day, as many weeks as fit.
+pub fn fasting_heatmap<Tz: TimeZone>(
+    conn: &mut SqliteConnection,
+    user_id: i32,
+    tz: &Tz,
+    width: usize,
+    clock: &dyn Clock,
+) -> Result<String, FastingAppError> {
+    let today = local_today(tz, clock);
+    let weeks = heatmap_weeks(width);
+    let this_monday = today - Duration::days(i64::from(today.weekday().num_days_from_monday()));
+    let from = this_monday - Duration::weeks(weeks as i64 - 1);
+
+    let hours: BTreeMap<NaiveDate, f64> =
+        aggregate_fasting_by_period(conn, user_id, from, today, BucketSize::Day, tz)?
+            .into_iter()
+            .map(|period| (period.period_start, period.total_minutes as f64 / 60.0))
+            .collect();
+
+    Ok(render_calendar_heatmap(&hours, today, width))
+}
+
+/// ✅ Renders a histogram of completed fast durations, excluding pauses.
+pub fn fast_duration_histogram(
+    conn: &mut SqliteConnection,
+    user_id: i32,
+    width: usize,
+    clock: &dyn Clock,
+) -> Result<String, FastingAppError> {
+    let durations: Vec<i64> = get_fasting_history(conn, user_id, clock)?
+        .into_iter()
+        .filter(|(event, _)| event.stop_time.is_some())
+        .map(|(_, minutes)| minutes)
+        .collect();
+
+    Ok(render_duration_histogram(&durations, width))
+}
+
+/// ✅ Draws one horizontal bar per day, scaled to the longest day.
+pub fn render_daily_bar_chart(hours_by_day: &[(NaiveDate, f64)], width: usize) -> String {
+    let label_width = "Mon 01 Jan │".chars().count();
+    let value_width = " 24.0h".len();
+    let bar_width = width.max(MIN_CHART_WIDTH) - label_width - value_width;
+    let max_hours = hours_by_day.iter().map(|(_, hours)| *hours).fold(0.0, f64::max);
+
+    hours_by_day
+        .iter()
+        .map(|(day, hours)| {
+            let bar = if max_hours > 0.0 {
+                render_bar(hours / max_hours * bar_width as f64)
+            } else {
+                String::new()
+            };
+            format!("{} │{:<bar_width$} {:>4.1}h\n", day.format("%a %d %b"), bar, hours)
+        })
+        .collect()
+}
+
+/// ✅ Draws a GitHub-style heatmap: one row per weekday, one column per week, ending at `today`.
+///
+/// - Cells are shaded by fasting hours: none, under 12, under 16, under 24, and 24 or more.
+/// - Days after `today` are left blank.
+pub fn render_calendar_heatmap(hours_by_day: &BTreeMap<NaiveDate, f64>, today: NaiveDate, width: usize) -> String {
+    let weeks = heatmap_weeks(width);
+    let this_monday = today - Duration::days(i64::from(today.weekday().num_days_from_monday()));
+    let first_monday = this_monday - Duration::weeks(weeks as i64 - 1);
+    let mondays: Vec<NaiveDate> = (0..weeks).map(|week| first_monday + Duration::weeks(week as i64)).collect();
+
+    // ✅ Month labels above the first week of each month, skipped when they would collide
+    let mut header = vec![' '; 4 + weeks * 2];
+    let mut next_free_column = 0;
+    for (week, monday) in mondays.iter().enumerate() {
+        let column = 4 + week * 2;
+        let starts_month = week == 0 || monday.month() != mondays[week - 1].month();
+        if starts_month && column >= next_free_column {
+            for (offset, letter) in monday.format("%b").to_string().chars().enumerate() {
+                if let Some(slot) = header.get_mut(column + offset) {
+                    *slot = letter;
+                }
+            }
+            next_free_column = column + 4;
+        }
+    }
+    let header: String = header.into_iter().collect();
+
+    let mut chart = format!("{}\n", header.trim_end());
+    for (weekday, label) in ["Mon", "", "Wed", "", "Fri", "", "Sun"].iter().enumerate() {
+        chart.push_str(&format!("{:<4}", label));
+        for monday in &mondays {
+            let day = *monday + Duration::days(weekday as i64);
+            if day > today {
+                chart.push_str("  ");
+                continue;
+            }
+            let hours = hours_by_day.get(&day).copied().unwrap_or(0.0);
+            chart.push(heat_level(hours));
+            chart.push(' ');
+        }
+        chart = chart.trim_end().to_string();
+        chart.push('\n');
+    }
+    chart.push_str(&format!(
+        "    less {} more\n",
+        HEAT_LEVELS.iter().map(char::to_string).collect::<Vec<_>>().join(" ")
+    ));
+    chart
+}
+
+/// ✅ Draws a histogram of fast durations in 4-hour buckets, with a final 48h+ bucket.
+pub fn render_duration_histogram(durations_minutes: &[i64], width: usize) -> String {
+    if durations_minutes.is_empty() {
+        return "No completed fasts yet.\n".to_string();
+    }
+
+    let bin_count = (HISTOGRAM_MAX_HOURS / HISTOGRAM_BIN_HOURS) as usize + 1;
+    let mut counts = vec![0usize; bin_count];
+    for minutes in durations_minutes {
+        let bin = (minutes / 60 / HISTOGRAM_BIN_HOURS).clamp(0, bin_count as i64 - 1) as usize;
+        counts[bin] += 1;
+    }
+
+    // ✅ Drop empty buckets above the longest fast so the chart stays compact
+    let last_used = counts.iter().rposition(|count| *count > 0).unwrap_or(0);
+    counts.truncate(last_used + 1);
+
+    let label_width = "48h+    │".chars().count();
+    let count_width = " 9999".len();
+    let bar_width = width.max(MIN_CHART_WIDTH) - label_width - count_width;
+    let max_count = counts.iter().copied().max().unwrap_or(0);
+
+    counts
+        .iter()
+        .enumerate()
+        .map(|(bin, count)| {
+            let low = bin as i64 * HISTOGRAM_BIN_HOURS;
+            let label = if bin == bin_count - 1 {
+                format!("{}h+", low)
+            } else {
+                format!("{}-{}h", low, low + HISTOGRAM_BIN_HOURS)
+            };
+            let bar = if max_count > 0 {
+                render_bar(*count as f64 / max_count as f64 * bar_width as f64)
+            } else {
+                String::new()
+            };
+            format!("{:<8}│{:<bar_width$} {:>4}\n", label, bar, count)
+        })
+        .collect()
+}
+
+/// Draws a bar `length` characters long, using partial blocks for the remainder.
+fn render_bar(length: f64) -> String {
+    let eighths = (length * 8.0).round() as usize;
+    let mut bar = PARTIAL_BLOCKS[7].to_string().repeat(eighths / 8);
+    if let Some(partial) = (eighths % 8).checked_sub(1) {
+        bar.push(PARTIAL_BLOCKS[partial]);
+    }
+    bar
+}
+
+/// Number of week columns that fit in `width`, leaving room for weekday labels.
+fn heatmap_weeks(width: usize) -> usize {
+    ((width.max(MIN_CHART_WIDTH) - 4) / 2).min(53)
+}
+
+/// Shade for a day with `hours` of fasting.
+fn heat_level(hours: f64) -> char {
+    match hours {
+        h if h <= 0.0 => HEAT_LEVELS[0],
+        h if h < 12.0 => HEAT_LEVELS[1],
+        h if h < 16.0 => HEAT_LEVELS[2],
+        h if h < 24.0 => HEAT_LEVELS[3],
+        _ => HEAT_LEVELS[4],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bar_chart_fits_width() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 4, d).unwrap();
+        let chart = render_daily_bar_chart(&[(day(1), 16.0), (day(2), 8.0), (day(3), 0.0)], 60);
+
+        for line in chart.lines() {
+            assert!(line.chars().count() <= 60, "line too wide: {}", line);
+        }
+        assert!(chart.lines().next().unwrap().contains('█'));
+        assert!(chart.lines().nth(2).unwrap().ends_with(" 0.0h"));
+    }
+
+    #[test]
+    fn test_histogram_buckets_durations() {
+        let chart = render_duration_histogram(&[17 * 60, 16 * 60 + 30, 3 * 60, 72 * 60], 60);
+        let lines: Vec<&str> = chart.lines().collect();
+
+        assert_eq!(lines.len(), 13);
+        assert!(lines[4].starts_with("16-20h") && lines[4].ends_with("   2"));
+        assert!(lines[12].starts_with("48h+") && lines[12].ends_with("   1"));
+    }
+
+    #[test]
+    fn test_daily_chart_rejects_out_of_range_days() {
+        let mut conn = crate::db::test_connection();
+        let clock = crate::clock::FakeClock::new(NaiveDate::from_ymd_opt(2025, 4, 1).unwrap().and_hms_opt(12, 0, 0).unwrap());
+
+        for days in [0, MAX_CHART_DAYS + 1, u32::MAX] {
+            assert!(matches!(
+                daily_fasting_chart(&mut conn, 1, days, &chrono_tz::UTC, 80, &clock),
+                Err(FastingAppError::InvalidRequest(_))
+            ));
+        }
+        assert!(daily_fasting_chart(&mut conn, 1, MAX_CHART_DAYS, &chrono_tz::UTC, 80, &clock).is_ok());
+    }
+}
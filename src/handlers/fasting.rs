@@ -1,80 +1,598 @@
+use crate::clock::Clock;
 use crate::errors::FastingAppError;
-use crate::models::{FastingEvent, FastingSession};
-use crate::schema::fasting_events::dsl::{fasting_events, id as event_id, goal_id as event_goal_id, stop_time, user_id as schema_user_id};
-use crate::schema::fasting_sessions::dsl::{fasting_sessions, user_id as session_user_id};
-use chrono::{NaiveDateTime, Utc};
+use crate::handlers::goals::validate_goal_link;
+use crate::handlers::meals::{insert_break_fast_meal, BreakFastMealInput};
+use crate::handlers::protocols::find_protocol;
+use crate::models::{FastingEvent, FastingGoal, FastingPause, NewFastingPause};
+use crate::schema::fasting_events::dsl::{
+    fasting_events, goal_id as event_goal_id, id as event_id, start_time, stop_time,
+    user_id as schema_user_id,
+};
+use crate::schema::fasting_pauses::dsl::{
+    event_id as pause_event_id, fasting_pauses, id as pause_id, pause_time, resume_time,
+};
+use chrono::NaiveDateTime;
 use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
 use diesel::SqliteConnection;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Snapshot of a user's ongoing fast.
+#[derive(Debug, Serialize)]
+pub struct FastingStatus {
+    pub event_id: i32,
+    pub start_time: NaiveDateTime,
+    /// Minutes fasted so far, excluding paused intervals.
+    pub elapsed_minutes: i64,
+    /// Set while the fast is paused.
+    pub paused_since: Option<NaiveDateTime>,
+    pub goal_id: Option<i32>,
+    pub protocol_id: Option<i32>,
+    /// Target length of the fast from its protocol or goal, in minutes.
+    pub target_minutes: Option<i64>,
+    /// Minutes left until the linked goal's duration is reached; zero once it is.
+    pub goal_remaining_minutes: Option<i64>,
+    pub goal_deadline: Option<NaiveDateTime>,
+}
 
 /// ✅ Starts fasting, with or without a goal.
+///
+/// - Runs inside an immediate transaction so two concurrent starts cannot both succeed.
+/// - Returns `ExistingSessionError` if the user already has an open fast.
+/// - Rejects start times in the future or inside an earlier logged fast.
+/// - With a protocol, the target duration of the fast follows from its fasting window.
 pub fn start_fasting(
     conn: &mut SqliteConnection,
     user_id: i32,
     event_start_time: NaiveDateTime,
     goal_id: Option<i32>, // ✅ New parameter for fasting goal
+    protocol_id: Option<i32>,
+    clock: &dyn Clock,
 ) -> Result<(), FastingAppError> {
     use crate::models::NewFastingEvent;
 
-    let new_event = NewFastingEvent {
-        user_id,
-        start_time: event_start_time,
-        stop_time: None,
-        created_at: Some(Utc::now().naive_utc()),
-        goal_id, // ✅ Store goal_id (if provided)
-    };
+    conn.immediate_transaction(|conn| {
+        let open_event = fasting_events
+            .filter(schema_user_id.eq(user_id))
+            .filter(stop_time.is_null())
+            .select(event_id)
+            .first::<i32>(conn)
+            .optional()
+            .map_err(FastingAppError::DatabaseError)?;
 
-    diesel::insert_into(fasting_events) // ✅ FIXED: Use table reference
-        .values(&new_event)
-        .execute(conn)
-        .map(|_| ())
-        .map_err(FastingAppError::DatabaseError)
+        if open_event.is_some() {
+            return Err(FastingAppError::ExistingSessionError(user_id));
+        }
+
+        let now = clock.now();
+        validate_fast_window(conn, user_id, event_start_time, None, None, now)?;
+        if let Some(chosen_protocol) = protocol_id {
+            find_protocol(conn, user_id, chosen_protocol)?;
+        }
+        if let Some(chosen_goal) = goal_id {
+            validate_goal_link(conn, user_id, chosen_goal, event_start_time)?;
+        }
+
+        let new_event = NewFastingEvent {
+            user_id,
+            start_time: event_start_time,
+            stop_time: None,
+            created_at: Some(now),
+            goal_id, // ✅ Store goal_id (if provided)
+            protocol_id,
+        };
+
+        diesel::insert_into(fasting_events) // ✅ FIXED: Use table reference
+            .values(&new_event)
+            .execute(conn)
+            .map(|_| ())
+            .map_err(|err| match err {
+                // ✅ The partial unique index on open events backs up the check above
+                DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                    FastingAppError::ExistingSessionError(user_id)
+                }
+                other => FastingAppError::DatabaseError(other),
+            })
+    })
 }
 
 /// ✅ Stops a fasting session for a user.
+///
+/// - Validates the finished window with `validate_fast_window`.
+/// - A pause that is still open is resumed at `event_end_time`, which must not be before it.
+/// - An optional break-fast meal is stored with the fast, eaten at `event_end_time`.
 pub fn stop_fasting(
     conn: &mut SqliteConnection,
     user_id: i32,
     event_end_time: NaiveDateTime,
+    break_fast_meal: Option<&BreakFastMealInput>,
+    clock: &dyn Clock,
 ) -> Result<(), FastingAppError> {
     use diesel::dsl::update;
 
-    let ongoing_event = find_ongoing_fasting_event(conn, user_id)?;
+    conn.transaction(|conn| {
+        let ongoing_event = find_ongoing_fasting_event(conn, user_id)?;
+
+        validate_fast_window(
+            conn,
+            user_id,
+            ongoing_event.start_time,
+            Some(event_end_time),
+            Some(ongoing_event.id),
+            clock.now(),
+        )?;
+        if let Some(open_pause) = find_open_pause(conn, ongoing_event.id)? {
+            if event_end_time < open_pause.pause_time {
+                return Err(FastingAppError::InvalidRequest(
+                    "A fast cannot stop before it was paused.".to_string(),
+                ));
+            }
+        }
+
+        if let Some(meal) = break_fast_meal {
+            insert_break_fast_meal(conn, ongoing_event.id, event_end_time, meal)?;
+        }
 
-    update(fasting_events.filter(event_id.eq(ongoing_event.id))) // ✅ FIXED: Use filter() instead of find()
-        .set(stop_time.eq(Some(event_end_time)))
+        update(
+            fasting_pauses
+                .filter(pause_event_id.eq(ongoing_event.id))
+                .filter(resume_time.is_null()),
+        )
+        .set(resume_time.eq(Some(event_end_time)))
         .execute(conn)
-        .map(|_| ())
+        .map_err(FastingAppError::DatabaseError)?;
+
+        update(fasting_events.filter(event_id.eq(ongoing_event.id))) // ✅ FIXED: Use filter() instead of find()
+            .set(stop_time.eq(Some(event_end_time)))
+            .execute(conn)
+            .map(|_| ())
+            .map_err(FastingAppError::DatabaseError)
+    })
+}
+
+/// ✅ Pauses the user's ongoing fast without ending it.
+///
+/// - Returns `SessionError` if there is no ongoing fast or it is already paused.
+/// - Returns `FutureTimestamp` if `pause_at` lies after `clock.now()`.
+pub fn pause_fasting(
+    conn: &mut SqliteConnection,
+    user_id: i32,
+    pause_at: NaiveDateTime,
+    clock: &dyn Clock,
+) -> Result<(), FastingAppError> {
+    let now = clock.now();
+    if pause_at > now {
+        return Err(FastingAppError::FutureTimestamp(pause_at));
+    }
+
+    conn.transaction(|conn| {
+        let ongoing_event = find_ongoing_fasting_event(conn, user_id)?;
+
+        if pause_at < ongoing_event.start_time {
+            return Err(FastingAppError::InvalidRequest(
+                "A pause cannot start before the fast started.".to_string(),
+            ));
+        }
+        if find_open_pause(conn, ongoing_event.id)?.is_some() {
+            return Err(FastingAppError::SessionError(
+                "The current fast is already paused.".to_string(),
+            ));
+        }
+
+        // ✅ A backdated pause must not overlap an earlier one, or that time is subtracted twice
+        let last_resume = fasting_pauses
+            .filter(pause_event_id.eq(ongoing_event.id))
+            .select(diesel::dsl::max(resume_time))
+            .first::<Option<NaiveDateTime>>(conn)
+            .map_err(FastingAppError::DatabaseError)?;
+        if let Some(last_resume) = last_resume.filter(|resumed| pause_at < *resumed) {
+            return Err(FastingAppError::InvalidRequest(format!(
+                "A pause cannot start before the fast was last resumed at {}.",
+                last_resume
+            )));
+        }
+
+        let new_pause = NewFastingPause {
+            event_id: ongoing_event.id,
+            pause_time: pause_at,
+            resume_time: None,
+            created_at: Some(now),
+        };
+
+        diesel::insert_into(fasting_pauses)
+            .values(&new_pause)
+            .execute(conn)
+            .map(|_| ())
+            .map_err(FastingAppError::DatabaseError)
+    })
+}
+
+/// ✅ Resumes the user's paused fast.
+///
+/// - Returns `SessionError` if there is no ongoing fast or it is not paused.
+/// - Returns `FutureTimestamp` if `resume_at` lies after `clock.now()`.
+pub fn resume_fasting(
+    conn: &mut SqliteConnection,
+    user_id: i32,
+    resume_at: NaiveDateTime,
+    clock: &dyn Clock,
+) -> Result<(), FastingAppError> {
+    if resume_at > clock.now() {
+        return Err(FastingAppError::FutureTimestamp(resume_at));
+    }
+
+    conn.transaction(|conn| {
+        let ongoing_event = find_ongoing_fasting_event(conn, user_id)?;
+
+        let open_pause = find_open_pause(conn, ongoing_event.id)?.ok_or_else(|| {
+            FastingAppError::SessionError("The current fast is not paused.".to_string())
+        })?;
+
+        if resume_at < open_pause.pause_time {
+            return Err(FastingAppError::InvalidRequest(
+                "A fast cannot be resumed before it was paused.".to_string(),
+            ));
+        }
+
+        diesel::update(fasting_pauses.filter(pause_id.eq(open_pause.id)))
+            .set(resume_time.eq(Some(resume_at)))
+            .execute(conn)
+            .map(|_| ())
+            .map_err(FastingAppError::DatabaseError)
+    })
+}
+
+/// ✅ Records a fast that already happened, e.g. one the user forgot to log.
+///
+/// - Validates the window with `validate_fast_window` before inserting.
+pub fn record_completed_fast(
+    conn: &mut SqliteConnection,
+    user_id: i32,
+    event_start_time: NaiveDateTime,
+    event_end_time: NaiveDateTime,
+    goal_id: Option<i32>,
+    protocol_id: Option<i32>,
+    clock: &dyn Clock,
+) -> Result<(), FastingAppError> {
+    use crate::models::NewFastingEvent;
+
+    conn.immediate_transaction(|conn| {
+        let now = clock.now();
+        validate_fast_window(conn, user_id, event_start_time, Some(event_end_time), None, now)?;
+        if let Some(chosen_protocol) = protocol_id {
+            find_protocol(conn, user_id, chosen_protocol)?;
+        }
+        if let Some(chosen_goal) = goal_id {
+            validate_goal_link(conn, user_id, chosen_goal, event_start_time)?;
+        }
+
+        let new_event = NewFastingEvent {
+            user_id,
+            start_time: event_start_time,
+            stop_time: Some(event_end_time),
+            created_at: Some(now),
+            goal_id,
+            protocol_id,
+        };
+
+        diesel::insert_into(fasting_events)
+            .values(&new_event)
+            .execute(conn)
+            .map(|_| ())
+            .map_err(FastingAppError::DatabaseError)
+    })
+}
+
+/// ✅ Changes the start and stop times of one of the user's fasting events.
+///
+/// - Passing `None` as the stop time is only allowed for the ongoing fast.
+/// - Returns `EventNotFound` if the event does not belong to the user.
+pub fn edit_fasting_event(
+    conn: &mut SqliteConnection,
+    user_id: i32,
+    fasting_event_id: i32,
+    new_start_time: NaiveDateTime,
+    new_stop_time: Option<NaiveDateTime>,
+    clock: &dyn Clock,
+) -> Result<(), FastingAppError> {
+    conn.immediate_transaction(|conn| {
+        let event = find_user_fasting_event(conn, user_id, fasting_event_id)?;
+
+        if event.stop_time.is_some() && new_stop_time.is_none() {
+            return Err(FastingAppError::InvalidRequest(
+                "A completed fast needs a stop time.".to_string(),
+            ));
+        }
+
+        let now = clock.now();
+        validate_fast_window(conn, user_id, new_start_time, new_stop_time, Some(event.id), now)?;
+
+        diesel::update(fasting_events.filter(event_id.eq(event.id)))
+            .set((start_time.eq(new_start_time), stop_time.eq(new_stop_time)))
+            .execute(conn)
+            .map(|_| ())
+            .map_err(FastingAppError::DatabaseError)
+    })
+}
+
+/// ✅ Deletes one of the user's fasting events together with its pauses and journal.
+///
+/// - Returns `EventNotFound` if the event does not belong to the user.
+pub fn delete_fasting_event(
+    conn: &mut SqliteConnection,
+    user_id: i32,
+    fasting_event_id: i32,
+) -> Result<(), FastingAppError> {
+    conn.transaction(|conn| {
+        let event = find_user_fasting_event(conn, user_id, fasting_event_id)?;
+
+        diesel::delete(fasting_pauses.filter(pause_event_id.eq(event.id)))
+            .execute(conn)
+            .map_err(FastingAppError::DatabaseError)?;
+
+        {
+            use crate::schema::break_fast_meals::dsl::{break_fast_meals, event_id as meal_event_id};
+            use crate::schema::fast_journal_entries::dsl::{event_id as journal_event_id, fast_journal_entries};
+            use crate::schema::fluid_intakes::dsl::{event_id as intake_event_id, fluid_intakes};
+
+            diesel::delete(break_fast_meals.filter(meal_event_id.eq(event.id)))
+                .execute(conn)
+                .map_err(FastingAppError::DatabaseError)?;
+
+            diesel::delete(fast_journal_entries.filter(journal_event_id.eq(event.id)))
+                .execute(conn)
+                .map_err(FastingAppError::DatabaseError)?;
+
+            // ✅ Drinks stay logged, just no longer attached to a fast
+            diesel::update(fluid_intakes.filter(intake_event_id.eq(event.id)))
+                .set(intake_event_id.eq(None::<i32>))
+                .execute(conn)
+                .map_err(FastingAppError::DatabaseError)?;
+        }
+
+        diesel::delete(fasting_events.filter(event_id.eq(event.id)))
+            .execute(conn)
+            .map(|_| ())
+            .map_err(FastingAppError::DatabaseError)
+    })
+}
+
+/// ✅ Retrieves all fasting events for a user, most recent first.
+pub fn get_user_fasting_events(
+    conn: &mut SqliteConnection,
+    user_id: i32,
+) -> Result<Vec<FastingEvent>, FastingAppError> {
+    fasting_events
+        .filter(schema_user_id.eq(user_id))
+        .order(start_time.desc())
+        .select(FastingEvent::as_select())
+        .load::<FastingEvent>(conn)
         .map_err(FastingAppError::DatabaseError)
 }
 
+/// ✅ Validates a fasting window before it is stored.
+///
+/// - `InvalidTimeRange` if the stop time is not after the start time.
+/// - `FutureTimestamp` if either time lies after `now`.
+/// - `OverlappingFast` if the window overlaps another of the user's events.
+///   An open window (no stop time) extends indefinitely, as does an ongoing fast.
+pub fn validate_fast_window(
+    conn: &mut SqliteConnection,
+    user_id: i32,
+    window_start: NaiveDateTime,
+    window_stop: Option<NaiveDateTime>,
+    exclude_event_id: Option<i32>,
+    now: NaiveDateTime,
+) -> Result<(), FastingAppError> {
+    if let Some(stop) = window_stop {
+        if stop <= window_start {
+            return Err(FastingAppError::InvalidTimeRange {
+                start: window_start,
+                stop,
+            });
+        }
+        if stop > now {
+            return Err(FastingAppError::FutureTimestamp(stop));
+        }
+    }
+    if window_start > now {
+        return Err(FastingAppError::FutureTimestamp(window_start));
+    }
+
+    let mut query = fasting_events
+        .filter(schema_user_id.eq(user_id))
+        .filter(stop_time.is_null().or(stop_time.gt(window_start)))
+        .select(event_id)
+        .into_boxed();
+
+    if let Some(stop) = window_stop {
+        query = query.filter(start_time.lt(stop));
+    }
+    if let Some(excluded) = exclude_event_id {
+        query = query.filter(event_id.ne(excluded));
+    }
+
+    match query.first::<i32>(conn).optional().map_err(FastingAppError::DatabaseError)? {
+        Some(conflicting_id) => Err(FastingAppError::OverlappingFast(conflicting_id)),
+        None => Ok(()),
+    }
+}
+
 /// ✅ Retrieves the current fasting status for a user.
+///
+/// - Returns `None` when the user has no ongoing fast.
+/// - `elapsed_minutes` excludes time spent paused and is measured up to `clock.now()`.
 pub fn get_current_fasting_status(
     conn: &mut SqliteConnection,
     user_id: i32,
-) -> Result<Option<(NaiveDateTime, i64)>, FastingAppError> {
-    let ongoing_event = find_ongoing_fasting_event(conn, user_id)?;
+    clock: &dyn Clock,
+) -> Result<Option<FastingStatus>, FastingAppError> {
+    let ongoing_event = fasting_events
+        .filter(schema_user_id.eq(user_id))
+        .filter(stop_time.is_null())
+        .select(FastingEvent::as_select())
+        .first::<FastingEvent>(conn)
+        .optional()
+        .map_err(FastingAppError::DatabaseError)?;
+
+    let Some(event) = ongoing_event else {
+        return Ok(None);
+    };
+
+    let pauses = load_pauses_for_event(conn, event.id)?;
+    let target_minutes = event_target_minutes(conn, &event)?;
+    let now = clock.now();
+    let elapsed_minutes = effective_fasting_minutes(&event, &pauses, now);
+
+    let linked_goal = match event.goal_id {
+        Some(linked_goal_id) => {
+            use crate::schema::fasting_goals::dsl::{fasting_goals, id as goal_table_id};
 
-    let duration = Utc::now().naive_utc() - ongoing_event.start_time;
-    Ok(Some((ongoing_event.start_time, duration.num_minutes())))
+            fasting_goals
+                .filter(goal_table_id.eq(linked_goal_id))
+                .select(FastingGoal::as_select())
+                .first::<FastingGoal>(conn)
+                .optional()
+                .map_err(FastingAppError::DatabaseError)?
+        }
+        None => None,
+    };
+
+    Ok(Some(FastingStatus {
+        event_id: event.id,
+        start_time: event.start_time,
+        elapsed_minutes,
+        paused_since: pauses
+            .iter()
+            .find(|pause| pause.resume_time.is_none())
+            .map(|pause| pause.pause_time),
+        goal_id: event.goal_id,
+        protocol_id: event.protocol_id,
+        target_minutes,
+        goal_remaining_minutes: linked_goal
+            .as_ref()
+            .map(|goal| (i64::from(goal.goal_duration) * 60 - elapsed_minutes).max(0)),
+        goal_deadline: linked_goal.map(|goal| goal.deadline),
+    }))
 }
 
-/// ✅ Retrieves all fasting sessions for a user.
-pub fn get_user_fasting_sessions(
+/// ✅ Resolves the target length of a fast, in minutes.
+///
+/// - A protocol's fasting window takes precedence over a linked goal's duration.
+/// - Returns `None` when the fast has neither.
+pub fn event_target_minutes(
     conn: &mut SqliteConnection,
-    user_id: i32,
-) -> Result<Vec<FastingSession>, FastingAppError> {
-    fasting_sessions
-        .filter(session_user_id.eq(user_id))
-        .select(FastingSession::as_select())
-        .load::<FastingSession>(conn)
+    event: &FastingEvent,
+) -> Result<Option<i64>, FastingAppError> {
+    use crate::schema::fasting_goals::dsl::{fasting_goals, goal_duration, id as goal_table_id};
+
+    if let Some(event_protocol_id) = event.protocol_id {
+        let protocol = find_protocol(conn, event.user_id, event_protocol_id)?;
+        return Ok(Some(protocol.target_fasting_minutes()));
+    }
+
+    let Some(linked_goal_id) = event.goal_id else {
+        return Ok(None);
+    };
+
+    let hours = fasting_goals
+        .filter(goal_table_id.eq(linked_goal_id))
+        .select(goal_duration)
+        .first::<i32>(conn)
+        .optional()
+        .map_err(FastingAppError::DatabaseError)?;
+
+    Ok(hours.map(|h| i64::from(h) * 60))
+}
+
+/// ✅ Calculates the minutes fasted in an event, excluding paused intervals.
+///
+/// - Ongoing events and open pauses are measured up to `until`.
+pub fn effective_fasting_minutes(
+    event: &FastingEvent,
+    pauses: &[FastingPause],
+    until: NaiveDateTime,
+) -> i64 {
+    let end = event.stop_time.unwrap_or(until);
+    let total = end.signed_duration_since(event.start_time).num_minutes();
+
+    let paused: i64 = pauses
+        .iter()
+        .filter(|pause| pause.event_id == event.id)
+        .map(|pause| {
+            let pause_start = pause.pause_time.max(event.start_time);
+            let pause_end = pause.resume_time.unwrap_or(end).min(end);
+            pause_end
+                .signed_duration_since(pause_start)
+                .num_minutes()
+                .max(0)
+        })
+        .sum();
+
+    (total - paused).max(0)
+}
+
+/// ✅ Loads the pauses of a single fasting event, oldest first.
+pub fn load_pauses_for_event(
+    conn: &mut SqliteConnection,
+    fasting_event_id: i32,
+) -> Result<Vec<FastingPause>, FastingAppError> {
+    fasting_pauses
+        .filter(pause_event_id.eq(fasting_event_id))
+        .order(pause_time.asc())
+        .select(FastingPause::as_select())
+        .load::<FastingPause>(conn)
         .map_err(FastingAppError::DatabaseError)
 }
 
+/// ✅ Loads the pauses for several fasting events, grouped by event ID.
+pub fn load_pauses_by_event(
+    conn: &mut SqliteConnection,
+    event_ids: &[i32],
+) -> Result<HashMap<i32, Vec<FastingPause>>, FastingAppError> {
+    let pauses = fasting_pauses
+        .filter(pause_event_id.eq_any(event_ids))
+        .order(pause_time.asc())
+        .select(FastingPause::as_select())
+        .load::<FastingPause>(conn)
+        .map_err(FastingAppError::DatabaseError)?;
+
+    let mut grouped: HashMap<i32, Vec<FastingPause>> = HashMap::new();
+    for pause in pauses {
+        grouped.entry(pause.event_id).or_default().push(pause);
+    }
+    Ok(grouped)
+}
+
 /// ✅ Updates the fasting goal **without resetting the fast timer**.
+///
+/// - A new goal must still be open at `clock.now()`.
 pub fn update_fasting_goal(
     conn: &mut SqliteConnection,
     user_id_input: i32,
     new_goal_id: Option<i32>,
+    clock: &dyn Clock,
+) -> Result<(), FastingAppError> {
+    if let Some(chosen_goal) = new_goal_id {
+        validate_goal_link(conn, user_id_input, chosen_goal, clock.now())?;
+    }
+    set_ongoing_fast_goal(conn, user_id_input, new_goal_id)
+}
+
+/// ✅ Removes the fasting goal **without stopping the fast**.
+pub fn remove_fasting_goal(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+) -> Result<(), FastingAppError> {
+    set_ongoing_fast_goal(conn, user_id_input, None)
+}
+
+/// Links the user's ongoing fast to `new_goal_id`, or unlinks it with `None`.
+fn set_ongoing_fast_goal(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    new_goal_id: Option<i32>,
 ) -> Result<(), FastingAppError> {
     let active_fast = fasting_events
         .filter(schema_user_id.eq(user_id_input))
@@ -101,16 +619,38 @@ pub fn update_fasting_goal(
     }
 }
 
-/// ✅ Removes the fasting goal **without stopping the fast**.
-pub fn remove_fasting_goal(
+/// ✅ Finds the open pause of a fasting event, if any.
+fn find_open_pause(
     conn: &mut SqliteConnection,
-    user_id_input: i32,
-) -> Result<(), FastingAppError> {
-    update_fasting_goal(conn, user_id_input, None)
+    fasting_event_id: i32,
+) -> Result<Option<FastingPause>, FastingAppError> {
+    fasting_pauses
+        .filter(pause_event_id.eq(fasting_event_id))
+        .filter(resume_time.is_null())
+        .select(FastingPause::as_select())
+        .first::<FastingPause>(conn)
+        .optional()
+        .map_err(FastingAppError::DatabaseError)
+}
+
+/// ✅ Finds a fasting event by ID, making sure it belongs to the user.
+pub(crate) fn find_user_fasting_event(
+    conn: &mut SqliteConnection,
+    user_id: i32,
+    fasting_event_id: i32,
+) -> Result<FastingEvent, FastingAppError> {
+    fasting_events
+        .filter(event_id.eq(fasting_event_id))
+        .filter(schema_user_id.eq(user_id))
+        .select(FastingEvent::as_select())
+        .first::<FastingEvent>(conn)
+        .optional()
+        .map_err(FastingAppError::DatabaseError)?
+        .ok_or(FastingAppError::EventNotFound(fasting_event_id))
 }
 
 /// ✅ Finds an ongoing fasting event for a user.
-fn find_ongoing_fasting_event(
+pub(crate) fn find_ongoing_fasting_event(
     conn: &mut SqliteConnection,
     user_id: i32,
 ) -> Result<FastingEvent, FastingAppError> {
@@ -120,3 +660,442 @@ fn find_ongoing_fasting_event(
         .first::<FastingEvent>(conn)
         .map_err(|_| FastingAppError::SessionError("No ongoing fasting session found.".to_string()))
 }
+
+/// Summary of the changes made by `repair_fasting_events`.
+#[derive(Debug, Default)]
+pub struct RepairReport {
+    /// Events removed because they duplicated another event's start time.
+    pub duplicates_removed: usize,
+    /// Events whose stop time was set or moved back to end before the next event.
+    pub events_closed: usize,
+}
+
+/// ✅ Repairs overlapping and duplicate fasting events for every user.
+///
+/// - Events sharing a user and start time are merged into the oldest row; the duplicate's
+///   journal entries, drinks, meal and pauses move to that row (see `move_event_children`).
+/// - An event that is still open, or runs past the start of the user's next event,
+///   is closed at the next event's start time.
+/// - Leaves at most one open event per user, as required by the partial unique index.
+pub fn repair_fasting_events(conn: &mut SqliteConnection) -> Result<RepairReport, FastingAppError> {
+    use crate::schema::fasting_events::dsl::protocol_id as event_protocol_id;
+
+    conn.immediate_transaction(|conn| {
+        let events = fasting_events
+            .order((schema_user_id.asc(), start_time.asc(), event_id.asc()))
+            .select(FastingEvent::as_select())
+            .load::<FastingEvent>(conn)
+            .map_err(FastingAppError::DatabaseError)?;
+
+        let mut report = RepairReport::default();
+        let mut previous: Option<FastingEvent> = None;
+
+        for event in events {
+            let Some(mut kept) = previous.take() else {
+                previous = Some(event);
+                continue;
+            };
+
+            if kept.user_id != event.user_id {
+                previous = Some(event);
+                continue;
+            }
+
+            if kept.start_time == event.start_time {
+                // ✅ Duplicate: keep the earliest row, preferring a known stop time and goal
+                let merged_stop = match (kept.stop_time, event.stop_time) {
+                    (Some(a), Some(b)) => Some(a.max(b)),
+                    (a, b) => a.or(b),
+                };
+                let merged_goal = kept.goal_id.or(event.goal_id);
+                let merged_protocol = kept.protocol_id.or(event.protocol_id);
+
+                move_event_children(conn, event.id, kept.id)?;
+                diesel::delete(fasting_events.filter(event_id.eq(event.id)))
+                    .execute(conn)
+                    .map_err(FastingAppError::DatabaseError)?;
+                diesel::update(fasting_events.filter(event_id.eq(kept.id)))
+                    .set((
+                        stop_time.eq(merged_stop),
+                        event_goal_id.eq(merged_goal),
+                        event_protocol_id.eq(merged_protocol),
+                    ))
+                    .execute(conn)
+                    .map_err(FastingAppError::DatabaseError)?;
+
+                kept.stop_time = merged_stop;
+                kept.goal_id = merged_goal;
+                kept.protocol_id = merged_protocol;
+                report.duplicates_removed += 1;
+                previous = Some(kept);
+                continue;
+            }
+
+            let overlaps = kept.stop_time.is_none_or(|stop| stop > event.start_time);
+            if overlaps {
+                diesel::update(fasting_events.filter(event_id.eq(kept.id)))
+                    .set(stop_time.eq(Some(event.start_time)))
+                    .execute(conn)
+                    .map_err(FastingAppError::DatabaseError)?;
+                report.events_closed += 1;
+            }
+
+            previous = Some(event);
+        }
+
+        Ok(report)
+    })
+}
+
+/// Moves the rows attached to a duplicate event onto the event it is merged into.
+///
+/// - Journal entries and drinks are always moved.
+/// - The break-fast meal and pauses are moved only if the kept event has none; otherwise they
+///   describe the same fast twice and are deleted, so paused time is not subtracted twice.
+fn move_event_children(conn: &mut SqliteConnection, from: i32, to: i32) -> Result<(), FastingAppError> {
+    use crate::schema::break_fast_meals::dsl::{break_fast_meals, event_id as meal_event_id};
+    use crate::schema::fast_journal_entries::dsl::{event_id as journal_event_id, fast_journal_entries};
+    use crate::schema::fluid_intakes::dsl::{event_id as intake_event_id, fluid_intakes};
+
+    diesel::update(fast_journal_entries.filter(journal_event_id.eq(from)))
+        .set(journal_event_id.eq(to))
+        .execute(conn)
+        .map_err(FastingAppError::DatabaseError)?;
+    diesel::update(fluid_intakes.filter(intake_event_id.eq(Some(from))))
+        .set(intake_event_id.eq(Some(to)))
+        .execute(conn)
+        .map_err(FastingAppError::DatabaseError)?;
+
+    let kept_has_meal = diesel::select(diesel::dsl::exists(break_fast_meals.filter(meal_event_id.eq(to))))
+        .get_result::<bool>(conn)
+        .map_err(FastingAppError::DatabaseError)?;
+    if kept_has_meal {
+        diesel::delete(break_fast_meals.filter(meal_event_id.eq(from)))
+            .execute(conn)
+            .map_err(FastingAppError::DatabaseError)?;
+    } else {
+        diesel::update(break_fast_meals.filter(meal_event_id.eq(from)))
+            .set(meal_event_id.eq(to))
+            .execute(conn)
+            .map_err(FastingAppError::DatabaseError)?;
+    }
+
+    let kept_has_pauses = diesel::select(diesel::dsl::exists(fasting_pauses.filter(pause_event_id.eq(to))))
+        .get_result::<bool>(conn)
+        .map_err(FastingAppError::DatabaseError)?;
+    if kept_has_pauses {
+        diesel::delete(fasting_pauses.filter(pause_event_id.eq(from)))
+            .execute(conn)
+            .map(|_| ())
+            .map_err(FastingAppError::DatabaseError)
+    } else {
+        diesel::update(fasting_pauses.filter(pause_event_id.eq(from)))
+            .set(pause_event_id.eq(to))
+            .execute(conn)
+            .map(|_| ())
+            .map_err(FastingAppError::DatabaseError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FakeClock;
+    use crate::db::{insert_test_user, test_connection};
+    use chrono::{Duration, NaiveDate};
+
+    fn at(hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 3, 20)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    fn event(stop: Option<NaiveDateTime>) -> FastingEvent {
+        FastingEvent {
+            id: 1,
+            user_id: 1,
+            start_time: at(6, 0),
+            stop_time: stop,
+            created_at: None,
+            goal_id: None,
+            protocol_id: None,
+        }
+    }
+
+    fn pause(from: NaiveDateTime, to: Option<NaiveDateTime>) -> FastingPause {
+        FastingPause {
+            id: 1,
+            event_id: 1,
+            pause_time: from,
+            resume_time: to,
+            created_at: None,
+        }
+    }
+
+    #[test]
+    fn test_effective_minutes_excludes_closed_pauses() {
+        let pauses = [pause(at(8, 0), Some(at(8, 30)))];
+        assert_eq!(effective_fasting_minutes(&event(Some(at(12, 0))), &pauses, at(23, 0)), 330);
+    }
+
+    #[test]
+    fn test_effective_minutes_stops_counting_while_paused() {
+        let pauses = [pause(at(10, 0), None)];
+        assert_eq!(effective_fasting_minutes(&event(None), &pauses, at(11, 0)), 240);
+    }
+
+    #[test]
+    fn test_ongoing_fast_is_measured_up_to_the_clock() {
+        let mut conn = test_connection();
+        let user = insert_test_user(&mut conn, "clocked");
+        let clock = FakeClock::new(at(6, 0));
+
+        start_fasting(&mut conn, user, clock.now(), None, None, &clock).unwrap();
+        clock.advance(Duration::minutes(90));
+        let status = get_current_fasting_status(&mut conn, user, &clock).unwrap().unwrap();
+        assert_eq!(status.elapsed_minutes, 90);
+
+        pause_fasting(&mut conn, user, clock.now(), &clock).unwrap();
+        clock.advance(Duration::hours(2));
+        let status = get_current_fasting_status(&mut conn, user, &clock).unwrap().unwrap();
+        assert_eq!(status.elapsed_minutes, 90);
+        assert_eq!(status.paused_since, Some(at(7, 30)));
+    }
+
+    #[test]
+    fn test_fasts_cannot_start_after_the_clock() {
+        let mut conn = test_connection();
+        let user = insert_test_user(&mut conn, "early");
+        let clock = FakeClock::new(at(6, 0));
+
+        assert!(start_fasting(&mut conn, user, at(6, 5), None, None, &clock).is_err());
+        clock.set(at(6, 5));
+        assert!(start_fasting(&mut conn, user, at(6, 5), None, None, &clock).is_ok());
+    }
+
+    #[test]
+    fn test_stop_time_must_fit_the_fast() {
+        let mut conn = test_connection();
+        let user = insert_test_user(&mut conn, "stopper");
+        let clock = FakeClock::new(at(12, 0));
+
+        start_fasting(&mut conn, user, at(6, 0), None, None, &clock).unwrap();
+        pause_fasting(&mut conn, user, at(10, 0), &clock).unwrap();
+        assert!(matches!(
+            stop_fasting(&mut conn, user, at(5, 0), None, &clock),
+            Err(FastingAppError::InvalidTimeRange { .. })
+        ));
+        assert!(matches!(
+            stop_fasting(&mut conn, user, at(13, 0), None, &clock),
+            Err(FastingAppError::FutureTimestamp(_))
+        ));
+        assert!(matches!(
+            stop_fasting(&mut conn, user, at(9, 0), None, &clock),
+            Err(FastingAppError::InvalidRequest(_))
+        ));
+
+        stop_fasting(&mut conn, user, at(11, 0), None, &clock).unwrap();
+        let stopped = get_user_fasting_events(&mut conn, user).unwrap()[0].id;
+        let pauses = load_pauses_for_event(&mut conn, stopped).unwrap();
+        assert_eq!(pauses[0].resume_time, Some(at(11, 0)));
+    }
+
+    #[test]
+    fn test_pauses_and_resumes_cannot_be_in_the_future() {
+        let mut conn = test_connection();
+        let user = insert_test_user(&mut conn, "ahead");
+        let clock = FakeClock::new(at(8, 0));
+
+        start_fasting(&mut conn, user, at(6, 0), None, None, &clock).unwrap();
+        assert!(matches!(
+            pause_fasting(&mut conn, user, at(8, 1), &clock),
+            Err(FastingAppError::FutureTimestamp(_))
+        ));
+        pause_fasting(&mut conn, user, at(8, 0), &clock).unwrap();
+        assert!(matches!(
+            resume_fasting(&mut conn, user, at(8, 1), &clock),
+            Err(FastingAppError::FutureTimestamp(_))
+        ));
+        assert!(resume_fasting(&mut conn, user, at(8, 0), &clock).is_ok());
+    }
+
+    #[test]
+    fn test_recorded_fast_must_stop_after_it_starts() {
+        let mut conn = test_connection();
+        let user = insert_test_user(&mut conn, "backwards");
+        let clock = FakeClock::new(at(23, 0));
+
+        assert!(matches!(
+            record_completed_fast(&mut conn, user, at(12, 0), at(12, 0), None, None, &clock),
+            Err(FastingAppError::InvalidTimeRange { .. })
+        ));
+        assert!(matches!(
+            record_completed_fast(&mut conn, user, at(12, 0), at(6, 0), None, None, &clock),
+            Err(FastingAppError::InvalidTimeRange { .. })
+        ));
+    }
+
+    #[test]
+    fn test_recorded_fast_cannot_end_in_the_future() {
+        let mut conn = test_connection();
+        let user = insert_test_user(&mut conn, "ahead");
+        let clock = FakeClock::new(at(12, 0));
+
+        assert!(matches!(
+            record_completed_fast(&mut conn, user, at(6, 0), at(13, 0), None, None, &clock),
+            Err(FastingAppError::FutureTimestamp(stop)) if stop == at(13, 0)
+        ));
+        assert!(record_completed_fast(&mut conn, user, at(6, 0), at(12, 0), None, None, &clock).is_ok());
+    }
+
+    #[test]
+    fn test_recorded_fast_cannot_overlap_another() {
+        let mut conn = test_connection();
+        let user = insert_test_user(&mut conn, "overlapping");
+        let other = insert_test_user(&mut conn, "someone-else");
+        let clock = FakeClock::new(at(23, 0));
+
+        record_completed_fast(&mut conn, user, at(6, 0), at(12, 0), None, None, &clock).unwrap();
+        let existing = get_user_fasting_events(&mut conn, user).unwrap()[0].id;
+        assert!(matches!(
+            record_completed_fast(&mut conn, user, at(11, 0), at(14, 0), None, None, &clock),
+            Err(FastingAppError::OverlappingFast(id)) if id == existing
+        ));
+        // Touching end to start is fine, and other users' fasts never conflict
+        assert!(record_completed_fast(&mut conn, user, at(12, 0), at(14, 0), None, None, &clock).is_ok());
+        assert!(record_completed_fast(&mut conn, other, at(6, 0), at(12, 0), None, None, &clock).is_ok());
+
+        // An ongoing fast extends indefinitely
+        start_fasting(&mut conn, user, at(20, 0), None, None, &clock).unwrap();
+        assert!(matches!(
+            validate_fast_window(&mut conn, user, at(21, 0), Some(at(22, 0)), None, clock.now()),
+            Err(FastingAppError::OverlappingFast(_))
+        ));
+    }
+
+    #[test]
+    fn test_editing_a_fast_only_checks_overlap_with_other_fasts() {
+        let mut conn = test_connection();
+        let user = insert_test_user(&mut conn, "editor");
+        let clock = FakeClock::new(at(23, 0));
+
+        record_completed_fast(&mut conn, user, at(6, 0), at(12, 0), None, None, &clock).unwrap();
+        record_completed_fast(&mut conn, user, at(14, 0), at(16, 0), None, None, &clock).unwrap();
+        let events = get_user_fasting_events(&mut conn, user).unwrap();
+        let (later, earlier) = (events[0].id, events[1].id);
+
+        edit_fasting_event(&mut conn, user, earlier, at(5, 0), Some(at(13, 0)), &clock).unwrap();
+        assert_eq!(find_user_fasting_event(&mut conn, user, earlier).unwrap().start_time, at(5, 0));
+        assert!(matches!(
+            edit_fasting_event(&mut conn, user, earlier, at(5, 0), Some(at(15, 0)), &clock),
+            Err(FastingAppError::OverlappingFast(id)) if id == later
+        ));
+        assert!(matches!(
+            edit_fasting_event(&mut conn, user, earlier, at(5, 0), None, &clock),
+            Err(FastingAppError::InvalidRequest(_))
+        ));
+    }
+
+    #[test]
+    fn test_deleting_a_fast_removes_what_was_logged_with_it() {
+        use crate::handlers::hydration::{log_intake, IntakeKind};
+        use crate::handlers::journal::{add_journal_entry, JournalEntryInput};
+        use crate::schema::break_fast_meals::dsl::{break_fast_meals, event_id as meal_event_id};
+        use crate::schema::fast_journal_entries::dsl::{event_id as journal_event_id, fast_journal_entries};
+        use crate::schema::fluid_intakes::dsl::{event_id as intake_event_id, fluid_intakes};
+
+        let mut conn = test_connection();
+        let user = insert_test_user(&mut conn, "deleter");
+        let clock = FakeClock::new(at(12, 0));
+
+        start_fasting(&mut conn, user, at(6, 0), None, None, &clock).unwrap();
+        pause_fasting(&mut conn, user, at(8, 0), &clock).unwrap();
+        resume_fasting(&mut conn, user, at(9, 0), &clock).unwrap();
+        add_journal_entry(&mut conn, user, None, JournalEntryInput { hunger: Some(4), ..Default::default() }, &clock)
+            .unwrap();
+        log_intake(&mut conn, user, IntakeKind::Water, 250, at(10, 0), &clock).unwrap();
+        let meal = BreakFastMealInput { description: "Eggs".to_string(), ..Default::default() };
+        stop_fasting(&mut conn, user, at(12, 0), Some(&meal), &clock).unwrap();
+        let fast = get_user_fasting_events(&mut conn, user).unwrap()[0].id;
+
+        delete_fasting_event(&mut conn, user, fast).unwrap();
+
+        assert!(get_user_fasting_events(&mut conn, user).unwrap().is_empty());
+        assert!(load_pauses_for_event(&mut conn, fast).unwrap().is_empty());
+        let meals: i64 = break_fast_meals.filter(meal_event_id.eq(fast)).count().get_result(&mut conn).unwrap();
+        let entries: i64 =
+            fast_journal_entries.filter(journal_event_id.eq(fast)).count().get_result(&mut conn).unwrap();
+        let drinks: i64 = fluid_intakes.filter(intake_event_id.eq(fast)).count().get_result(&mut conn).unwrap();
+        assert_eq!((meals, entries, drinks), (0, 0, 0));
+    }
+
+    #[test]
+    fn test_backdated_pause_cannot_overlap_an_earlier_one() {
+        let mut conn = test_connection();
+        let user = insert_test_user(&mut conn, "overlap");
+        let clock = FakeClock::new(at(12, 0));
+
+        start_fasting(&mut conn, user, at(6, 0), None, None, &clock).unwrap();
+        pause_fasting(&mut conn, user, at(8, 0), &clock).unwrap();
+        resume_fasting(&mut conn, user, at(9, 0), &clock).unwrap();
+
+        assert!(matches!(
+            pause_fasting(&mut conn, user, at(8, 30), &clock),
+            Err(FastingAppError::InvalidRequest(_))
+        ));
+        assert!(pause_fasting(&mut conn, user, at(9, 0), &clock).is_ok());
+    }
+
+    #[test]
+    fn test_repair_moves_a_duplicates_pauses_and_protocol_to_the_kept_event() {
+        use crate::models::{NewFastingEvent, NewFastingPause};
+
+        let mut conn = test_connection();
+        let user = insert_test_user(&mut conn, "dupe");
+        crate::handlers::protocols::create_protocol(&mut conn, user, "Weekday 16:8", 16, 8, None).unwrap();
+        let protocol = {
+            use crate::schema::fasting_protocols::dsl::{fasting_protocols, id, name};
+            fasting_protocols.filter(name.eq("Weekday 16:8")).select(id).first::<i32>(&mut conn).unwrap()
+        };
+        let mut insert = |protocol_id: Option<i32>| {
+            diesel::insert_into(fasting_events)
+                .values(&NewFastingEvent {
+                    user_id: user,
+                    start_time: at(6, 0),
+                    stop_time: Some(at(12, 0)),
+                    created_at: None,
+                    goal_id: None,
+                    protocol_id,
+                })
+                .returning(event_id)
+                .get_result::<i32>(&mut conn)
+                .unwrap()
+        };
+        let kept = insert(None);
+        let duplicate = insert(Some(protocol));
+        diesel::insert_into(fasting_pauses)
+            .values(&NewFastingPause {
+                event_id: duplicate,
+                pause_time: at(8, 0),
+                resume_time: Some(at(9, 0)),
+                created_at: None,
+            })
+            .execute(&mut conn)
+            .unwrap();
+
+        let report = repair_fasting_events(&mut conn).unwrap();
+        assert_eq!(report.duplicates_removed, 1);
+
+        let event = find_user_fasting_event(&mut conn, user, kept).unwrap();
+        assert_eq!(event.protocol_id, Some(protocol));
+        let pauses = load_pauses_for_event(&mut conn, kept).unwrap();
+        assert_eq!(effective_fasting_minutes(&event, &pauses, at(23, 0)), 300);
+        let orphaned = fasting_pauses
+            .filter(pause_event_id.eq(duplicate))
+            .count()
+            .get_result::<i64>(&mut conn)
+            .unwrap();
+        assert_eq!(orphaned, 0);
+    }
+}
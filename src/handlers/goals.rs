@@ -1,60 +1,937 @@
+use crate::clock::Clock;
 use crate::errors::FastingAppError;
-use crate::models::{FastingGoal, NewFastingGoal, User};
+use crate::handlers::fasting::{effective_fasting_minutes, load_pauses_by_event};
+use crate::handlers::programs::generate_program_goals;
+use crate::handlers::timezone::{format_local, get_user_timezone, local_midnight_utc, local_today};
+use crate::models::{FastingEvent, FastingGoal, FastingPause, NewFastingGoal, NewRecurringGoal, RecurringGoal, User};
 use crate::schema::fasting_goals::dsl::*;
-use chrono::NaiveDateTime;
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, TimeZone, Weekday};
 use diesel::prelude::*;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
 
-/// ✅ Adds a new fasting goal for the user.
-pub fn add_goal(user: &User, conn: &mut SqliteConnection) -> Result<(), FastingAppError> {
-    use crate::handlers::menu::prompt_user_input; // ✅ Reuse input prompt function
+/// Where a goal stands relative to its deadline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GoalStatus {
+    /// No linked fast is running and the deadline has not passed.
+    Pending,
+    /// A linked fast is running and the deadline has not passed.
+    InProgress,
+    /// A linked fast reached the goal duration before the deadline.
+    Achieved,
+    /// The deadline passed without a linked fast reaching the goal duration.
+    Missed,
+}
+
+impl GoalStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GoalStatus::Pending => "pending",
+            GoalStatus::InProgress => "in_progress",
+            GoalStatus::Achieved => "achieved",
+            GoalStatus::Missed => "missed",
+        }
+    }
+}
+
+impl FromStr for GoalStatus {
+    type Err = FastingAppError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "pending" => Ok(GoalStatus::Pending),
+            "in_progress" => Ok(GoalStatus::InProgress),
+            "achieved" => Ok(GoalStatus::Achieved),
+            "missed" => Ok(GoalStatus::Missed),
+            other => Err(FastingAppError::InvalidRequest(format!(
+                "Unknown goal status '{}'.",
+                other
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for GoalStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            GoalStatus::Pending => "Pending",
+            GoalStatus::InProgress => "In progress",
+            GoalStatus::Achieved => "Achieved",
+            GoalStatus::Missed => "Missed",
+        })
+    }
+}
+
+/// The outcome of evaluating a goal against its linked fasts.
+#[derive(Debug, Serialize)]
+pub struct GoalEvaluation {
+    pub goal_id: i32,
+    pub status: GoalStatus,
+    pub achieved_at: Option<NaiveDateTime>,
+    /// Longest linked fast before the deadline, excluding pauses.
+    pub best_minutes: i64,
+    /// The linked fast that is still running, if any.
+    pub active_event_id: Option<i32>,
+    /// Progress of the running fast toward the goal duration.
+    pub active_progress_percent: Option<f64>,
+}
+
+/// How often a recurring goal produces a new goal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecurrencePeriod {
+    /// One goal per selected weekday, due at the end of that day.
+    Daily,
+    /// One goal per week, due at the end of Sunday.
+    Weekly,
+}
+
+impl RecurrencePeriod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RecurrencePeriod::Daily => "daily",
+            RecurrencePeriod::Weekly => "weekly",
+        }
+    }
+}
+
+impl FromStr for RecurrencePeriod {
+    type Err = FastingAppError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.trim().to_lowercase().as_str() {
+            "daily" | "day" => Ok(RecurrencePeriod::Daily),
+            "weekly" | "week" => Ok(RecurrencePeriod::Weekly),
+            other => Err(FastingAppError::InvalidRequest(format!(
+                "Unknown recurrence '{}'. Use daily or weekly.",
+                other
+            ))),
+        }
+    }
+}
+
+/// Weekday mask for Monday to Friday.
+pub const WEEKDAYS_MASK: i32 = 0b001_1111;
 
-    let duration_input = prompt_user_input("Enter the goal duration in hours: ");
-    let parsed_duration: i32 = duration_input.parse().map_err(|_| {
-        FastingAppError::InvalidRequest("❌ Invalid duration entered.".to_string())
-    })?;
+/// Weekday mask for every day of the week.
+pub const EVERY_DAY_MASK: i32 = 0b111_1111;
 
-    let deadline_input = prompt_user_input("Enter the deadline (YYYY-MM-DD HH:MM): ");
-    let goal_deadline = NaiveDateTime::parse_from_str(&deadline_input, "%Y-%m-%d %H:%M")
-        .map_err(|_| FastingAppError::InvalidRequest("❌ Invalid deadline format.".to_string()))?;
+/// ✅ Builds a weekday mask (bit 0 = Monday) from a list of days.
+pub fn weekday_mask(days: &[Weekday]) -> i32 {
+    days.iter()
+        .fold(0, |mask, day| mask | 1 << day.num_days_from_monday())
+}
+
+/// ✅ Creates a one-off fasting goal of `goal_hours` hours, due by `goal_deadline`.
+pub fn create_goal(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    goal_hours: i32,
+    goal_deadline: NaiveDateTime,
+    clock: &dyn Clock,
+) -> Result<FastingGoal, FastingAppError> {
+    validate_goal_hours(goal_hours)?;
 
     let new_goal = NewFastingGoal {
-        user_id: user.id,
-        goal_duration: parsed_duration,
+        user_id: user_id_input,
+        goal_duration: goal_hours,
         deadline: goal_deadline,
-        created_at: Some(chrono::Utc::now().naive_utc()),
+        created_at: Some(clock.now()),
+        recurring_goal_id: None,
+        period_start: None,
+        enrollment_id: None,
     };
 
     diesel::insert_into(fasting_goals)
         .values(&new_goal)
+        .returning(FastingGoal::as_returning())
+        .get_result(conn)
+        .map_err(FastingAppError::DatabaseError)
+}
+
+/// ✅ Lists the user's goals by deadline, generating any due recurring and program goals first.
+///
+/// - Archived goals are only included when `include_archived` is set.
+pub fn list_goals(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    include_archived: bool,
+    clock: &dyn Clock,
+) -> Result<Vec<FastingGoal>, FastingAppError> {
+    let today = local_today(&get_user_timezone(conn, user_id_input)?, clock);
+    generate_recurring_goals(conn, user_id_input, today, clock)?;
+    generate_program_goals(conn, user_id_input, today, clock)?;
+
+    let mut query = fasting_goals
+        .filter(user_id.eq(user_id_input))
+        .order((deadline.asc(), id.asc()))
+        .select(FastingGoal::as_select())
+        .into_boxed();
+
+    if !include_archived {
+        query = query.filter(archived_at.is_null());
+    }
+
+    query
+        .load::<FastingGoal>(conn)
+        .map_err(FastingAppError::DatabaseError)
+}
+
+/// ✅ Finds a goal by ID, making sure it belongs to the user.
+///
+/// - Returns `GoalNotFound` for unknown IDs and `GoalAccessDenied` for other users' goals.
+pub fn find_user_goal(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    goal_id: i32,
+) -> Result<FastingGoal, FastingAppError> {
+    let goal = fasting_goals
+        .filter(id.eq(goal_id))
+        .select(FastingGoal::as_select())
+        .first::<FastingGoal>(conn)
+        .optional()
+        .map_err(FastingAppError::DatabaseError)?
+        .ok_or(FastingAppError::GoalNotFound(goal_id))?;
+
+    if goal.user_id != user_id_input {
+        return Err(FastingAppError::GoalAccessDenied(goal_id));
+    }
+    Ok(goal)
+}
+
+/// ✅ Checks that a fast starting at `fast_start` may be linked to the goal.
+///
+/// - The goal must exist and belong to the user.
+/// - Its deadline must be after `fast_start`; time fasted after the deadline never counts.
+pub fn validate_goal_link(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    goal_id: i32,
+    fast_start: NaiveDateTime,
+) -> Result<FastingGoal, FastingAppError> {
+    let goal = find_user_goal(conn, user_id_input, goal_id)?;
+    check_goal_open(&goal, fast_start)?;
+    Ok(goal)
+}
+
+/// ✅ Rejects a goal whose deadline is at or before `at`.
+pub fn check_goal_open(goal: &FastingGoal, at: NaiveDateTime) -> Result<(), FastingAppError> {
+    if goal.deadline <= at {
+        return Err(FastingAppError::GoalDeadlinePassed {
+            goal_id: goal.id,
+            deadline: goal.deadline,
+        });
+    }
+    Ok(())
+}
+
+/// ✅ Lists the goals a fast can still be linked to: not archived and not yet due.
+pub fn list_active_goals(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    clock: &dyn Clock,
+) -> Result<Vec<FastingGoal>, FastingAppError> {
+    let now = clock.now();
+    Ok(list_goals(conn, user_id_input, false, clock)?
+        .into_iter()
+        .filter(|goal| goal.deadline > now)
+        .collect())
+}
+
+/// ✅ Changes the duration and deadline of one of the user's goals.
+///
+/// - The stored status is reset; it is recomputed on the next evaluation.
+pub fn update_goal(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    goal_id: i32,
+    goal_hours: i32,
+    goal_deadline: NaiveDateTime,
+) -> Result<(), FastingAppError> {
+    validate_goal_hours(goal_hours)?;
+    let goal = find_user_goal(conn, user_id_input, goal_id)?;
+
+    diesel::update(fasting_goals.filter(id.eq(goal.id)))
+        .set((
+            goal_duration.eq(goal_hours),
+            deadline.eq(goal_deadline),
+            status.eq(GoalStatus::Pending.as_str()),
+            achieved_at.eq(None::<NaiveDateTime>),
+        ))
+        .execute(conn)
+        .map(|_| ())
+        .map_err(FastingAppError::DatabaseError)
+}
+
+/// ✅ Deletes one of the user's goals.
+///
+/// - Fasts and schedule windows linked to the goal keep their history with no goal.
+pub fn delete_goal(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    goal_id: i32,
+) -> Result<(), FastingAppError> {
+    use crate::schema::fasting_events::dsl::{fasting_events, goal_id as event_goal_id};
+    use crate::schema::fasting_schedules::dsl::{fasting_schedules, goal_id as schedule_goal_id};
+
+    let goal = find_user_goal(conn, user_id_input, goal_id)?;
+
+    conn.transaction(|conn| {
+        diesel::update(fasting_events.filter(event_goal_id.eq(goal.id)))
+            .set(event_goal_id.eq(None::<i32>))
+            .execute(conn)
+            .map_err(FastingAppError::DatabaseError)?;
+
+        diesel::update(fasting_schedules.filter(schedule_goal_id.eq(goal.id)))
+            .set(schedule_goal_id.eq(None::<i32>))
+            .execute(conn)
+            .map_err(FastingAppError::DatabaseError)?;
+
+        diesel::delete(fasting_goals.filter(id.eq(goal.id)))
+            .execute(conn)
+            .map(|_| ())
+            .map_err(FastingAppError::DatabaseError)
+    })
+}
+
+/// ✅ Archives or restores one of the user's goals.
+///
+/// - Archived goals are hidden from the goal list but keep their links and status.
+pub fn set_goal_archived(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    goal_id: i32,
+    archived: bool,
+    clock: &dyn Clock,
+) -> Result<(), FastingAppError> {
+    let goal = find_user_goal(conn, user_id_input, goal_id)?;
+    let archived_time = archived.then(|| clock.now());
+
+    diesel::update(fasting_goals.filter(id.eq(goal.id)))
+        .set(archived_at.eq(archived_time))
         .execute(conn)
+        .map(|_| ())
+        .map_err(FastingAppError::DatabaseError)
+}
+
+/// ✅ Creates a recurring goal, e.g. 16 hours every weekday.
+///
+/// - `weekdays` only applies to daily goals and must contain at least one day.
+/// - Goal instances are generated from `starts_on` up to the current local period.
+pub fn create_recurring_goal(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    goal_hours: i32,
+    period: RecurrencePeriod,
+    weekdays: &[Weekday],
+    starts_on: NaiveDate,
+    clock: &dyn Clock,
+) -> Result<RecurringGoal, FastingAppError> {
+    use crate::schema::recurring_goals::dsl::recurring_goals;
+
+    validate_goal_hours(goal_hours)?;
+    let mask = match period {
+        RecurrencePeriod::Daily => weekday_mask(weekdays),
+        RecurrencePeriod::Weekly => EVERY_DAY_MASK,
+    };
+    if mask == 0 {
+        return Err(FastingAppError::InvalidRequest(
+            "A daily goal needs at least one weekday.".to_string(),
+        ));
+    }
+
+    let new_recurring_goal = NewRecurringGoal {
+        user_id: user_id_input,
+        goal_duration: goal_hours,
+        period: period.as_str().to_string(),
+        weekdays: mask,
+        starts_on,
+        created_at: Some(clock.now()),
+    };
+
+    let recurring_goal = diesel::insert_into(recurring_goals)
+        .values(&new_recurring_goal)
+        .returning(RecurringGoal::as_returning())
+        .get_result(conn)
         .map_err(FastingAppError::DatabaseError)?;
 
-    println!("✅ Goal added successfully!");
-    Ok(())
+    let today = local_today(&get_user_timezone(conn, user_id_input)?, clock);
+    generate_recurring_goals(conn, user_id_input, today, clock)?;
+    Ok(recurring_goal)
 }
 
-/// ✅ Displays the fasting goals for the given user.
-pub fn view_goals(user: &User, conn: &mut SqliteConnection) -> Result<(), FastingAppError> {
-    let user_goals = fasting_goals
-        .filter(user_id.eq(user.id))
-        .select(FastingGoal::as_select())
-        .load::<FastingGoal>(conn)
+/// ✅ Lists the user's recurring goals, oldest first.
+pub fn list_recurring_goals(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+) -> Result<Vec<RecurringGoal>, FastingAppError> {
+    use crate::schema::recurring_goals::dsl::{id as recurring_id, recurring_goals, user_id as recurring_user_id};
+
+    recurring_goals
+        .filter(recurring_user_id.eq(user_id_input))
+        .order(recurring_id.asc())
+        .select(RecurringGoal::as_select())
+        .load::<RecurringGoal>(conn)
+        .map_err(FastingAppError::DatabaseError)
+}
+
+/// ✅ Stops one of the user's recurring goals.
+///
+/// - Goals it already generated are kept as ordinary goals.
+pub fn delete_recurring_goal(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    recurring_goal_id_input: i32,
+) -> Result<(), FastingAppError> {
+    use crate::schema::recurring_goals::dsl::{id as recurring_id, recurring_goals, user_id as recurring_user_id};
+
+    conn.transaction(|conn| {
+        let deleted = diesel::delete(
+            recurring_goals
+                .filter(recurring_id.eq(recurring_goal_id_input))
+                .filter(recurring_user_id.eq(user_id_input)),
+        )
+        .execute(conn)
         .map_err(FastingAppError::DatabaseError)?;
 
+        if deleted == 0 {
+            return Err(FastingAppError::RecurringGoalNotFound(recurring_goal_id_input));
+        }
+
+        diesel::update(fasting_goals.filter(recurring_goal_id.eq(recurring_goal_id_input)))
+            .set(recurring_goal_id.eq(None::<i32>))
+            .execute(conn)
+            .map(|_| ())
+            .map_err(FastingAppError::DatabaseError)
+    })
+}
+
+/// ✅ Creates the goal instances of the user's recurring goals for every period up to `through`.
+///
+/// - Periods that already have a goal are skipped, so this is safe to call repeatedly.
+/// - Returns the number of goals created.
+pub fn generate_recurring_goals(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    through: NaiveDate,
+    clock: &dyn Clock,
+) -> Result<usize, FastingAppError> {
+    let templates = list_recurring_goals(conn, user_id_input)?;
+    let tz = get_user_timezone(conn, user_id_input)?;
+    let now = clock.now();
+    let mut created = 0;
+
+    for template in &templates {
+        let last_generated = fasting_goals
+            .filter(recurring_goal_id.eq(template.id))
+            .select(diesel::dsl::max(period_start))
+            .first::<Option<NaiveDate>>(conn)
+            .map_err(FastingAppError::DatabaseError)?;
+        let period_length = match template.period.parse::<RecurrencePeriod>() {
+            Ok(RecurrencePeriod::Weekly) => Duration::weeks(1),
+            _ => Duration::days(1),
+        };
+        let from = last_generated.map_or(template.starts_on, |last| last + period_length);
+
+        let new_goals: Vec<NewFastingGoal> = recurring_periods(template, from, through, &tz)
+            .into_iter()
+            .map(|(start, due)| NewFastingGoal {
+                user_id: user_id_input,
+                goal_duration: template.goal_duration,
+                deadline: due,
+                created_at: Some(now),
+                recurring_goal_id: Some(template.id),
+                period_start: Some(start),
+                enrollment_id: None,
+            })
+            .collect();
+
+        if !new_goals.is_empty() {
+            created += diesel::insert_into(fasting_goals)
+                .values(&new_goals)
+                .execute(conn)
+                .map_err(FastingAppError::DatabaseError)?;
+        }
+    }
+    Ok(created)
+}
+
+/// ✅ Lists the periods of a recurring goal that start between `from` and `through`, inclusive.
+///
+/// - Returns each period's local start date and its deadline: local midnight in `tz`
+///   after the period ends, as UTC.
+/// - Weekly periods start on Monday; a week already underway at `from` is included.
+pub fn recurring_periods<Tz: TimeZone>(
+    template: &RecurringGoal,
+    from: NaiveDate,
+    through: NaiveDate,
+    tz: &Tz,
+) -> Vec<(NaiveDate, NaiveDateTime)> {
+    let from = from.max(template.starts_on);
+    let midnight = |date: NaiveDate| local_midnight_utc(tz, date);
+
+    match template.period.parse::<RecurrencePeriod>() {
+        Ok(RecurrencePeriod::Daily) => from
+            .iter_days()
+            .take_while(|date| *date <= through)
+            .filter(|date| template.weekdays & (1 << date.weekday().num_days_from_monday()) != 0)
+            .map(|date| (date, midnight(date + Duration::days(1))))
+            .collect(),
+        Ok(RecurrencePeriod::Weekly) => {
+            let first_monday = from - Duration::days(i64::from(from.weekday().num_days_from_monday()));
+            first_monday
+                .iter_weeks()
+                .take_while(|monday| *monday <= through)
+                .map(|monday| (monday, midnight(monday + Duration::weeks(1))))
+                .collect()
+        }
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Checks that a goal duration is a positive number of hours.
+fn validate_goal_hours(goal_hours: i32) -> Result<(), FastingAppError> {
+    if goal_hours <= 0 {
+        return Err(FastingAppError::InvalidRequest(
+            "The goal duration must be a positive number of hours.".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// ✅ Displays the fasting goals for the given user, with their current status, in their timezone.
+pub fn view_goals(user: &User, conn: &mut SqliteConnection, clock: &dyn Clock) -> Result<(), FastingAppError> {
+    let tz = get_user_timezone(conn, user.id)?;
+    let user_goals = list_goals(conn, user.id, false, clock)?;
+    let evaluations: HashMap<i32, GoalEvaluation> = evaluate_goals(conn, user.id, clock)?
+        .into_iter()
+        .map(|evaluation| (evaluation.goal_id, evaluation))
+        .collect();
+
     if user_goals.is_empty() {
         println!("❌ No fasting goals found for user {}.", user.username);
     } else {
         println!("📋 Fasting goals for {}:", user.username);
         for goal in user_goals {
             println!(
-                "- ⏳ Goal: {} hours | 📅 Deadline: {} | 🕒 Created At: {}",
+                "- [{}] ⏳ Goal: {} hours | 📅 Deadline: {} | 🕒 Created At: {}",
+                goal.id,
                 goal.goal_duration,
-                goal.deadline,
+                format_local(&tz, goal.deadline),
                 goal.created_at
-                    .map(|ts| ts.to_string())
+                    .map(|ts| format_local(&tz, ts))
                     .unwrap_or_else(|| "Unknown".to_string())
             );
+            if let Some(evaluation) = evaluations.get(&goal.id) {
+                let progress = evaluation
+                    .active_progress_percent
+                    .map_or_else(String::new, |percent| format!(" | current fast at {:.0}%", percent));
+                let achieved = evaluation
+                    .achieved_at
+                    .map_or_else(String::new, |at| format!(" on {}", format_local(&tz, at)));
+                println!(
+                    "  Status: {}{} | best fast {:.1} h{}",
+                    evaluation.status,
+                    achieved,
+                    evaluation.best_minutes as f64 / 60.0,
+                    progress
+                );
+            }
         }
     }
     Ok(())
 }
+
+/// ✅ Evaluates each of the user's goals against the fasts linked to it, and stores the status.
+///
+/// - Only fasting time before the deadline counts, excluding pauses.
+/// - Statuses are recomputed every time, so editing or deleting a fast is reflected.
+/// - Running fasts and passed deadlines are judged as of `clock.now()`.
+pub fn evaluate_goals(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    clock: &dyn Clock,
+) -> Result<Vec<GoalEvaluation>, FastingAppError> {
+    use crate::schema::fasting_events::dsl::{
+        fasting_events, goal_id as event_goal_id, user_id as event_user_id,
+    };
+
+    let user_goals = fasting_goals
+        .filter(user_id.eq(user_id_input))
+        .select(FastingGoal::as_select())
+        .load::<FastingGoal>(conn)
+        .map_err(FastingAppError::DatabaseError)?;
+    let goal_ids: Vec<i32> = user_goals.iter().map(|goal| goal.id).collect();
+
+    // Program days also count fasts that were not linked to any goal
+    let mut event_query = fasting_events
+        .filter(event_goal_id.eq_any(&goal_ids))
+        .select(FastingEvent::as_select())
+        .into_boxed();
+    if user_goals.iter().any(|goal| goal.enrollment_id.is_some()) {
+        event_query = event_query.or_filter(event_user_id.eq(user_id_input).and(event_goal_id.is_null()));
+    }
+    let candidate_events = event_query
+        .load::<FastingEvent>(conn)
+        .map_err(FastingAppError::DatabaseError)?;
+    let event_ids: Vec<i32> = candidate_events.iter().map(|event| event.id).collect();
+    let pauses = load_pauses_by_event(conn, &event_ids)?;
+    let tz = get_user_timezone(conn, user_id_input)?;
+    let now = clock.now();
+
+    let mut evaluations = Vec::with_capacity(user_goals.len());
+    for goal in &user_goals {
+        let events: Vec<&FastingEvent> = candidate_events
+            .iter()
+            .filter(|event| counts_toward_goal(goal, event, now, &tz))
+            .collect();
+        let evaluation = evaluate_goal(goal, &events, &pauses, now, &tz);
+
+        if goal.status != evaluation.status.as_str() || goal.achieved_at != evaluation.achieved_at {
+            diesel::update(fasting_goals.filter(id.eq(goal.id)))
+                .set((status.eq(evaluation.status.as_str()), achieved_at.eq(evaluation.achieved_at)))
+                .execute(conn)
+                .map_err(FastingAppError::DatabaseError)?;
+        }
+        evaluations.push(evaluation);
+    }
+    Ok(evaluations)
+}
+
+/// ✅ Whether a fast counts toward a goal.
+///
+/// - Fasts linked to the goal always count.
+/// - A program day also counts unlinked fasts that overlap that local day in `tz`.
+pub fn counts_toward_goal<Tz: TimeZone>(
+    goal: &FastingGoal,
+    event: &FastingEvent,
+    now: NaiveDateTime,
+    tz: &Tz,
+) -> bool {
+    if event.goal_id == Some(goal.id) {
+        return true;
+    }
+    match (program_day_start(goal, tz), event.goal_id) {
+        (Some(day_start), None) => event.start_time < goal.deadline && event.stop_time.unwrap_or(now) > day_start,
+        _ => false,
+    }
+}
+
+/// When a program day goal's day begins in `tz`, or `None` for other goals.
+fn program_day_start<Tz: TimeZone>(goal: &FastingGoal, tz: &Tz) -> Option<NaiveDateTime> {
+    match (goal.enrollment_id, goal.period_start) {
+        (Some(_), Some(day)) => Some(local_midnight_utc(tz, day)),
+        _ => None,
+    }
+}
+
+/// ✅ Evaluates one goal against its linked fasts as of `now`.
+///
+/// - `achieved_at` is when a fast's unpaused time first reached the goal, not when it stopped.
+/// - A program day only counts time between its local midnight in `tz` and its deadline.
+pub fn evaluate_goal<Tz: TimeZone>(
+    goal: &FastingGoal,
+    events: &[&FastingEvent],
+    pauses: &HashMap<i32, Vec<FastingPause>>,
+    now: NaiveDateTime,
+    tz: &Tz,
+) -> GoalEvaluation {
+    let goal_minutes = i64::from(goal.goal_duration) * 60;
+    let mut best_minutes = 0;
+    let mut first_achieved: Option<NaiveDateTime> = None;
+    let mut active_event_id = None;
+    let mut active_progress_percent = None;
+    let day_start = program_day_start(goal, tz);
+
+    for event in events {
+        let event_pauses = pauses.get(&event.id).map(Vec::as_slice).unwrap_or(&[]);
+        let end_before_deadline = event.stop_time.unwrap_or(now).min(goal.deadline);
+        let clipped = FastingEvent {
+            start_time: day_start.map_or(event.start_time, |day_start| event.start_time.max(day_start)),
+            stop_time: Some(end_before_deadline),
+            ..**event
+        };
+        let minutes = if end_before_deadline > clipped.start_time {
+            effective_fasting_minutes(&clipped, event_pauses, end_before_deadline)
+        } else {
+            0
+        };
+
+        best_minutes = best_minutes.max(minutes);
+        if minutes >= goal_minutes {
+            let crossed_at = goal_crossing_time(&clipped, event_pauses, goal_minutes).min(end_before_deadline);
+            if first_achieved.is_none_or(|at| crossed_at < at) {
+                first_achieved = Some(crossed_at);
+            }
+        }
+        if event.stop_time.is_none() {
+            active_event_id = Some(event.id);
+            active_progress_percent = (goal_minutes > 0).then(|| minutes as f64 / goal_minutes as f64 * 100.0);
+        }
+    }
+
+    let goal_status = if first_achieved.is_some() {
+        GoalStatus::Achieved
+    } else if now >= goal.deadline {
+        GoalStatus::Missed
+    } else if active_event_id.is_some() {
+        GoalStatus::InProgress
+    } else {
+        GoalStatus::Pending
+    };
+
+    GoalEvaluation {
+        goal_id: goal.id,
+        status: goal_status,
+        achieved_at: first_achieved,
+        best_minutes,
+        active_event_id,
+        active_progress_percent,
+    }
+}
+
+/// The instant a fast's unpaused time reaches `goal_minutes`: its start plus the goal,
+/// pushed back by every pause that began before that point.
+fn goal_crossing_time(event: &FastingEvent, pauses: &[FastingPause], goal_minutes: i64) -> NaiveDateTime {
+    let mut ordered: Vec<&FastingPause> = pauses.iter().filter(|pause| pause.event_id == event.id).collect();
+    ordered.sort_by_key(|pause| pause.pause_time);
+
+    let mut crossed_at = event.start_time + Duration::minutes(goal_minutes);
+    for pause in ordered {
+        let pause_start = pause.pause_time.max(event.start_time);
+        if pause_start >= crossed_at {
+            break;
+        }
+        if let Some(resumed) = pause.resume_time.filter(|resumed| *resumed > pause_start) {
+            crossed_at += resumed - pause_start;
+        }
+    }
+    crossed_at
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn at(hour: i64) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 4, 1).unwrap().and_hms_opt(0, 0, 0).unwrap() + Duration::hours(hour)
+    }
+
+    fn goal(hours: i32, deadline_hour: i64) -> FastingGoal {
+        FastingGoal {
+            id: 1,
+            user_id: 1,
+            goal_duration: hours,
+            deadline: at(deadline_hour),
+            created_at: None,
+            status: "pending".to_string(),
+            achieved_at: None,
+            archived_at: None,
+            recurring_goal_id: None,
+            period_start: None,
+            enrollment_id: None,
+        }
+    }
+
+    fn event(start_hour: i64, stop_hour: Option<i64>) -> FastingEvent {
+        FastingEvent {
+            id: 5,
+            user_id: 1,
+            start_time: at(start_hour),
+            stop_time: stop_hour.map(at),
+            created_at: None,
+            goal_id: Some(1),
+            protocol_id: None,
+        }
+    }
+
+    #[test]
+    fn test_goal_counts_only_time_before_deadline() {
+        let pauses = HashMap::new();
+
+        let finished = event(0, Some(18));
+        let evaluation = evaluate_goal(&goal(16, 20), &[&finished], &pauses, at(30), &Utc);
+        assert_eq!(evaluation.status, GoalStatus::Achieved);
+        assert_eq!(evaluation.achieved_at, Some(at(16)));
+
+        let paused = HashMap::from([(
+            finished.id,
+            vec![FastingPause {
+                id: 1,
+                event_id: finished.id,
+                pause_time: at(2),
+                resume_time: Some(at(3)),
+                created_at: None,
+            }],
+        )]);
+        let evaluation = evaluate_goal(&goal(16, 20), &[&finished], &paused, at(30), &Utc);
+        assert_eq!(evaluation.achieved_at, Some(at(17)));
+
+        let late = event(10, Some(30));
+        let evaluation = evaluate_goal(&goal(16, 20), &[&late], &pauses, at(40), &Utc);
+        assert_eq!(evaluation.status, GoalStatus::Missed);
+        assert_eq!(evaluation.best_minutes, 10 * 60);
+    }
+
+    #[test]
+    fn test_program_day_counts_only_time_within_its_day() {
+        let program_day = FastingGoal {
+            enrollment_id: Some(2),
+            period_start: Some(NaiveDate::from_ymd_opt(2025, 4, 2).unwrap()),
+            ..goal(16, 48)
+        };
+        let overnight = FastingEvent { goal_id: None, ..event(14, Some(36)) };
+        let evaluation = evaluate_goal(&program_day, &[&overnight], &HashMap::new(), at(50), &Utc);
+        assert_eq!((evaluation.status, evaluation.best_minutes), (GoalStatus::Missed, 12 * 60));
+
+        let longer = FastingEvent { goal_id: None, ..event(14, Some(42)) };
+        let evaluation = evaluate_goal(&program_day, &[&longer], &HashMap::new(), at(50), &Utc);
+        assert_eq!(evaluation.achieved_at, Some(at(40)));
+    }
+
+    #[test]
+    fn test_running_fast_is_in_progress() {
+        let running = event(0, None);
+        let evaluation = evaluate_goal(&goal(16, 48), &[&running], &HashMap::new(), at(8), &Utc);
+
+        assert_eq!(evaluation.status, GoalStatus::InProgress);
+        assert_eq!(evaluation.active_progress_percent, Some(50.0));
+    }
+
+    #[test]
+    fn test_goal_closes_at_its_deadline() {
+        assert!(check_goal_open(&goal(16, 20), at(19)).is_ok());
+        assert!(matches!(
+            check_goal_open(&goal(16, 20), at(20)),
+            Err(FastingAppError::GoalDeadlinePassed { goal_id: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_goal_statuses_follow_the_clock() {
+        use crate::clock::FakeClock;
+        use crate::db::{insert_test_user, test_connection};
+        use crate::handlers::fasting::start_fasting;
+
+        let mut conn = test_connection();
+        let user = insert_test_user(&mut conn, "goal-setter");
+        let clock = FakeClock::new(at(0));
+        let linked = create_goal(&mut conn, user, 16, at(20), &clock).unwrap();
+        let unlinked = create_goal(&mut conn, user, 16, at(12), &clock).unwrap();
+        start_fasting(&mut conn, user, at(0), Some(linked.id), None, &clock).unwrap();
+
+        let mut status_of = |goal: &FastingGoal, clock: &FakeClock| {
+            evaluate_goals(&mut conn, user, clock)
+                .unwrap()
+                .into_iter()
+                .find(|evaluation| evaluation.goal_id == goal.id)
+                .map(|evaluation| evaluation.status)
+        };
+
+        clock.set(at(10));
+        assert_eq!(status_of(&linked, &clock), Some(GoalStatus::InProgress));
+        assert_eq!(status_of(&unlinked, &clock), Some(GoalStatus::Pending));
+
+        clock.set(at(16));
+        assert_eq!(status_of(&linked, &clock), Some(GoalStatus::Achieved));
+        assert_eq!(status_of(&unlinked, &clock), Some(GoalStatus::Missed));
+
+        let active: Vec<i32> = list_active_goals(&mut conn, user, &clock)
+            .unwrap()
+            .into_iter()
+            .map(|goal| goal.id)
+            .collect();
+        assert_eq!(active, vec![linked.id]);
+    }
+
+    #[test]
+    fn test_editing_a_fast_moves_the_achievement_time() {
+        use crate::clock::FakeClock;
+        use crate::db::{insert_test_user, test_connection};
+        use crate::handlers::fasting::{edit_fasting_event, record_completed_fast};
+        use crate::schema::fasting_events::dsl::{fasting_events, id as event_id};
+
+        let mut conn = test_connection();
+        let user = insert_test_user(&mut conn, "goal-setter");
+        let clock = FakeClock::new(at(24));
+        let goal = create_goal(&mut conn, user, 16, at(30), &clock).unwrap();
+        record_completed_fast(&mut conn, user, at(0), at(20), Some(goal.id), None, &clock).unwrap();
+        let fast = fasting_events.select(event_id).first::<i32>(&mut conn).unwrap();
+
+        let stored_achievement = |conn: &mut SqliteConnection| {
+            evaluate_goals(conn, user, &clock).unwrap();
+            fasting_goals
+                .filter(id.eq(goal.id))
+                .select(achieved_at)
+                .first::<Option<NaiveDateTime>>(conn)
+                .unwrap()
+        };
+        assert_eq!(stored_achievement(&mut conn), Some(at(16)));
+
+        // Starting the fast later means the goal was reached later, not at the old time
+        edit_fasting_event(&mut conn, user, fast, at(2), Some(at(20)), &clock).unwrap();
+        assert_eq!(stored_achievement(&mut conn), Some(at(18)));
+    }
+
+    fn recurring(period: &str, weekdays: i32) -> RecurringGoal {
+        RecurringGoal {
+            id: 3,
+            user_id: 1,
+            goal_duration: 16,
+            period: period.to_string(),
+            weekdays,
+            starts_on: NaiveDate::from_ymd_opt(2025, 4, 1).unwrap(),
+            created_at: None,
+        }
+    }
+
+    #[test]
+    fn test_weekday_goals_skip_the_weekend() {
+        // 2025-04-01 is a Tuesday; the range runs through the following Tuesday
+        let from = NaiveDate::from_ymd_opt(2025, 4, 1).unwrap();
+        let through = NaiveDate::from_ymd_opt(2025, 4, 8).unwrap();
+        let periods = recurring_periods(&recurring("daily", WEEKDAYS_MASK), from, through, &Utc);
+
+        let days: Vec<u32> = periods.iter().map(|(start, _)| start.day()).collect();
+        assert_eq!(days, vec![1, 2, 3, 4, 7, 8]);
+        assert_eq!(periods[0].1, at(24));
+    }
+
+    #[test]
+    fn test_daily_deadlines_follow_local_midnight() {
+        use chrono_tz::America::New_York;
+
+        // Clocks go forward in New York on 2025-03-09
+        let from = NaiveDate::from_ymd_opt(2025, 3, 8).unwrap();
+        let through = NaiveDate::from_ymd_opt(2025, 3, 9).unwrap();
+        let mut template = recurring("daily", EVERY_DAY_MASK);
+        template.starts_on = from;
+        let deadlines: Vec<NaiveDateTime> = recurring_periods(&template, from, through, &New_York)
+            .into_iter()
+            .map(|(_, due)| due)
+            .collect();
+
+        let utc = |day: u32, hour: u32| NaiveDate::from_ymd_opt(2025, 3, day).unwrap().and_hms_opt(hour, 0, 0).unwrap();
+        assert_eq!(deadlines, vec![utc(9, 5), utc(10, 4)]);
+    }
+
+    #[test]
+    fn test_weekly_goals_start_on_monday() {
+        let from = NaiveDate::from_ymd_opt(2025, 4, 3).unwrap();
+        let through = NaiveDate::from_ymd_opt(2025, 4, 14).unwrap();
+        let periods = recurring_periods(&recurring("weekly", EVERY_DAY_MASK), from, through, &Utc);
+
+        let mondays: Vec<NaiveDate> = periods.iter().map(|(start, _)| *start).collect();
+        assert_eq!(
+            mondays,
+            vec![
+                NaiveDate::from_ymd_opt(2025, 3, 31).unwrap(),
+                NaiveDate::from_ymd_opt(2025, 4, 7).unwrap(),
+                NaiveDate::from_ymd_opt(2025, 4, 14).unwrap(),
+            ]
+        );
+    }
+}
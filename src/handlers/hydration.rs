@@ -0,0 +1,308 @@
+use crate::clock::Clock;
+use crate::errors::FastingAppError;
+use crate::handlers::fasting::get_current_fasting_status;
+use crate::models::{FluidIntake, NewFluidIntake};
+use crate::schema::fluid_intakes::dsl::{
+    amount_ml, consumed_at, event_id, fluid_intakes, kind, user_id,
+};
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel::SqliteConnection;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// The drinks that can be logged during a fast.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntakeKind {
+    Water,
+    Electrolytes,
+    BlackCoffee,
+    Tea,
+}
+
+impl IntakeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IntakeKind::Water => "water",
+            IntakeKind::Electrolytes => "electrolytes",
+            IntakeKind::BlackCoffee => "black_coffee",
+            IntakeKind::Tea => "tea",
+        }
+    }
+}
+
+impl FromStr for IntakeKind {
+    type Err = FastingAppError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.trim().to_lowercase().as_str() {
+            "water" => Ok(IntakeKind::Water),
+            "electrolytes" | "electrolyte" => Ok(IntakeKind::Electrolytes),
+            "black_coffee" | "black coffee" | "coffee" => Ok(IntakeKind::BlackCoffee),
+            "tea" => Ok(IntakeKind::Tea),
+            other => Err(FastingAppError::InvalidRequest(format!(
+                "Unknown drink '{}'. Use water, electrolytes, black_coffee or tea.",
+                other
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for IntakeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Fluid totals for a single fast, in millilitres.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct HydrationTotals {
+    pub total_ml: i64,
+    pub water_ml: i64,
+    pub electrolytes_ml: i64,
+    pub black_coffee_ml: i64,
+    pub tea_ml: i64,
+}
+
+impl HydrationTotals {
+    fn add(&mut self, drink: &str, millilitres: i64) {
+        self.total_ml += millilitres;
+        match drink {
+            "water" => self.water_ml += millilitres,
+            "electrolytes" => self.electrolytes_ml += millilitres,
+            "black_coffee" => self.black_coffee_ml += millilitres,
+            "tea" => self.tea_ml += millilitres,
+            _ => {}
+        }
+    }
+}
+
+/// Hydration during the ongoing fast compared with the user's target.
+#[derive(Debug)]
+pub struct HydrationProgress {
+    pub event_id: i32,
+    pub totals: HydrationTotals,
+    /// The daily target scaled to the number of days the fast has spanned.
+    pub target_ml: Option<i64>,
+}
+
+impl HydrationProgress {
+    /// ✅ Percentage of the target reached, if a target is set.
+    pub fn percent_of_target(&self) -> Option<f64> {
+        self.target_ml
+            .filter(|target| *target > 0)
+            .map(|target| self.totals.total_ml as f64 / target as f64 * 100.0)
+    }
+}
+
+/// ✅ Logs a drink, attaching it to the ongoing fast when it falls within it.
+pub fn log_intake(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    drink: IntakeKind,
+    millilitres: i32,
+    drunk_at: NaiveDateTime,
+    clock: &dyn Clock,
+) -> Result<(), FastingAppError> {
+    if millilitres <= 0 {
+        return Err(FastingAppError::InvalidRequest(
+            "Intake amount must be positive.".to_string(),
+        ));
+    }
+
+    let active_event_id = get_current_fasting_status(conn, user_id_input, clock)?
+        .filter(|status| drunk_at >= status.start_time)
+        .map(|status| status.event_id);
+
+    let new_intake = NewFluidIntake {
+        user_id: user_id_input,
+        event_id: active_event_id,
+        kind: drink.as_str().to_string(),
+        amount_ml: millilitres,
+        consumed_at: drunk_at,
+        created_at: Some(clock.now()),
+    };
+
+    diesel::insert_into(fluid_intakes)
+        .values(&new_intake)
+        .execute(conn)
+        .map(|_| ())
+        .map_err(FastingAppError::DatabaseError)
+}
+
+/// ✅ Lists the drinks logged during one of the user's fasts, oldest first.
+pub fn list_intakes_for_event(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    fasting_event_id: i32,
+) -> Result<Vec<FluidIntake>, FastingAppError> {
+    fluid_intakes
+        .filter(user_id.eq(user_id_input))
+        .filter(event_id.eq(fasting_event_id))
+        .order(consumed_at.asc())
+        .select(FluidIntake::as_select())
+        .load::<FluidIntake>(conn)
+        .map_err(FastingAppError::DatabaseError)
+}
+
+/// ✅ Sets or clears the user's daily hydration target.
+pub fn set_daily_hydration_target(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    target_ml: Option<i32>,
+) -> Result<(), FastingAppError> {
+    use crate::schema::users::dsl::{daily_hydration_target_ml, id as users_id, users};
+
+    if target_ml.is_some_and(|target| target <= 0) {
+        return Err(FastingAppError::InvalidRequest(
+            "The hydration target must be positive.".to_string(),
+        ));
+    }
+
+    diesel::update(users.filter(users_id.eq(user_id_input)))
+        .set(daily_hydration_target_ml.eq(target_ml))
+        .execute(conn)
+        .map(|_| ())
+        .map_err(FastingAppError::DatabaseError)
+}
+
+/// ✅ Reports hydration during the ongoing fast against the daily target.
+///
+/// - Each started day of the fast adds one daily target, so a 30 hour fast
+///   is measured against two days' worth.
+/// - Returns `None` when the user has no ongoing fast.
+pub fn current_fast_hydration(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    clock: &dyn Clock,
+) -> Result<Option<HydrationProgress>, FastingAppError> {
+    use crate::schema::users::dsl::{daily_hydration_target_ml, id as users_id, users};
+
+    let Some(status) = get_current_fasting_status(conn, user_id_input, clock)? else {
+        return Ok(None);
+    };
+
+    let daily_target = users
+        .filter(users_id.eq(user_id_input))
+        .select(daily_hydration_target_ml)
+        .first::<Option<i32>>(conn)
+        .map_err(FastingAppError::DatabaseError)?;
+
+    let totals = hydration_totals_by_event(conn, user_id_input)?
+        .remove(&status.event_id)
+        .unwrap_or_default();
+
+    let wall_clock_hours = clock
+        .now()
+        .signed_duration_since(status.start_time)
+        .num_hours();
+    let days_spanned = wall_clock_hours / 24 + 1;
+
+    Ok(Some(HydrationProgress {
+        event_id: status.event_id,
+        totals,
+        target_ml: daily_target.map(|target| i64::from(target) * days_spanned),
+    }))
+}
+
+/// ✅ Sums the user's drinks per fasting event.
+///
+/// - Drinks logged outside a fast are not included.
+pub fn hydration_totals_by_event(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+) -> Result<HashMap<i32, HydrationTotals>, FastingAppError> {
+    let rows = fluid_intakes
+        .filter(user_id.eq(user_id_input))
+        .filter(event_id.is_not_null())
+        .group_by((event_id, kind))
+        .select((event_id, kind, diesel::dsl::sum(amount_ml)))
+        .load::<(Option<i32>, String, Option<i64>)>(conn)
+        .map_err(FastingAppError::DatabaseError)?;
+
+    let mut totals: HashMap<i32, HydrationTotals> = HashMap::new();
+    for (fast_id, drink, millilitres) in rows {
+        if let Some(fast_id) = fast_id {
+            totals
+                .entry(fast_id)
+                .or_default()
+                .add(&drink, millilitres.unwrap_or(0));
+        }
+    }
+    Ok(totals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        current_fast_hydration, hydration_totals_by_event, list_intakes_for_event, log_intake,
+        set_daily_hydration_target, IntakeKind,
+    };
+    use crate::clock::FakeClock;
+    use crate::db::{insert_test_user, test_connection};
+    use crate::handlers::fasting::{get_current_fasting_status, start_fasting, stop_fasting};
+    use crate::schema::fluid_intakes::dsl::{consumed_at, event_id, fluid_intakes, user_id};
+    use chrono::{NaiveDate, NaiveDateTime};
+    use diesel::prelude::*;
+    use diesel::SqliteConnection;
+
+    fn at(day: u32, hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 4, day).unwrap().and_hms_opt(hour, 0, 0).unwrap()
+    }
+
+    fn attached_events(conn: &mut SqliteConnection, user: i32) -> Vec<Option<i32>> {
+        fluid_intakes
+            .filter(user_id.eq(user))
+            .order(consumed_at.asc())
+            .select(event_id)
+            .load::<Option<i32>>(conn)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_drinks_attach_only_to_the_fast_they_fall_in() {
+        let mut conn = test_connection();
+        let user = insert_test_user(&mut conn, "drinker");
+        let clock = FakeClock::new(at(1, 8));
+
+        log_intake(&mut conn, user, IntakeKind::Water, 250, at(1, 7), &clock).unwrap();
+        start_fasting(&mut conn, user, at(1, 8), None, None, &clock).unwrap();
+        clock.set(at(1, 12));
+        log_intake(&mut conn, user, IntakeKind::Tea, 300, at(1, 6), &clock).unwrap();
+        log_intake(&mut conn, user, IntakeKind::Water, 500, at(1, 8), &clock).unwrap();
+
+        let fast = get_current_fasting_status(&mut conn, user, &clock).unwrap().unwrap().event_id;
+        assert_eq!(attached_events(&mut conn, user), vec![None, None, Some(fast)]);
+        assert_eq!(list_intakes_for_event(&mut conn, user, fast).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_totals_are_summed_per_fast_and_scaled_to_the_target() {
+        let mut conn = test_connection();
+        let user = insert_test_user(&mut conn, "drinker");
+        let clock = FakeClock::new(at(1, 8));
+        set_daily_hydration_target(&mut conn, user, Some(2000)).unwrap();
+        assert!(current_fast_hydration(&mut conn, user, &clock).unwrap().is_none());
+
+        start_fasting(&mut conn, user, at(1, 8), None, None, &clock).unwrap();
+        clock.set(at(2, 14));
+        log_intake(&mut conn, user, IntakeKind::Water, 500, at(1, 9), &clock).unwrap();
+        log_intake(&mut conn, user, IntakeKind::Water, 700, at(1, 20), &clock).unwrap();
+        log_intake(&mut conn, user, IntakeKind::BlackCoffee, 200, at(2, 10), &clock).unwrap();
+
+        // 30 hours into the fast counts two daily targets
+        let progress = current_fast_hydration(&mut conn, user, &clock).unwrap().unwrap();
+        assert_eq!(progress.target_ml, Some(4000));
+        assert_eq!(progress.totals.total_ml, 1400);
+        assert_eq!(progress.totals.water_ml, 1200);
+        assert_eq!(progress.totals.black_coffee_ml, 200);
+        assert_eq!(progress.percent_of_target(), Some(35.0));
+
+        stop_fasting(&mut conn, user, at(2, 14), None, &clock).unwrap();
+        log_intake(&mut conn, user, IntakeKind::Water, 400, at(2, 15), &clock).unwrap();
+        let totals = hydration_totals_by_event(&mut conn, user).unwrap();
+        assert_eq!(totals.len(), 1);
+        assert_eq!(totals[&progress.event_id].total_ml, 1400);
+    }
+}
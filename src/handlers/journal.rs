@@ -0,0 +1,193 @@
+use crate::clock::Clock;
+use crate::errors::FastingAppError;
+use crate::handlers::fasting::{find_ongoing_fasting_event, find_user_fasting_event};
+use crate::models::{FastJournalEntry, FastingEvent, NewFastJournalEntry};
+use crate::schema::fast_journal_entries::dsl::{entry_time, event_id, fast_journal_entries};
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel::SqliteConnection;
+
+/// Symptom tags offered by the menu; any other tag is accepted as well.
+pub const COMMON_SYMPTOMS: [&str; 7] = [
+    "headache",
+    "dizziness",
+    "nausea",
+    "fatigue",
+    "irritability",
+    "cravings",
+    "brain-fog",
+];
+
+/// The fields of a journal entry supplied by the user.
+#[derive(Debug, Default)]
+pub struct JournalEntryInput {
+    pub entry_time: Option<NaiveDateTime>, // Defaults to now
+    pub note: Option<String>,
+    pub mood: Option<i32>,
+    pub hunger: Option<i32>,
+    pub energy: Option<i32>,
+    pub symptoms: Vec<String>,
+}
+
+impl FastJournalEntry {
+    /// ✅ Splits the stored symptom list into individual tags.
+    pub fn symptom_tags(&self) -> Vec<&str> {
+        self.symptoms
+            .as_deref()
+            .map(|tags| tags.split(',').filter(|tag| !tag.is_empty()).collect())
+            .unwrap_or_default()
+    }
+}
+
+/// ✅ Adds a journal entry to one of the user's fasts.
+///
+/// - With `fasting_event_id` set to `None` the entry goes to the ongoing fast.
+/// - Scores must be between 1 and 10, and the entry must fall within the fast.
+/// - Without an `entry_time` the entry is logged at `clock.now()`.
+pub fn add_journal_entry(
+    conn: &mut SqliteConnection,
+    user_id: i32,
+    fasting_event_id: Option<i32>,
+    input: JournalEntryInput,
+    clock: &dyn Clock,
+) -> Result<(), FastingAppError> {
+    let event = resolve_event(conn, user_id, fasting_event_id)?;
+
+    for (field, score) in [("mood", input.mood), ("hunger", input.hunger), ("energy", input.energy)] {
+        if let Some(value) = score {
+            if !(1..=10).contains(&value) {
+                return Err(FastingAppError::InvalidRequest(format!(
+                    "{} must be between 1 and 10, got {}.",
+                    field, value
+                )));
+            }
+        }
+    }
+
+    let note = input.note.map(|text| text.trim().to_string()).filter(|text| !text.is_empty());
+    let symptoms = normalize_symptoms(&input.symptoms);
+
+    if note.is_none()
+        && symptoms.is_none()
+        && input.mood.is_none()
+        && input.hunger.is_none()
+        && input.energy.is_none()
+    {
+        return Err(FastingAppError::InvalidRequest(
+            "A journal entry needs a note, a score or a symptom.".to_string(),
+        ));
+    }
+
+    let now = clock.now();
+    let logged_at = input.entry_time.unwrap_or(now);
+    let fast_end = event.stop_time.unwrap_or(now);
+    if logged_at < event.start_time || logged_at > fast_end {
+        return Err(FastingAppError::InvalidRequest(format!(
+            "Journal entries must fall between {} and {}.",
+            event.start_time, fast_end
+        )));
+    }
+
+    let new_entry = NewFastJournalEntry {
+        event_id: event.id,
+        entry_time: logged_at,
+        note,
+        mood: input.mood,
+        hunger: input.hunger,
+        energy: input.energy,
+        symptoms,
+        created_at: Some(now),
+    };
+
+    diesel::insert_into(fast_journal_entries)
+        .values(&new_entry)
+        .execute(conn)
+        .map(|_| ())
+        .map_err(FastingAppError::DatabaseError)
+}
+
+/// ✅ Lists the journal entries of one of the user's fasts, oldest first.
+///
+/// - With `fasting_event_id` set to `None` the ongoing fast is used.
+pub fn list_journal_entries(
+    conn: &mut SqliteConnection,
+    user_id: i32,
+    fasting_event_id: Option<i32>,
+) -> Result<Vec<FastJournalEntry>, FastingAppError> {
+    let event = resolve_event(conn, user_id, fasting_event_id)?;
+
+    fast_journal_entries
+        .filter(event_id.eq(event.id))
+        .order(entry_time.asc())
+        .select(FastJournalEntry::as_select())
+        .load::<FastJournalEntry>(conn)
+        .map_err(FastingAppError::DatabaseError)
+}
+
+/// Finds the requested event, or the ongoing fast when no ID is given.
+fn resolve_event(
+    conn: &mut SqliteConnection,
+    user_id: i32,
+    fasting_event_id: Option<i32>,
+) -> Result<FastingEvent, FastingAppError> {
+    match fasting_event_id {
+        Some(requested_id) => find_user_fasting_event(conn, user_id, requested_id),
+        None => find_ongoing_fasting_event(conn, user_id),
+    }
+}
+
+/// Lowercases, deduplicates and joins symptom tags for storage.
+fn normalize_symptoms(symptoms: &[String]) -> Option<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for symptom in symptoms {
+        let tag = symptom
+            .trim()
+            .to_lowercase()
+            .replace([',', ' '], "-");
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    (!tags.is_empty()).then(|| tags.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FakeClock;
+    use crate::db::{insert_test_user, test_connection};
+    use crate::handlers::fasting::start_fasting;
+    use chrono::NaiveDate;
+
+    fn at(hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 4, 1).unwrap().and_hms_opt(hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_entries_are_validated_and_listed_per_fast() {
+        let mut conn = test_connection();
+        let alice = insert_test_user(&mut conn, "alice");
+        let bob = insert_test_user(&mut conn, "bob");
+        let clock = FakeClock::new(at(10));
+        start_fasting(&mut conn, alice, at(6), None, None, &clock).unwrap();
+
+        let scored = |hunger: i32| JournalEntryInput { hunger: Some(hunger), ..Default::default() };
+        assert!(add_journal_entry(&mut conn, alice, None, scored(11), &clock).is_err());
+        assert!(add_journal_entry(&mut conn, alice, None, JournalEntryInput::default(), &clock).is_err());
+        let too_early = JournalEntryInput { entry_time: Some(at(5)), ..scored(3) };
+        assert!(add_journal_entry(&mut conn, alice, None, too_early, &clock).is_err());
+
+        let symptoms = vec!["Headache".to_string(), " brain fog".to_string(), "headache".to_string()];
+        add_journal_entry(&mut conn, alice, None, JournalEntryInput { symptoms, ..scored(7) }, &clock).unwrap();
+        add_journal_entry(&mut conn, alice, None, JournalEntryInput { entry_time: Some(at(8)), ..scored(4) }, &clock)
+            .unwrap();
+
+        let entries = list_journal_entries(&mut conn, alice, None).unwrap();
+        assert_eq!(entries.iter().map(|entry| entry.hunger).collect::<Vec<_>>(), vec![Some(4), Some(7)]);
+        assert_eq!(entries[1].symptom_tags(), vec!["headache", "brain-fog"]);
+
+        let fast = entries[0].event_id;
+        assert!(list_journal_entries(&mut conn, bob, Some(fast)).is_err());
+        assert!(add_journal_entry(&mut conn, bob, Some(fast), scored(5), &clock).is_err());
+    }
+}
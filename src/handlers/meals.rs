@@ -0,0 +1,140 @@
+use crate::errors::FastingAppError;
+use crate::handlers::fasting::find_user_fasting_event;
+use crate::models::{BreakFastMeal, NewBreakFastMeal};
+use crate::schema::break_fast_meals::dsl::{break_fast_meals, eaten_at, event_id};
+use chrono::{NaiveDateTime, Utc};
+use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use diesel::SqliteConnection;
+use serde::Deserialize;
+
+/// The meal a fast is broken with, as entered by the user.
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct BreakFastMealInput {
+    pub description: String,
+    pub calories_min: Option<i32>, // Approximate range; a single estimate sets both ends
+    pub calories_max: Option<i32>,
+    pub protein_g: Option<f64>,
+    pub carbs_g: Option<f64>,
+    pub fat_g: Option<f64>,
+}
+
+impl BreakFastMeal {
+    /// ✅ Middle of the calorie range, or the one end that was given.
+    pub fn calorie_estimate(&self) -> Option<i32> {
+        match (self.calories_min, self.calories_max) {
+            (Some(low), Some(high)) => Some((low + high) / 2),
+            (low, high) => low.or(high),
+        }
+    }
+}
+
+/// ✅ Stores the meal that broke one of the user's fasts.
+///
+/// - The fast must be finished; `eaten_at` defaults to its stop time.
+/// - A fast has at most one break-fast meal.
+pub fn log_break_fast_meal(
+    conn: &mut SqliteConnection,
+    user_id: i32,
+    fasting_event_id: i32,
+    input: &BreakFastMealInput,
+) -> Result<(), FastingAppError> {
+    let event = find_user_fasting_event(conn, user_id, fasting_event_id)?;
+    let Some(stopped_at) = event.stop_time else {
+        return Err(FastingAppError::SessionError(
+            "A break-fast meal can only be logged once the fast has ended.".to_string(),
+        ));
+    };
+    insert_break_fast_meal(conn, event.id, stopped_at, input)
+}
+
+/// ✅ Returns the meal that broke one of the user's fasts, if one was logged.
+pub fn get_break_fast_meal(
+    conn: &mut SqliteConnection,
+    user_id: i32,
+    fasting_event_id: i32,
+) -> Result<Option<BreakFastMeal>, FastingAppError> {
+    let event = find_user_fasting_event(conn, user_id, fasting_event_id)?;
+
+    break_fast_meals
+        .filter(event_id.eq(event.id))
+        .select(BreakFastMeal::as_select())
+        .first::<BreakFastMeal>(conn)
+        .optional()
+        .map_err(FastingAppError::DatabaseError)
+}
+
+/// ✅ Lists every break-fast meal the user has logged, oldest first.
+pub fn list_break_fast_meals(
+    conn: &mut SqliteConnection,
+    user_id: i32,
+) -> Result<Vec<BreakFastMeal>, FastingAppError> {
+    use crate::schema::fasting_events::dsl::{fasting_events, user_id as event_user_id};
+
+    break_fast_meals
+        .inner_join(fasting_events)
+        .filter(event_user_id.eq(user_id))
+        .order(eaten_at.asc())
+        .select(BreakFastMeal::as_select())
+        .load::<BreakFastMeal>(conn)
+        .map_err(FastingAppError::DatabaseError)
+}
+
+/// Validates a meal and inserts it for `fasting_event_id`; callers check ownership.
+pub(crate) fn insert_break_fast_meal(
+    conn: &mut SqliteConnection,
+    fasting_event_id: i32,
+    eaten: NaiveDateTime,
+    input: &BreakFastMealInput,
+) -> Result<(), FastingAppError> {
+    let description = input.description.trim();
+    if description.is_empty() {
+        return Err(FastingAppError::InvalidRequest(
+            "Describe what the fast was broken with.".to_string(),
+        ));
+    }
+    if input.calories_min.is_some_and(|kcal| kcal < 0) || input.calories_max.is_some_and(|kcal| kcal < 0) {
+        return Err(FastingAppError::InvalidRequest(
+            "Calories cannot be negative.".to_string(),
+        ));
+    }
+    if let (Some(low), Some(high)) = (input.calories_min, input.calories_max) {
+        if low > high {
+            return Err(FastingAppError::InvalidRequest(format!(
+                "The calorie range {}-{} is reversed.",
+                low, high
+            )));
+        }
+    }
+    for (macro_name, grams) in [("protein", input.protein_g), ("carbs", input.carbs_g), ("fat", input.fat_g)] {
+        if grams.is_some_and(|g| !g.is_finite() || g < 0.0) {
+            return Err(FastingAppError::InvalidRequest(format!(
+                "{} must be a non-negative number of grams.",
+                macro_name
+            )));
+        }
+    }
+
+    let new_meal = NewBreakFastMeal {
+        event_id: fasting_event_id,
+        description: description.to_string(),
+        calories_min: input.calories_min,
+        calories_max: input.calories_max,
+        protein_g: input.protein_g,
+        carbs_g: input.carbs_g,
+        fat_g: input.fat_g,
+        eaten_at: eaten,
+        created_at: Some(Utc::now().naive_utc()),
+    };
+
+    diesel::insert_into(break_fast_meals)
+        .values(&new_meal)
+        .execute(conn)
+        .map(|_| ())
+        .map_err(|err| match err {
+            DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => FastingAppError::InvalidRequest(
+                "A break-fast meal is already logged for this fast.".to_string(),
+            ),
+            other => FastingAppError::DatabaseError(other),
+        })
+}
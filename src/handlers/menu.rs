@@ -1,25 +1,73 @@
-use chrono::Utc;
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use chrono_tz::Tz;
 use diesel::SqliteConnection;
 use std::io::{self, Write};
 
+use crate::auth::{list_active_sessions, revoke_all_sessions, revoke_session, sign_in_local, sign_in_local_with_device};
+use crate::clock::Clock;
+use crate::errors::FastingAppError;
 use crate::handlers::fasting::{
-    get_current_fasting_status, start_fasting, stop_fasting, get_user_fasting_sessions,remove_fasting_goal, update_fasting_goal
+    start_fasting, stop_fasting, remove_fasting_goal, update_fasting_goal,
+    pause_fasting, resume_fasting, FastingStatus, record_completed_fast, edit_fasting_event,
+    delete_fasting_event, get_user_fasting_events,
 };
 use crate::handlers::analytics::{
     calculate_average_fasting_duration, calculate_total_fasting_time, show_fasting_history,
 };
-use crate::handlers::goals::{add_goal, view_goals};
+use crate::handlers::charts::{
+    daily_fasting_chart, fast_duration_histogram, fasting_heatmap, terminal_width, MAX_CHART_DAYS,
+};
+use crate::handlers::goals::{
+    create_goal, create_recurring_goal, delete_goal, delete_recurring_goal, list_active_goals, list_goals,
+    list_recurring_goals, set_goal_archived, update_goal, view_goals, RecurrencePeriod, EVERY_DAY_MASK,
+    WEEKDAYS_MASK,
+};
+use crate::handlers::programs::{
+    delete_program, drop_out_of_program, enroll_in_program, get_program_days, get_program_progress,
+    import_program, list_enrollments, list_programs, ProgramDayOutcome, ProgramProgress,
+};
+use crate::handlers::protocols::{create_protocol, delete_protocol, list_protocols};
+use crate::handlers::analytics::{
+    aggregate_fasting_by_period, calculate_streaks, BucketSize, PeriodStats, StreakReport,
+    DEFAULT_STREAK_THRESHOLD_MINUTES,
+    calculate_weight_change, find_large_refeeds, journal_scores_by_fasting_hour, summarize_break_fast_patterns,
+    summarize_fasts_by_protocol,
+};
+use crate::handlers::body_metrics::{
+    add_measurement, delete_measurement, get_unit_preferences, list_measurements, set_unit_preferences,
+    update_measurement, MeasurementKind, UnitPreferences,
+};
+use crate::export::{export_to_csv, export_to_json};
+use crate::handlers::hydration::{
+    current_fast_hydration, hydration_totals_by_event, log_intake, set_daily_hydration_target, HydrationProgress,
+    IntakeKind,
+};
+use crate::handlers::meals::{log_break_fast_meal, BreakFastMealInput};
+use crate::handlers::journal::{add_journal_entry, list_journal_entries, JournalEntryInput, COMMON_SYMPTOMS};
+use crate::handlers::stages::{get_fasting_stage_status, StageModel, StageStatus};
+use crate::handlers::timezone::{
+    format_local, get_user_timezone, local_to_utc, local_today, set_user_timezone, to_local, DEFAULT_TIMEZONE,
+};
+use crate::handlers::schedule::{
+    add_schedule_window, delete_schedule_window, list_schedule_windows, review_schedule, PlannedFastStatus,
+};
 
 use crate::users::find::find_user_by_id;
-use crate::users::login::{login, associate_device_id};
+use crate::users::devices::{create_pairing_code, list_devices, redeem_pairing_code, revoke_device};
 use crate::users::create::create_user;
+use crate::users::lockout::LockoutPolicy;
+use crate::users::password::{change_password, PasswordPolicy};
 
-use crate::models::User;
+use crate::models::{FastingEvent, User};
+
+/// Device name recorded for sessions started from this menu.
+const CLI_DEVICE_NAME: &str = "Command line";
 
 /// ✅ Displays the main menu and routes users to submenus after login.
 /// ✅ Displays the main menu and routes users to submenus after login.
-pub fn display_main_menu(conn: &mut SqliteConnection) {
-    let mut user: Option<User> = None;
+pub fn display_main_menu(conn: &mut SqliteConnection, clock: &dyn Clock) {
+    // The signed-in user, with the session started for them if they used a password
+    let mut user: Option<(User, Option<i32>)> = None;
 
     loop {
         println!("\nWelcome to Fasting-Rust! 🚀");
@@ -28,8 +76,8 @@ pub fn display_main_menu(conn: &mut SqliteConnection) {
         println!("3. Exit");
 
         match prompt_user_choice("Enter your choice (1-3): ") {
-            Some(1) => user = handle_login_menu(conn),
-            Some(2) => user = handle_register_menu(conn),
+            Some(1) => user = handle_login_menu(conn, clock),
+            Some(2) => user = handle_register_menu(conn).map(|registered| (registered, None)),
             Some(3) => {
                 println!("👋 Exiting... Goodbye!");
                 break;
@@ -38,27 +86,38 @@ pub fn display_main_menu(conn: &mut SqliteConnection) {
         }
 
         // ✅ If user successfully logs in, route them to the main app menu
-        if let Some(ref u) = user {
+        if let Some((ref u, session_id)) = user {
             println!("✅ Welcome, {}! You are now logged in.", u.username);
-            return display_authenticated_menu(conn, u);  // ✅ Call new menu function
+            return display_authenticated_menu(conn, u, session_id, clock);  // ✅ Call new menu function
         }
     }
 }
 
 /// ✅ Displays the **Authenticated User Menu** after login.
-fn display_authenticated_menu(conn: &mut SqliteConnection, user: &User) {
+///
+/// - Logging out signs out `session_id`, if the login started a session.
+fn display_authenticated_menu(conn: &mut SqliteConnection, user: &User, session_id: Option<i32>, clock: &dyn Clock) {
     loop {
         println!("\n📌 **Main Menu**");
         println!("1. Fasting Menu");
         println!("2. Analytics Menu");
-        println!("3. Account Settings");
-        println!("4. Logout");
+        println!("3. Body Metrics");
+        println!("4. Account Settings");
+        println!("5. Logout");
 
-        match prompt_user_choice("Enter your choice (1-4): ") {
-            Some(1) => handle_fasting_menu(conn, user),
-            Some(2) => handle_analytics_menu(conn, user),
-            Some(3) => handle_account_settings(conn, user),
-            Some(4) => {
+        match prompt_user_choice("Enter your choice (1-5): ") {
+            Some(1) => handle_fasting_menu(conn, user, clock),
+            Some(2) => handle_analytics_menu(conn, user, clock),
+            Some(3) => handle_body_metrics_menu(conn, user, clock),
+            Some(4) => handle_account_settings(conn, user, session_id, clock),
+            Some(5) => {
+                if let Some(session_id) = session_id {
+                    match revoke_session(conn, user.id, session_id, clock) {
+                        // Already signed out from the session list
+                        Ok(()) | Err(FastingAppError::AuthSessionNotFound(_)) => {}
+                        Err(e) => eprintln!("❌ Failed to end the session: {}", e),
+                    }
+                }
                 println!("👋 Logged out. Returning to main screen...");
                 break;
             }
@@ -73,139 +132,1033 @@ fn handle_register_menu(conn: &mut SqliteConnection) -> Option<User> {
     let username = prompt_user_input("Enter your desired username: ");
     let password = prompt_user_input("Enter your password: ");
 
-    match create_user(conn, &username, &password) {
+    let policy = match PasswordPolicy::from_env() {
+        Ok(policy) => policy,
+        Err(e) => {
+            eprintln!("❌ Registration failed: {}", e);
+            return None;
+        }
+    };
+
+    match create_user(conn, &username, &password, &policy) {
         Ok(_) => {
             println!("✅ Registration successful! You can now log in.");
             None
         }
         Err(e) => {
-            eprintln!("❌ Registration failed: {}", e);
+            eprintln!("❌ Registration failed: {}", e.user_friendly_message());
             None
         }
     }
 }
 
 /// ✅ Handles user login and authentication.
-fn handle_login_menu(conn: &mut SqliteConnection) -> Option<User> {
+///
+/// - Every login starts a session, signed with `AUTH_SECRET`, that is ended on logout.
+/// - A new device is paired with a code generated from Account Settings on a signed-in device.
+fn handle_login_menu(conn: &mut SqliteConnection, clock: &dyn Clock) -> Option<(User, Option<i32>)> {
+    // ✅ Sessions started here sign no tokens, so the menu works without AUTH_SECRET
+    let lockout = match LockoutPolicy::from_env() {
+        Ok(lockout) => lockout,
+        Err(e) => {
+            eprintln!("❌ Login failed: {}", e);
+            return None;
+        }
+    };
+
     loop {
         println!("\nLogin Menu:");
         println!("1. Login with Username & Password");
-        println!("2. Login with Device ID");
-        println!("3. Back to Main Menu");
+        println!("2. Login with a Device Credential");
+        println!("3. Pair This Device With a Code");
+        println!("4. Back to Main Menu");
 
-        match prompt_user_choice("Enter your choice (1-3): ") {
+        match prompt_user_choice("Enter your choice (1-4): ") {
             Some(1) => {
                 let username = prompt_user_input("Enter your username: ");
                 let password = prompt_user_input("Enter your password: ");
 
-                match login(conn, Some(&username), Some(&password), None) {
-                    Ok(user) => {
+                match sign_in_local(conn, &lockout, &username, &password, Some(CLI_DEVICE_NAME), clock) {
+                    Ok((user, session_id)) => {
                         println!("✅ Login successful! Welcome, {}.", user.username);
-                        return Some(user);
+                        return Some((user, Some(session_id)));
                     }
                     Err(e) => eprintln!("❌ Login failed: {}", e),
                 }
             }
             Some(2) => {
-                let device_id = prompt_user_input("Enter your device ID: ");
+                let credential = prompt_user_input("Enter your device credential: ");
 
-                match login(conn, None, None, Some(&device_id)) {
-                    Ok(user) => {
+                match sign_in_local_with_device(conn, &credential, clock) {
+                    Ok((user, session_id)) => {
                         println!("✅ Device login successful! Welcome, {}.", user.username);
-                        return Some(user);
+                        return Some((user, Some(session_id)));
                     }
                     Err(e) => eprintln!("❌ Login failed: {}", e),
                 }
             }
-            Some(3) => return None, // Go back to main menu
+            Some(3) => {
+                let code = prompt_user_input("Enter the pairing code: ");
+                let device_name = prompt_user_input("Name this device: ");
+
+                let paired = match redeem_pairing_code(conn, &code, &device_name, clock) {
+                    Ok(paired) => paired,
+                    Err(e) => {
+                        eprintln!("❌ Pairing failed: {}", e.user_friendly_message());
+                        continue;
+                    }
+                };
+                println!("✅ Device paired. Your device credential is:\n\n    {}\n", paired.credential);
+                println!("Keep it somewhere safe; it is shown only once.");
+
+                match sign_in_local_with_device(conn, &paired.credential, clock) {
+                    Ok((user, session_id)) => {
+                        println!("✅ Welcome, {}.", user.username);
+                        return Some((user, Some(session_id)));
+                    }
+                    Err(e) => eprintln!("❌ Login failed: {}", e),
+                }
+            }
+            Some(4) => return None, // Go back to main menu
             _ => println!("❌ Invalid choice. Please select a valid option."),
         }
     }
 }
 
 /// ✅ Handles the **Fasting Menu**.
-fn handle_fasting_menu(conn: &mut SqliteConnection, user: &User) {
+fn handle_fasting_menu(conn: &mut SqliteConnection, user: &User, clock: &dyn Clock) {
+    let tz = user_timezone(conn, user);
     loop {
         println!("\n🔥 **Fasting Menu**:");
         println!("1. Start Fasting");
         println!("2. Stop Fasting");
-        println!("3. View Fasting Status");
-        println!("4. Add Goal");
-        println!("5. View Goals");
-        println!("6. Update Fasting Goal");
-        println!("7. Remove Fasting Goal");
-        println!("8. Back to Main Menu");
+        println!("3. Pause Fasting");
+        println!("4. Resume Fasting");
+        println!("5. View Fasting Status");
+        println!("6. Manage Fast History");
+        println!("7. Add Goal");
+        println!("8. Manage Goals");
+        println!("9. Update Fasting Goal");
+        println!("10. Remove Fasting Goal");
+        println!("11. Fasting Protocols");
+        println!("12. Weekly Schedule");
+        println!("13. Fast Journal");
+        println!("14. Hydration");
+        println!("15. Challenge Programs");
+        println!("16. Back to Main Menu");
 
-        match prompt_user_choice("Enter your choice (1-8): ") {
+        match prompt_user_choice("Enter your choice (1-16): ") {
             Some(1) => {
-                let goal_id = prompt_optional_goal_id();
-                if let Err(e) = start_fasting(conn, user.id, Utc::now().naive_utc(), goal_id) {
+                let goal_id = prompt_optional_goal_id(conn, user, clock);
+                let protocol_id = prompt_optional_protocol_id(conn, user);
+                if let Err(e) = start_fasting(conn, user.id, clock.now(), goal_id, protocol_id, clock) {
                     eprintln!("❌ Error starting fasting session: {}", e);
                 } else {
                     println!("✅ Fasting session started successfully.");
                 }
             }
             Some(2) => {
-                if let Err(e) = stop_fasting(conn, user.id, Utc::now().naive_utc()) {
+                let meal = prompt_break_fast_meal();
+                if let Err(e) = stop_fasting(conn, user.id, clock.now(), meal.as_ref(), clock) {
                     eprintln!("❌ Error stopping fasting session: {}", e);
                 } else {
                     println!("✅ Fasting session stopped successfully.");
                 }
             }
-            Some(3) => match get_current_fasting_status(conn, user.id) {
-                Ok(Some((start_time, duration))) => println!(
-                    "⏳ Fasting started at {} and has lasted for {} minutes.",
-                    start_time, duration
-                ),
-                Ok(None) => println!("❌ No active fasting session found."),
-                Err(e) => eprintln!("❌ Error retrieving fasting status: {}", e),
-            },
+            Some(3) => {
+                if let Err(e) = pause_fasting(conn, user.id, clock.now(), clock) {
+                    eprintln!("❌ Error pausing fasting session: {}", e);
+                } else {
+                    println!("⏸️ Fasting session paused. Resume it when you are ready.");
+                }
+            }
             Some(4) => {
-                if let Err(e) = add_goal(user, conn) {
-                    eprintln!("❌ Error adding goal: {}", e);
+                if let Err(e) = resume_fasting(conn, user.id, clock.now(), clock) {
+                    eprintln!("❌ Error resuming fasting session: {}", e);
                 } else {
-                    println!("✅ Goal added successfully.");
+                    println!("▶️ Fasting session resumed.");
                 }
             }
-            Some(5) => {
-                if let Err(e) = view_goals(user, conn) {
-                    eprintln!("❌ Error viewing goals: {}", e);
+            Some(5) => match get_fasting_stage_status(conn, user.id, &StageModel::default(), clock) {
+                Ok(Some(status)) => {
+                    print_fasting_status(&status.fasting, &tz);
+                    print_stage_status(&status);
+                    if let Ok(Some(progress)) = current_fast_hydration(conn, user.id, clock) {
+                        print_hydration_progress(&progress);
+                    }
+                }
+                Ok(None) => println!("❌ No active fasting session found."),
+                Err(e) => eprintln!("❌ Error retrieving fasting status: {}", e),
+            },
+            Some(6) => handle_fast_history_menu(conn, user, clock),
+            Some(7) => {
+                let Some(hours) = prompt_user_choice("Enter goal duration in hours: ") else {
+                    println!("❌ Invalid number of hours.");
+                    continue;
+                };
+                let Some(deadline) = prompt_datetime("Enter the deadline (YYYY-MM-DD HH:MM): ", &tz) else {
+                    continue;
+                };
+                match create_goal(conn, user.id, hours, deadline, clock) {
+                    Ok(goal) => println!("✅ Goal {} added successfully.", goal.id),
+                    Err(e) => eprintln!("❌ Error adding goal: {}", e),
                 }
             }
-            Some(6) => {
-                let new_goal_id = prompt_optional_goal_id();
-                if let Err(e) = update_fasting_goal(conn, user.id, new_goal_id) {
+            Some(8) => handle_goals_menu(conn, user, clock),
+            Some(9) => {
+                let new_goal_id = prompt_optional_goal_id(conn, user, clock);
+                if let Err(e) = update_fasting_goal(conn, user.id, new_goal_id, clock) {
                     eprintln!("❌ Error updating fasting goal: {}", e);
                 } else {
                     println!("✅ Fasting goal updated successfully.");
                 }
             }
-            Some(7) => {
+            Some(10) => {
                 if let Err(e) = remove_fasting_goal(conn, user.id) {
                     eprintln!("❌ Error removing fasting goal: {}", e);
                 } else {
                     println!("✅ Fasting goal removed successfully.");
                 }
             }
+            Some(11) => handle_protocols_menu(conn, user),
+            Some(12) => handle_schedule_menu(conn, user, clock),
+            Some(13) => handle_journal_menu(conn, user, clock),
+            Some(14) => handle_hydration_menu(conn, user, clock),
+            Some(15) => handle_programs_menu(conn, user, clock),
+            Some(16) => break,
+            _ => println!("❌ Invalid choice. Please select a valid option."),
+        }
+    }
+}
+
+/// ✅ Handles viewing, editing, archiving and scheduling fasting goals.
+fn handle_goals_menu(conn: &mut SqliteConnection, user: &User, clock: &dyn Clock) {
+    let tz = user_timezone(conn, user);
+    loop {
+        println!("\n🎯 **Goals**:");
+        println!("1. View Goals");
+        println!("2. Edit a Goal");
+        println!("3. Delete a Goal");
+        println!("4. Archive a Goal");
+        println!("5. Restore an Archived Goal");
+        println!("6. View Archived Goals");
+        println!("7. Add a Recurring Goal");
+        println!("8. View Recurring Goals");
+        println!("9. Stop a Recurring Goal");
+        println!("10. Back to Fasting Menu");
+
+        match prompt_user_choice("Enter your choice (1-10): ") {
+            Some(1) => {
+                if let Err(e) = view_goals(user, conn, clock) {
+                    eprintln!("❌ Error viewing goals: {}", e);
+                }
+            }
+            Some(2) => {
+                let Some(goal_id) = prompt_user_choice("Enter the goal ID to edit: ") else {
+                    continue;
+                };
+                let Some(hours) = prompt_user_choice("Enter the new duration in hours: ") else {
+                    println!("❌ Invalid number of hours.");
+                    continue;
+                };
+                let Some(deadline) = prompt_datetime("Enter the new deadline (YYYY-MM-DD HH:MM): ", &tz) else {
+                    continue;
+                };
+                match update_goal(conn, user.id, goal_id, hours, deadline) {
+                    Ok(_) => println!("✅ Goal updated."),
+                    Err(e) => eprintln!("❌ Error updating goal: {}", e),
+                }
+            }
+            Some(3) => {
+                let Some(goal_id) = prompt_user_choice("Enter the goal ID to delete: ") else {
+                    continue;
+                };
+                match delete_goal(conn, user.id, goal_id) {
+                    Ok(_) => println!("✅ Goal deleted."),
+                    Err(e) => eprintln!("❌ Error deleting goal: {}", e),
+                }
+            }
+            Some(choice @ (4 | 5)) => {
+                let archive = choice == 4;
+                if !archive {
+                    print_archived_goals(conn, user, &tz, clock);
+                }
+                let Some(goal_id) = prompt_user_choice("Enter the goal ID: ") else {
+                    continue;
+                };
+                match set_goal_archived(conn, user.id, goal_id, archive, clock) {
+                    Ok(_) if archive => println!("✅ Goal archived."),
+                    Ok(_) => println!("✅ Goal restored."),
+                    Err(e) => eprintln!("❌ Error updating goal: {}", e),
+                }
+            }
+            Some(6) => print_archived_goals(conn, user, &tz, clock),
+            Some(7) => {
+                let period = match prompt_user_input("Repeat daily or weekly? ").parse::<RecurrencePeriod>() {
+                    Ok(period) => period,
+                    Err(e) => {
+                        println!("❌ {}", e);
+                        continue;
+                    }
+                };
+                let weekdays = match period {
+                    RecurrencePeriod::Daily => {
+                        let Some(days) = prompt_weekdays() else {
+                            continue;
+                        };
+                        days
+                    }
+                    RecurrencePeriod::Weekly => Vec::new(),
+                };
+                let Some(hours) = prompt_user_choice("Enter goal duration in hours: ") else {
+                    println!("❌ Invalid number of hours.");
+                    continue;
+                };
+                let today = local_today(&tz, clock);
+                match create_recurring_goal(conn, user.id, hours, period, &weekdays, today, clock) {
+                    Ok(_) => println!("✅ Recurring goal added."),
+                    Err(e) => eprintln!("❌ Error adding recurring goal: {}", e),
+                }
+            }
+            Some(8) => print_recurring_goals(conn, user),
+            Some(9) => {
+                print_recurring_goals(conn, user);
+                let Some(recurring_goal_id) = prompt_user_choice("Enter the recurring goal ID to stop: ") else {
+                    continue;
+                };
+                match delete_recurring_goal(conn, user.id, recurring_goal_id) {
+                    Ok(_) => println!("✅ Recurring goal stopped. Goals it already created are kept."),
+                    Err(e) => eprintln!("❌ Error stopping recurring goal: {}", e),
+                }
+            }
+            Some(10) => break,
+            _ => println!("❌ Invalid choice. Please select a valid option."),
+        }
+    }
+}
+
+/// ✅ Prints the user's archived goals.
+fn print_archived_goals(conn: &mut SqliteConnection, user: &User, tz: &Tz, clock: &dyn Clock) {
+    match list_goals(conn, user.id, true, clock) {
+        Ok(goals) => {
+            let archived: Vec<_> = goals.into_iter().filter(|goal| goal.archived_at.is_some()).collect();
+            if archived.is_empty() {
+                println!("❌ No archived goals.");
+            }
+            for goal in archived {
+                println!(
+                    "- Goal ID: {}, Duration: {}h, Deadline: {}, Status: {}",
+                    goal.id,
+                    goal.goal_duration,
+                    format_local(tz, goal.deadline),
+                    goal.status
+                );
+            }
+        }
+        Err(e) => eprintln!("❌ Error retrieving goals: {}", e),
+    }
+}
+
+/// ✅ Prints the user's recurring goals.
+fn print_recurring_goals(conn: &mut SqliteConnection, user: &User) {
+    match list_recurring_goals(conn, user.id) {
+        Ok(templates) if templates.is_empty() => println!("❌ No recurring goals."),
+        Ok(templates) => {
+            for template in templates {
+                let days = match template.weekdays {
+                    EVERY_DAY_MASK => "every day".to_string(),
+                    WEEKDAYS_MASK => "weekdays".to_string(),
+                    mask => [
+                        Weekday::Mon,
+                        Weekday::Tue,
+                        Weekday::Wed,
+                        Weekday::Thu,
+                        Weekday::Fri,
+                        Weekday::Sat,
+                        Weekday::Sun,
+                    ]
+                    .iter()
+                    .filter(|day| mask & (1 << day.num_days_from_monday()) != 0)
+                    .map(|day| day.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                };
+                let schedule = if template.period == "weekly" { "every week".to_string() } else { days };
+                println!(
+                    "- ID: {} | {}h {} | since {}",
+                    template.id, template.goal_duration, schedule, template.starts_on
+                );
+            }
+        }
+        Err(e) => eprintln!("❌ Error retrieving recurring goals: {}", e),
+    }
+}
+
+/// ✅ Handles logging, correcting and deleting past fasts.
+fn handle_fast_history_menu(conn: &mut SqliteConnection, user: &User, clock: &dyn Clock) {
+    let tz = user_timezone(conn, user);
+    loop {
+        println!("\n🗂️ **Fast History**:");
+        println!("1. Log a Past Fast");
+        println!("2. Edit a Fast");
+        println!("3. Delete a Fast");
+        println!("4. Log Break-Fast Meal for a Past Fast");
+        println!("5. Back to Fasting Menu");
+
+        match prompt_user_choice("Enter your choice (1-5): ") {
+            Some(1) => {
+                let Some(start) = prompt_datetime("Enter the start time (YYYY-MM-DD HH:MM): ", &tz) else {
+                    continue;
+                };
+                let Some(stop) = prompt_datetime("Enter the stop time (YYYY-MM-DD HH:MM): ", &tz) else {
+                    continue;
+                };
+                let goal_id = prompt_optional_goal_id(conn, user, clock);
+                let protocol_id = prompt_optional_protocol_id(conn, user);
+                match record_completed_fast(conn, user.id, start, stop, goal_id, protocol_id, clock) {
+                    Ok(_) => println!("✅ Past fast logged successfully."),
+                    Err(e) => eprintln!("❌ Error logging fast: {}", e),
+                }
+            }
+            Some(2) => {
+                let Some(event) = prompt_fasting_event(conn, user, &tz) else {
+                    continue;
+                };
+                let start = prompt_optional_datetime(
+                    "Enter the new start time (YYYY-MM-DD HH:MM, or press Enter to keep): ",
+                    &tz,
+                )
+                .unwrap_or(event.start_time);
+                // ✅ Ongoing fasts keep no stop time; they are stopped from the Fasting Menu
+                let stop = event.stop_time.map(|current_stop| {
+                    prompt_optional_datetime(
+                        "Enter the new stop time (YYYY-MM-DD HH:MM, or press Enter to keep): ",
+                        &tz,
+                    )
+                    .unwrap_or(current_stop)
+                });
+                match edit_fasting_event(conn, user.id, event.id, start, stop, clock) {
+                    Ok(_) => println!("✅ Fast updated successfully."),
+                    Err(e) => eprintln!("❌ Error updating fast: {}", e),
+                }
+            }
+            Some(3) => {
+                let Some(event) = prompt_fasting_event(conn, user, &tz) else {
+                    continue;
+                };
+                let confirm = prompt_user_input("Type 'yes' to delete this fast: ");
+                if confirm.eq_ignore_ascii_case("yes") {
+                    match delete_fasting_event(conn, user.id, event.id) {
+                        Ok(_) => println!("✅ Fast deleted."),
+                        Err(e) => eprintln!("❌ Error deleting fast: {}", e),
+                    }
+                } else {
+                    println!("↩️ Deletion cancelled.");
+                }
+            }
+            Some(4) => {
+                let Some(event) = prompt_fasting_event(conn, user, &tz) else {
+                    continue;
+                };
+                let Some(meal) = prompt_break_fast_meal() else {
+                    continue;
+                };
+                match log_break_fast_meal(conn, user.id, event.id, &meal) {
+                    Ok(_) => println!("✅ Break-fast meal logged."),
+                    Err(e) => eprintln!("❌ Error logging break-fast meal: {}", e),
+                }
+            }
+            Some(5) => break,
+            _ => println!("❌ Invalid choice. Please select a valid option."),
+        }
+    }
+}
+
+/// ✅ Lists the user's fasts and prompts for one of them by ID.
+fn prompt_fasting_event(conn: &mut SqliteConnection, user: &User, tz: &Tz) -> Option<FastingEvent> {
+    let mut events = match get_user_fasting_events(conn, user.id) {
+        Ok(events) => events,
+        Err(e) => {
+            eprintln!("❌ Error retrieving fasts: {}", e);
+            return None;
+        }
+    };
+
+    if events.is_empty() {
+        println!("❌ No fasts found.");
+        return None;
+    }
+
+    println!("📋 Your fasts:");
+    for event in &events {
+        println!(
+            "- ID {}: {} → {}",
+            event.id,
+            format_local(tz, event.start_time),
+            event.stop_time
+                .map_or_else(|| "Ongoing".to_string(), |end| format_local(tz, end))
+        );
+    }
+
+    let chosen_id = prompt_user_choice("Enter the fast ID: ")?;
+    match events.iter().position(|event| event.id == chosen_id) {
+        Some(index) => Some(events.swap_remove(index)),
+        None => {
+            println!("❌ No fast with ID {}.", chosen_id);
+            None
+        }
+    }
+}
+
+/// ✅ Prints the status of the ongoing fast, including whether it is paused.
+fn print_fasting_status(status: &FastingStatus, tz: &Tz) {
+    println!(
+        "⏳ Fasting started at {} and has lasted for {} minutes.",
+        format_local(tz, status.start_time),
+        status.elapsed_minutes
+    );
+    if let Some(paused_since) = status.paused_since {
+        println!("⏸️ Paused since {}. Paused time is not counted.", format_local(tz, paused_since));
+    }
+    if let Some(target) = status.target_minutes {
+        let remaining = target - status.elapsed_minutes;
+        if remaining > 0 {
+            println!("🎯 Target: {} minutes ({} minutes to go).", target, remaining);
+        } else {
+            println!("🎯 Target of {} minutes reached!", target);
+        }
+    }
+    if let (Some(remaining), Some(deadline)) = (status.goal_remaining_minutes, status.goal_deadline) {
+        if remaining > 0 {
+            println!(
+                "🏁 Goal: {}h {}m to go (deadline {}).",
+                remaining / 60,
+                remaining % 60,
+                format_local(tz, deadline)
+            );
+        } else {
+            println!("🏁 Goal duration reached!");
+        }
+    }
+}
+
+/// ✅ Prints the metabolic stage of the ongoing fast.
+fn print_stage_status(status: &StageStatus) {
+    println!(
+        "🧬 Current stage: {} - {}",
+        status.current_stage.name, status.current_stage.description
+    );
+    if let (Some(next), Some(minutes)) = (&status.next_stage, status.minutes_to_next_stage) {
+        println!(
+            "⏭️ Next stage: {} in {}h {}m.",
+            next.name,
+            minutes / 60,
+            minutes % 60
+        );
+    }
+}
+
+/// ✅ Handles listing, creating and deleting fasting protocols.
+fn handle_protocols_menu(conn: &mut SqliteConnection, user: &User) {
+    loop {
+        println!("\n📐 **Fasting Protocols**:");
+        println!("1. List Protocols");
+        println!("2. Create a Custom Protocol");
+        println!("3. Delete a Custom Protocol");
+        println!("4. Back to Fasting Menu");
+
+        match prompt_user_choice("Enter your choice (1-4): ") {
+            Some(1) => print_protocols(conn, user),
+            Some(2) => {
+                let name = prompt_user_input("Enter the protocol name: ");
+                let Some(fasting_hours) = prompt_user_choice("Enter the fasting window in hours: ") else {
+                    println!("❌ Invalid number of hours.");
+                    continue;
+                };
+                let Some(eating_hours) = prompt_user_choice("Enter the eating window in hours: ") else {
+                    println!("❌ Invalid number of hours.");
+                    continue;
+                };
+                let description = prompt_user_input("Enter a description (or press Enter to skip): ");
+                let description = (!description.is_empty()).then_some(description.as_str());
+                match create_protocol(conn, user.id, &name, fasting_hours, eating_hours, description) {
+                    Ok(_) => println!("✅ Protocol created."),
+                    Err(e) => eprintln!("❌ Error creating protocol: {}", e),
+                }
+            }
+            Some(3) => {
+                print_protocols(conn, user);
+                let Some(protocol_id) = prompt_user_choice("Enter the protocol ID to delete: ") else {
+                    continue;
+                };
+                match delete_protocol(conn, user.id, protocol_id) {
+                    Ok(_) => println!("✅ Protocol deleted."),
+                    Err(e) => eprintln!("❌ Error deleting protocol: {}", e),
+                }
+            }
+            Some(4) => break,
+            _ => println!("❌ Invalid choice. Please select a valid option."),
+        }
+    }
+}
+
+/// ✅ Handles browsing, importing and following multi-week challenge programs.
+fn handle_programs_menu(conn: &mut SqliteConnection, user: &User, clock: &dyn Clock) {
+    let tz = user_timezone(conn, user);
+    loop {
+        println!("\n🏁 **Challenge Programs**:");
+        println!("1. List Programs");
+        println!("2. View a Program's Daily Targets");
+        println!("3. Enroll in a Program");
+        println!("4. View Progress");
+        println!("5. Drop Out of a Program");
+        println!("6. Import a Program from a File");
+        println!("7. Delete an Imported Program");
+        println!("8. Back to Fasting Menu");
+
+        match prompt_user_choice("Enter your choice (1-8): ") {
+            Some(1) => print_programs(conn, user, clock),
+            Some(2) => {
+                print_programs(conn, user, clock);
+                let Some(program_id) = prompt_user_choice("Enter the program ID: ") else {
+                    continue;
+                };
+                match get_program_days(conn, user.id, program_id) {
+                    Ok(days) => {
+                        for day in days {
+                            match day.target_hours {
+                                0 => println!("Day {:>3}: rest", day.day_number),
+                                hours => println!("Day {:>3}: {}h", day.day_number, hours),
+                            }
+                        }
+                    }
+                    Err(e) => eprintln!("❌ Error retrieving program: {}", e),
+                }
+            }
+            Some(3) => {
+                print_programs(conn, user, clock);
+                let Some(program_id) = prompt_user_choice("Enter the program ID to enroll in: ") else {
+                    continue;
+                };
+                let start_date = match prompt_user_input("Start date (YYYY-MM-DD, or press Enter for today): ") {
+                    input if input.is_empty() => local_today(&tz, clock),
+                    input => match NaiveDate::parse_from_str(&input, "%Y-%m-%d") {
+                        Ok(date) => date,
+                        Err(_) => {
+                            println!("❌ Invalid date format. Use YYYY-MM-DD.");
+                            continue;
+                        }
+                    },
+                };
+                match enroll_in_program(conn, user.id, program_id, start_date, clock) {
+                    Ok(_) => println!("✅ Enrolled. Each day's target will appear in your goals."),
+                    Err(e) => eprintln!("❌ Error enrolling: {}", e),
+                }
+            }
+            Some(4) => {
+                let Some(enrollment_id) = prompt_enrollment_id(conn, user, clock) else {
+                    continue;
+                };
+                match get_program_progress(conn, user.id, enrollment_id, clock) {
+                    Ok(progress) => print_program_progress(&progress),
+                    Err(e) => eprintln!("❌ Error retrieving progress: {}", e),
+                }
+            }
+            Some(5) => {
+                let Some(enrollment_id) = prompt_enrollment_id(conn, user, clock) else {
+                    continue;
+                };
+                match drop_out_of_program(conn, user.id, enrollment_id, clock) {
+                    Ok(_) => println!("✅ You dropped out of the program. Your results so far are kept."),
+                    Err(e) => eprintln!("❌ Error dropping out: {}", e),
+                }
+            }
+            Some(6) => {
+                let file_path = prompt_user_input("Path to the program JSON file: ");
+                match import_program(conn, user.id, &file_path, clock) {
+                    Ok(program) => println!("✅ Imported '{}' (ID {}).", program.name, program.id),
+                    Err(e) => eprintln!("❌ Error importing program: {}", e),
+                }
+            }
+            Some(7) => {
+                print_programs(conn, user, clock);
+                let Some(program_id) = prompt_user_choice("Enter the program ID to delete: ") else {
+                    continue;
+                };
+                match delete_program(conn, user.id, program_id) {
+                    Ok(_) => println!("✅ Program deleted."),
+                    Err(e) => eprintln!("❌ Error deleting program: {}", e),
+                }
+            }
             Some(8) => break,
             _ => println!("❌ Invalid choice. Please select a valid option."),
         }
     }
 }
 
+/// ✅ Prints the built-in programs and the user's imported programs.
+fn print_programs(conn: &mut SqliteConnection, user: &User, clock: &dyn Clock) {
+    match list_programs(conn, user.id, clock) {
+        Ok(programs) => {
+            for program in programs {
+                let source = if program.user_id.is_none() { "built-in" } else { "imported" };
+                let length = get_program_days(conn, user.id, program.id).map_or(0, |days| days.len());
+                println!("- ID: {} | {} ({} days, {})", program.id, program.name, length, source);
+                if let Some(description) = &program.description {
+                    println!("    {}", description);
+                }
+            }
+        }
+        Err(e) => eprintln!("❌ Error retrieving programs: {}", e),
+    }
+}
+
+/// ✅ Lists the user's enrollments and prompts for one, reporting when there are none.
+fn prompt_enrollment_id(conn: &mut SqliteConnection, user: &User, clock: &dyn Clock) -> Option<i32> {
+    let enrollments = match list_enrollments(conn, user.id, clock) {
+        Ok(enrollments) => enrollments,
+        Err(e) => {
+            eprintln!("❌ Error retrieving enrollments: {}", e);
+            return None;
+        }
+    };
+    if enrollments.is_empty() {
+        println!("❌ You have not enrolled in any program yet.");
+        return None;
+    }
+    for enrollment in &enrollments {
+        println!(
+            "- Enrollment ID: {} | Program {} | started {} | {}",
+            enrollment.id, enrollment.program_id, enrollment.start_date, enrollment.status
+        );
+    }
+    prompt_user_choice("Enter the enrollment ID: ")
+}
+
+/// ✅ Prints the day-by-day results of an enrollment.
+fn print_program_progress(progress: &ProgramProgress) {
+    println!(
+        "\n🏁 {} — started {} ({})",
+        progress.program_name, progress.enrollment.start_date, progress.enrollment.status
+    );
+    for day in &progress.days {
+        let (icon, label) = match day.outcome {
+            ProgramDayOutcome::Rest => ("💤", "rest day"),
+            ProgramDayOutcome::Upcoming => ("⬜", "upcoming"),
+            ProgramDayOutcome::Pending => ("⏳", "open"),
+            ProgramDayOutcome::InProgress => ("🔥", "in progress"),
+            ProgramDayOutcome::Achieved => ("✅", "achieved"),
+            ProgramDayOutcome::Missed => ("❌", "missed"),
+        };
+        if day.target_hours == 0 {
+            println!("{} Day {:>3} ({}): {}", icon, day.day_number, day.date, label);
+        } else {
+            println!("{} Day {:>3} ({}): {}h, {}", icon, day.day_number, day.date, day.target_hours, label);
+        }
+    }
+    println!(
+        "Achieved: {} | Missed: {} | Remaining: {}",
+        progress.achieved, progress.missed, progress.remaining
+    );
+    match progress.score_percent {
+        Some(score) => println!("Score: {:.0}% of decided days achieved", score),
+        None => println!("Score: no days decided yet"),
+    }
+}
+
+/// ✅ Handles drink logging and the daily hydration target.
+fn handle_hydration_menu(conn: &mut SqliteConnection, user: &User, clock: &dyn Clock) {
+    loop {
+        println!("\n💧 **Hydration**:");
+        println!("1. Log a Drink");
+        println!("2. View Progress for Current Fast");
+        println!("3. Set Daily Hydration Target");
+        println!("4. Back to Fasting Menu");
+
+        match prompt_user_choice("Enter your choice (1-4): ") {
+            Some(1) => {
+                let kind = match prompt_user_input("Drink (water, electrolytes, black_coffee, tea): ").parse::<IntakeKind>() {
+                    Ok(kind) => kind,
+                    Err(e) => {
+                        println!("❌ {}", e);
+                        continue;
+                    }
+                };
+                let Some(amount) = prompt_user_choice("Amount in ml: ") else {
+                    println!("❌ Invalid amount.");
+                    continue;
+                };
+                match log_intake(conn, user.id, kind, amount, clock.now(), clock) {
+                    Ok(_) => println!("✅ Logged {} ml of {}.", amount, kind),
+                    Err(e) => eprintln!("❌ Error logging drink: {}", e),
+                }
+            }
+            Some(2) => match current_fast_hydration(conn, user.id, clock) {
+                Ok(Some(progress)) => print_hydration_progress(&progress),
+                Ok(None) => println!("❌ No active fasting session found."),
+                Err(e) => eprintln!("❌ Error retrieving hydration: {}", e),
+            },
+            Some(3) => {
+                let input = prompt_user_input("Daily target in ml (or press Enter to clear): ");
+                let target = if input.is_empty() {
+                    None
+                } else {
+                    match input.parse::<i32>() {
+                        Ok(target) => Some(target),
+                        Err(_) => {
+                            println!("❌ Invalid amount.");
+                            continue;
+                        }
+                    }
+                };
+                match set_daily_hydration_target(conn, user.id, target) {
+                    Ok(_) => println!("✅ Hydration target saved."),
+                    Err(e) => eprintln!("❌ Error saving hydration target: {}", e),
+                }
+            }
+            Some(4) => break,
+            _ => println!("❌ Invalid choice. Please select a valid option."),
+        }
+    }
+}
+
+/// ✅ Prints hydration for the current fast, with progress toward the target.
+fn print_hydration_progress(progress: &HydrationProgress) {
+    let totals = &progress.totals;
+    match (progress.target_ml, progress.percent_of_target()) {
+        (Some(target), Some(percent)) => println!(
+            "💧 Hydration: {} / {} ml ({:.0}%)",
+            totals.total_ml, target, percent
+        ),
+        _ => println!("💧 Hydration: {} ml (no daily target set)", totals.total_ml),
+    }
+    println!(
+        "   Water {} ml | Electrolytes {} ml | Coffee {} ml | Tea {} ml",
+        totals.water_ml, totals.electrolytes_ml, totals.black_coffee_ml, totals.tea_ml
+    );
+}
+
+/// ✅ Handles the fast journal for the current or a past fast.
+fn handle_journal_menu(conn: &mut SqliteConnection, user: &User, clock: &dyn Clock) {
+    let tz = user_timezone(conn, user);
+    loop {
+        println!("\n📓 **Fast Journal**:");
+        println!("1. Add Entry to Current Fast");
+        println!("2. Add Entry to a Past Fast");
+        println!("3. View Entries for Current Fast");
+        println!("4. View Entries for a Past Fast");
+        println!("5. Back to Fasting Menu");
+
+        match prompt_user_choice("Enter your choice (1-5): ") {
+            Some(choice @ (1 | 2)) => {
+                // ✅ Entries for the current fast are logged now; past fasts need a time
+                let (event_id, entry_time) = if choice == 2 {
+                    let Some(event) = prompt_fasting_event(conn, user, &tz) else {
+                        continue;
+                    };
+                    let Some(at) = prompt_datetime("Enter the entry time (YYYY-MM-DD HH:MM): ", &tz) else {
+                        continue;
+                    };
+                    (Some(event.id), Some(at))
+                } else {
+                    (None, None)
+                };
+                let input = JournalEntryInput {
+                    entry_time,
+                    note: Some(prompt_user_input("Note (or press Enter to skip): ")),
+                    mood: prompt_optional_score("Mood 1-10 (or press Enter to skip): "),
+                    hunger: prompt_optional_score("Hunger 1-10 (or press Enter to skip): "),
+                    energy: prompt_optional_score("Energy 1-10 (or press Enter to skip): "),
+                    symptoms: prompt_user_input(&format!(
+                        "Symptoms, comma-separated (e.g. {}), or press Enter to skip: ",
+                        COMMON_SYMPTOMS.join(", ")
+                    ))
+                    .split(',')
+                    .map(str::to_string)
+                    .collect(),
+                };
+                match add_journal_entry(conn, user.id, event_id, input, clock) {
+                    Ok(_) => println!("✅ Journal entry saved."),
+                    Err(e) => eprintln!("❌ Error saving journal entry: {}", e),
+                }
+            }
+            Some(choice @ (3 | 4)) => {
+                let event_id = if choice == 4 {
+                    let Some(event) = prompt_fasting_event(conn, user, &tz) else {
+                        continue;
+                    };
+                    Some(event.id)
+                } else {
+                    None
+                };
+                match list_journal_entries(conn, user.id, event_id) {
+                    Ok(entries) if entries.is_empty() => println!("❌ No journal entries for this fast."),
+                    Ok(entries) => {
+                        println!("📋 Journal entries:");
+                        for entry in entries {
+                            let score = |label: &str, value: Option<i32>| {
+                                value.map(|v| format!(" | {} {}/10", label, v)).unwrap_or_default()
+                            };
+                            println!(
+                                "- {}{}{}{}{}{}",
+                                format_local(&tz, entry.entry_time),
+                                score("😊 Mood", entry.mood),
+                                score("🍽️ Hunger", entry.hunger),
+                                score("⚡ Energy", entry.energy),
+                                if entry.symptom_tags().is_empty() {
+                                    String::new()
+                                } else {
+                                    format!(" | 🤕 {}", entry.symptom_tags().join(", "))
+                                },
+                                entry.note.as_deref().map(|n| format!("\n  📝 {}", n)).unwrap_or_default()
+                            );
+                        }
+                    }
+                    Err(e) => eprintln!("❌ Error retrieving journal entries: {}", e),
+                }
+            }
+            Some(5) => break,
+            _ => println!("❌ Invalid choice. Please select a valid option."),
+        }
+    }
+}
+
+/// ✅ Handles the weekly fasting schedule and planned-versus-actual review.
+fn handle_schedule_menu(conn: &mut SqliteConnection, user: &User, clock: &dyn Clock) {
+    let tz = user_timezone(conn, user);
+    loop {
+        println!("\n🗓️ **Weekly Schedule**:");
+        println!("1. View Schedule");
+        println!("2. Add a Fasting Window");
+        println!("3. Delete a Fasting Window");
+        println!("4. Review the Last 7 Days");
+        println!("5. Back to Fasting Menu");
+
+        match prompt_user_choice("Enter your choice (1-5): ") {
+            Some(1) => print_schedule(conn, user),
+            Some(2) => {
+                let Ok(day) = prompt_user_input("Enter the weekday (e.g. Mon): ").parse::<Weekday>() else {
+                    println!("❌ Invalid weekday.");
+                    continue;
+                };
+                let Some(start) = prompt_time("Enter the start time (HH:MM): ") else {
+                    continue;
+                };
+                let Some(end) = prompt_time("Enter the end time (HH:MM, next day if earlier): ") else {
+                    continue;
+                };
+                let goal_id = prompt_optional_goal_id(conn, user, clock);
+                match add_schedule_window(conn, user.id, day, start, end, goal_id) {
+                    Ok(_) => println!("✅ Fasting window added."),
+                    Err(e) => eprintln!("❌ Error adding fasting window: {}", e),
+                }
+            }
+            Some(3) => {
+                print_schedule(conn, user);
+                let Some(schedule_id) = prompt_user_choice("Enter the window ID to delete: ") else {
+                    continue;
+                };
+                match delete_schedule_window(conn, user.id, schedule_id) {
+                    Ok(_) => println!("✅ Fasting window deleted."),
+                    Err(e) => eprintln!("❌ Error deleting fasting window: {}", e),
+                }
+            }
+            Some(4) => {
+                let today = local_today(&tz, clock);
+                match review_schedule(conn, user.id, today - Duration::days(7), today, clock) {
+                    Ok(outcomes) if outcomes.is_empty() => println!("❌ No planned fasts in the last 7 days."),
+                    Ok(outcomes) => {
+                        println!("📋 Planned vs actual:");
+                        for outcome in outcomes {
+                            let label = match outcome.status {
+                                PlannedFastStatus::OnTime => "✅ On time",
+                                PlannedFastStatus::LateStart => "⏰ Late start",
+                                PlannedFastStatus::EarlyBreak => "🍽️ Early break",
+                                PlannedFastStatus::Missed => "❌ Missed",
+                            };
+                            println!(
+                                "- {} → {}: {}",
+                                format_local(&tz, outcome.planned.start),
+                                format_local(&tz, outcome.planned.end),
+                                label
+                            );
+                        }
+                    }
+                    Err(e) => eprintln!("❌ Error reviewing schedule: {}", e),
+                }
+            }
+            Some(5) => break,
+            _ => println!("❌ Invalid choice. Please select a valid option."),
+        }
+    }
+}
+
+/// ✅ Prints the user's weekly fasting windows.
+fn print_schedule(conn: &mut SqliteConnection, user: &User) {
+    match list_schedule_windows(conn, user.id) {
+        Ok(windows) if windows.is_empty() => println!("❌ No fasting windows scheduled."),
+        Ok(windows) => {
+            println!("📋 Weekly fasting windows:");
+            for window in windows {
+                let day = Weekday::try_from(window.weekday as u8).unwrap_or(Weekday::Mon);
+                println!(
+                    "- ID {}: {} {} → {}",
+                    window.id,
+                    day,
+                    window.start_time.format("%H:%M"),
+                    window.end_time.format("%H:%M")
+                );
+            }
+        }
+        Err(e) => eprintln!("❌ Error retrieving schedule: {}", e),
+    }
+}
+
+/// ✅ Prints the protocols available to the user.
+fn print_protocols(conn: &mut SqliteConnection, user: &User) {
+    match list_protocols(conn, user.id) {
+        Ok(protocols) => {
+            println!("📋 Available protocols:");
+            for protocol in protocols {
+                println!(
+                    "- ID {}: {} ({}h fasting / {}h eating){}{}",
+                    protocol.id,
+                    protocol.name,
+                    protocol.fasting_hours,
+                    protocol.eating_hours,
+                    if protocol.is_built_in() { "" } else { " [custom]" },
+                    protocol
+                        .description
+                        .as_deref()
+                        .map(|text| format!(" - {}", text))
+                        .unwrap_or_default()
+                );
+            }
+        }
+        Err(e) => eprintln!("❌ Error retrieving protocols: {}", e),
+    }
+}
+
 
 /// ✅ Handles the **Analytics Menu**.
-fn handle_analytics_menu(conn: &mut SqliteConnection, user: &User) {
+fn handle_analytics_menu(conn: &mut SqliteConnection, user: &User, clock: &dyn Clock) {
+    let tz = user_timezone(conn, user);
     loop {
         println!("\n📊 **Analytics Menu**:");
         println!("1. Fasting History");
         println!("2. Average Fasting Duration");
         println!("3. Total Fasting Time");
-        println!("4. View All Fasting Sessions");
-        println!("5. Back to Main Menu");
+        println!("4. Summary by Protocol");
+        println!("5. Hunger & Mood by Fasting Hour");
+        println!("6. Export Fasting Data");
+        println!("7. Break-Fast Patterns");
+        println!("8. Streaks & Personal Records");
+        println!("9. Fasting by Day, Week or Month");
+        println!("10. Charts");
+        println!("11. Back to Main Menu");
 
-        match prompt_user_choice("Enter your choice (1-5): ") {
-            Some(1) => show_fasting_history(conn, user.id),
+        match prompt_user_choice("Enter your choice (1-11): ") {
+            Some(1) => show_fasting_history(conn, user.id, &tz, clock),
             Some(2) => match calculate_average_fasting_duration(conn, user.id) {
                 Ok(Some(avg)) => println!("📊 Average Fasting Duration: {} minutes.", avg),
                 Ok(None) => println!("❌ No fasting data available."),
@@ -215,24 +1168,120 @@ fn handle_analytics_menu(conn: &mut SqliteConnection, user: &User) {
                 Ok(total) => println!("⏳ Total Fasting Time: {} minutes.", total),
                 Err(e) => eprintln!("❌ Error calculating total fasting time: {}", e),
             },
-            Some(4) => match get_user_fasting_sessions(conn, user.id) {
-                Ok(sessions) => {
-                    if sessions.is_empty() {
-                        println!("❌ No fasting sessions found for user {}.", user.id);
-                    } else {
-                        println!("📋 Fasting sessions for user {}:", user.id);
-                        for session in sessions {
+            Some(4) => match summarize_fasts_by_protocol(conn, user.id) {
+                Ok(summaries) if summaries.is_empty() => println!("❌ No fasting data available."),
+                Ok(summaries) => {
+                    println!("📐 Fasts by protocol:");
+                    for summary in summaries {
+                        println!(
+                            "- {}: {} fast(s), {} minutes total, {} minutes average, target met {} time(s)",
+                            summary.protocol_name,
+                            summary.fast_count,
+                            summary.total_minutes,
+                            summary.average_minutes,
+                            summary.target_met_count
+                        );
+                    }
+                }
+                Err(e) => eprintln!("❌ Error summarizing fasts by protocol: {}", e),
+            },
+            Some(5) => match journal_scores_by_fasting_hour(conn, user.id) {
+                Ok(stats) if stats.is_empty() => println!("❌ No journal entries logged yet."),
+                Ok(stats) => {
+                    let show = |value: Option<f64>| value.map_or_else(|| "-".to_string(), |v| format!("{:.1}", v));
+                    println!("📈 Average scores by hour of fast:");
+                    for row in stats {
+                        println!(
+                            "- Hour {:>3}: hunger {} | mood {} | energy {} ({} entries)",
+                            row.hour,
+                            show(row.average_hunger),
+                            show(row.average_mood),
+                            show(row.average_energy),
+                            row.entry_count
+                        );
+                    }
+                }
+                Err(e) => eprintln!("❌ Error correlating journal scores: {}", e),
+            },
+            Some(6) => {
+                let events = get_user_fasting_events(conn, user.id)
+                    .and_then(|events| Ok((hydration_totals_by_event(conn, user.id)?, events)));
+                let (hydration, events) = match events {
+                    Ok(data) => data,
+                    Err(e) => {
+                        eprintln!("❌ Error loading fasting data: {}", e);
+                        continue;
+                    }
+                };
+                match prompt_user_input("Format (csv/json): ").to_lowercase().as_str() {
+                    "csv" => {
+                        let file_path = prompt_user_input("Enter the CSV file path: ");
+                        match export_to_csv(&events, &hydration, &file_path, &tz, clock) {
+                            Ok(_) => println!("✅ Fasting data exported to {}.", file_path),
+                            Err(e) => eprintln!("❌ Error exporting fasting data: {}", e),
+                        }
+                    }
+                    "json" => match export_to_json(&events, &hydration, &tz, clock) {
+                        Ok(json) => println!("{}", json),
+                        Err(e) => eprintln!("❌ Error exporting fasting data: {}", e),
+                    },
+                    _ => println!("❌ Unknown format. Use csv or json."),
+                }
+            }
+            Some(7) => {
+                match summarize_break_fast_patterns(conn, user.id) {
+                    Ok(patterns) if patterns.is_empty() => println!("❌ No break-fast meals logged yet."),
+                    Ok(patterns) => {
+                        println!("🍽️ Most common break-fast meals:");
+                        for pattern in patterns {
+                            let calories = pattern
+                                .average_calories
+                                .map_or_else(String::new, |kcal| format!(", ~{} kcal", kcal));
+                            println!("- {} ({} time(s){})", pattern.description, pattern.meal_count, calories);
+                        }
+                    }
+                    Err(e) => eprintln!("❌ Error summarizing break-fast meals: {}", e),
+                }
+                match find_large_refeeds(conn, user.id) {
+                    Ok(warnings) => {
+                        for warning in warnings {
                             println!(
-                                "- Start: {}, End: {:?}",
-                                session.start_time,
-                                session.stop_time.unwrap_or_else(|| Utc::now().naive_utc())
+                                "⚠️ Fast {} ({:.1} h) was broken with '{}' (up to {} kcal). Consider a smaller first meal after extended fasts.",
+                                warning.event_id,
+                                warning.fasted_minutes as f64 / 60.0,
+                                warning.description,
+                                warning.calories_max
                             );
                         }
                     }
+                    Err(e) => eprintln!("❌ Error checking refeeds: {}", e),
                 }
-                Err(e) => eprintln!("❌ Error retrieving fasting sessions: {}", e),
+            }
+            Some(8) => match calculate_streaks(conn, user.id, DEFAULT_STREAK_THRESHOLD_MINUTES, &tz, clock) {
+                Ok(report) => print_streak_report(&report, &tz),
+                Err(e) => eprintln!("❌ Error calculating streaks: {}", e),
             },
-            Some(5) => break,
+            Some(9) => {
+                let Some(from) = prompt_date("Report from (YYYY-MM-DD): ") else {
+                    continue;
+                };
+                let Some(to) = prompt_date("Report to (YYYY-MM-DD): ") else {
+                    continue;
+                };
+                let bucket_size = match prompt_user_input("Group by (day, week, month): ").parse::<BucketSize>() {
+                    Ok(size) => size,
+                    Err(e) => {
+                        println!("❌ {}", e);
+                        continue;
+                    }
+                };
+                match aggregate_fasting_by_period(conn, user.id, from, to, bucket_size, &tz) {
+                    Ok(periods) => print_period_stats(&periods),
+                    Err(e) => eprintln!("❌ Error building report: {}", e),
+                }
+            }
+            Some(10) => handle_charts_menu(conn, user, clock),
+            Some(11) => break,
             _ => println!("❌ Invalid choice. Please select a valid option."),
         }
     }
@@ -240,39 +1289,394 @@ fn handle_analytics_menu(conn: &mut SqliteConnection, user: &User) {
 
 
 
-/// ✅ Handles account settings (View Profile, Link Device)
-fn handle_account_settings(conn: &mut SqliteConnection, user: &User) {
+/// ✅ Handles the terminal charts, sized to the terminal width.
+fn handle_charts_menu(conn: &mut SqliteConnection, user: &User, clock: &dyn Clock) {
+    let tz = user_timezone(conn, user);
+    loop {
+        println!("\n📈 **Charts**:");
+        println!("1. Fasting Hours per Day");
+        println!("2. Fasting Calendar");
+        println!("3. Fast Length Histogram");
+        println!("4. Back to Analytics Menu");
+
+        let width = terminal_width();
+        let chart = match prompt_user_choice("Enter your choice (1-4): ") {
+            Some(1) => {
+                let input = prompt_user_input(&format!("Number of days (1-{}, or press Enter for 14): ", MAX_CHART_DAYS));
+                let days = if input.is_empty() { Some(14) } else { input.parse::<u32>().ok() };
+                let Some(days) = days else {
+                    println!("❌ Invalid number of days.");
+                    continue;
+                };
+                daily_fasting_chart(conn, user.id, days, &tz, width, clock)
+            }
+            Some(2) => fasting_heatmap(conn, user.id, &tz, width, clock),
+            Some(3) => fast_duration_histogram(conn, user.id, width, clock),
+            Some(4) => break,
+            _ => {
+                println!("❌ Invalid choice. Please select a valid option.");
+                continue;
+            }
+        };
+
+        match chart {
+            Ok(chart) => print!("{}", chart),
+            Err(e) => eprintln!("❌ Error drawing chart: {}", e),
+        }
+    }
+}
+
+/// ✅ Prints a period report, one line per period.
+fn print_period_stats(periods: &[PeriodStats]) {
+    let show = |value: Option<f64>| value.map_or_else(|| "-".to_string(), |v| format!("{:.0}", v));
+
+    println!("📅 Period         | Fasts | Total (min) |  Mean | Median |  Min |  Max");
+    for period in periods {
+        println!(
+            "{:<16} | {:>5} | {:>11} | {:>5} | {:>6} | {:>4} | {:>4}",
+            period.period_start,
+            period.fast_count,
+            period.total_minutes,
+            show(period.mean_minutes),
+            show(period.median_minutes),
+            show(period.min_minutes.map(|m| m as f64)),
+            show(period.max_minutes.map(|m| m as f64))
+        );
+    }
+}
+
+/// ✅ Prints streaks and personal records.
+fn print_streak_report(report: &StreakReport, tz: &Tz) {
+    let hours = |minutes: i64| minutes as f64 / 60.0;
+
+    println!(
+        "🔥 Current streak: {} day(s) | Longest streak: {} day(s){}",
+        report.current_streak_days,
+        report.longest_streak_days,
+        report
+            .longest_streak_end
+            .map_or_else(String::new, |end| format!(" (ended {})", end))
+    );
+    println!("🏆 Personal records:");
+    match &report.records.longest_fast {
+        Some(record) => println!(
+            "- Longest fast: {:.1} h (started {})",
+            hours(record.minutes),
+            format_local(tz, record.start_time)
+        ),
+        None => println!("- Longest fast: -"),
+    }
+    match &report.records.best_week {
+        Some(week) => println!(
+            "- Most fasting in a week: {:.1} h (week of {})",
+            hours(week.minutes),
+            week.week_start
+        ),
+        None => println!("- Most fasting in a week: -"),
+    }
+    println!(
+        "- Most consecutive goal hits: {}",
+        report.records.most_consecutive_goal_hits
+    );
+}
+
+/// ✅ Handles logging and reviewing body measurements.
+fn handle_body_metrics_menu(conn: &mut SqliteConnection, user: &User, clock: &dyn Clock) {
+    let tz = user_timezone(conn, user);
+    loop {
+        println!("\n⚖️ **Body Metrics**:");
+        println!("1. Log a Measurement");
+        println!("2. View Measurements");
+        println!("3. Edit a Measurement");
+        println!("4. Delete a Measurement");
+        println!("5. Weight Change Report");
+        println!("6. Back to Main Menu");
+
+        match prompt_user_choice("Enter your choice (1-6): ") {
+            Some(1) => {
+                let Some(kind) = prompt_measurement_kind() else {
+                    continue;
+                };
+                let Some(value) = prompt_measurement_value() else {
+                    continue;
+                };
+                let unit = prompt_user_input("Unit (or press Enter for your preferred unit): ");
+                let unit = (!unit.is_empty()).then_some(unit.as_str());
+                match add_measurement(conn, user.id, kind, value, unit, clock.now()) {
+                    Ok(_) => println!("✅ Measurement logged."),
+                    Err(e) => eprintln!("❌ Error logging measurement: {}", e),
+                }
+            }
+            Some(2) => print_measurements(conn, user, &tz),
+            Some(3) => {
+                print_measurements(conn, user, &tz);
+                let Some(measurement_id) = prompt_user_choice("Enter the measurement ID to edit: ") else {
+                    continue;
+                };
+                let Some(value) = prompt_measurement_value() else {
+                    continue;
+                };
+                let unit = prompt_user_input("Unit (e.g. kg, lb, %, cm, in): ");
+                let Some(measured_at) = prompt_datetime("Measured at (YYYY-MM-DD HH:MM): ", &tz) else {
+                    continue;
+                };
+                match update_measurement(conn, user.id, measurement_id, value, &unit, measured_at) {
+                    Ok(_) => println!("✅ Measurement updated."),
+                    Err(e) => eprintln!("❌ Error updating measurement: {}", e),
+                }
+            }
+            Some(4) => {
+                print_measurements(conn, user, &tz);
+                let Some(measurement_id) = prompt_user_choice("Enter the measurement ID to delete: ") else {
+                    continue;
+                };
+                match delete_measurement(conn, user.id, measurement_id) {
+                    Ok(_) => println!("✅ Measurement deleted."),
+                    Err(e) => eprintln!("❌ Error deleting measurement: {}", e),
+                }
+            }
+            Some(5) => {
+                let Some(from) = prompt_datetime("From (YYYY-MM-DD HH:MM): ", &tz) else {
+                    continue;
+                };
+                let to = prompt_optional_datetime("To (YYYY-MM-DD HH:MM, or press Enter for now): ", &tz)
+                    .unwrap_or_else(|| clock.now());
+                match calculate_weight_change(conn, user.id, from, to) {
+                    Ok(Some(report)) => {
+                        println!(
+                            "⚖️ {:.1} {} on {} → {:.1} {} on {} ({:+.1} {}).",
+                            report.start_weight,
+                            report.unit,
+                            to_local(&tz, report.start_measured_at).date_naive(),
+                            report.end_weight,
+                            report.unit,
+                            to_local(&tz, report.end_measured_at).date_naive(),
+                            report.change,
+                            report.unit
+                        );
+                        println!("⏳ Fasting in between: {:.1} hours.", report.fasting_hours);
+                        if let Some(rate) = report.change_per_100_fasting_hours {
+                            println!("📉 {:+.2} {} per 100 fasting hours.", rate, report.unit);
+                        }
+                    }
+                    Ok(None) => println!("❌ At least two weigh-ins are needed in that period."),
+                    Err(e) => eprintln!("❌ Error calculating weight change: {}", e),
+                }
+            }
+            Some(6) => break,
+            _ => println!("❌ Invalid choice. Please select a valid option."),
+        }
+    }
+}
+
+/// ✅ Prints the user's body measurements.
+fn print_measurements(conn: &mut SqliteConnection, user: &User, tz: &Tz) {
+    match list_measurements(conn, user.id, None) {
+        Ok(measurements) if measurements.is_empty() => println!("❌ No measurements logged yet."),
+        Ok(measurements) => {
+            println!("📋 Measurements:");
+            for measurement in measurements {
+                println!(
+                    "- ID {}: {} {} {} at {}",
+                    measurement.id,
+                    measurement.kind,
+                    measurement.value,
+                    measurement.unit,
+                    format_local(tz, measurement.measured_at)
+                );
+            }
+        }
+        Err(e) => eprintln!("❌ Error retrieving measurements: {}", e),
+    }
+}
+
+/// ✅ Handles account settings (View Profile, Paired Devices, Units, Timezone, Sessions, Password)
+fn handle_account_settings(conn: &mut SqliteConnection, user: &User, session_id: Option<i32>, clock: &dyn Clock) {
     loop {
         println!("\nAccount Settings:");
         println!("1. View My Profile");
-        println!("2. Link a New Device ID");
-        println!("3. Back to Main Menu");
+        println!("2. Paired Devices");
+        println!("3. Unit Preferences");
+        println!("4. Timezone");
+        println!("5. Active Sessions");
+        println!("6. Change Password");
+        println!("7. Back to Main Menu");
 
-        match prompt_user_choice("Enter your choice (1-3): ") {
+        match prompt_user_choice("Enter your choice (1-7): ") {
             Some(1) => {
-                match find_user_by_id(conn, user.id) {
+                match find_user_by_id(conn, user.id, clock) {
                     Ok(user) => {
                         println!("\n📌 User Profile:");
                         println!("👤 Username: {}", user.username);
-                        println!("📅 Created At: {:?}", user.created_at);
+                        let tz = user_timezone(conn, &user);
+                        println!(
+                            "📅 Created At: {}",
+                            user.created_at.map_or_else(|| "-".to_string(), |at| format_local(&tz, at))
+                        );
+                        println!("🌍 Timezone: {}", tz.name());
                     }
                     Err(e) => eprintln!("❌ Error retrieving profile: {}", e),
                 }
             }
-            Some(2) => {
-                let new_device_id = prompt_user_input("Enter your new device ID: ");
-                match associate_device_id(conn, user.id, &new_device_id) {
-                    Ok(_) => println!("✅ Device ID linked successfully."),
-                    Err(e) => eprintln!("❌ Failed to link device ID: {}", e),
+            Some(2) => handle_devices(conn, user, clock),
+            Some(3) => {
+                if let Ok(current) = get_unit_preferences(conn, user.id) {
+                    println!(
+                        "📏 Current units: {} and {}.",
+                        current.weight_unit.as_str(),
+                        current.length_unit.as_str()
+                    );
+                }
+                let weight_unit = prompt_user_input("Weight unit (kg/lb): ").parse();
+                let length_unit = prompt_user_input("Length unit (cm/in): ").parse();
+                let result = match (weight_unit, length_unit) {
+                    (Ok(weight_unit), Ok(length_unit)) => set_unit_preferences(
+                        conn,
+                        user.id,
+                        UnitPreferences { weight_unit, length_unit },
+                    ),
+                    (Err(e), _) | (_, Err(e)) => Err(e),
+                };
+                match result {
+                    Ok(_) => println!("✅ Unit preferences saved."),
+                    Err(e) => eprintln!("❌ Failed to save unit preferences: {}", e),
                 }
             }
-            Some(3) => break,
+            Some(4) => {
+                println!("🌍 Current timezone: {}.", user_timezone(conn, user).name());
+                let timezone_name = prompt_user_input("New timezone (IANA name, e.g. Europe/Berlin): ");
+                match set_user_timezone(conn, user.id, &timezone_name) {
+                    Ok(tz) => println!("✅ Times are now shown in {}.", tz.name()),
+                    Err(e) => eprintln!("❌ Failed to save timezone: {}", e),
+                }
+            }
+            Some(5) => handle_sessions(conn, user, session_id, clock),
+            Some(6) => handle_change_password(conn, user, clock),
+            Some(7) => break,
             _ => println!("❌ Invalid choice. Please select a valid option."),
         }
     }
 }
 
 
+/// ✅ Lists the user's active sessions and signs out one or all of them.
+fn handle_sessions(conn: &mut SqliteConnection, user: &User, current_session: Option<i32>, clock: &dyn Clock) {
+    let tz = user_timezone(conn, user);
+    match list_active_sessions(conn, user.id, clock) {
+        Ok(sessions) if sessions.is_empty() => {
+            println!("📭 No active sessions.");
+            return;
+        }
+        Ok(sessions) => {
+            println!("\n🔑 Active Sessions:");
+            for session in &sessions {
+                println!(
+                    "#{} {}{} | Signed in {} | Last used {} | Expires {}",
+                    session.id,
+                    session.device_name.as_deref().unwrap_or("Unknown device"),
+                    if Some(session.id) == current_session { " (this session)" } else { "" },
+                    format_local(&tz, session.created_at),
+                    format_local(&tz, session.last_used_at),
+                    format_local(&tz, session.expires_at)
+                );
+            }
+        }
+        Err(e) => {
+            eprintln!("❌ Error retrieving sessions: {}", e);
+            return;
+        }
+    }
+
+    let choice = prompt_user_input("Session # to sign out, 'all' to sign out everywhere, or press Enter to go back: ");
+    if choice.is_empty() {
+        return;
+    }
+    if choice.eq_ignore_ascii_case("all") {
+        match revoke_all_sessions(conn, user.id, clock) {
+            Ok(count) => println!("✅ Signed out of {} session(s).", count),
+            Err(e) => eprintln!("❌ Failed to sign out: {}", e),
+        }
+        return;
+    }
+    match choice.trim_start_matches('#').parse::<i32>() {
+        Ok(session_id) => match revoke_session(conn, user.id, session_id, clock) {
+            Ok(()) => println!("✅ Session #{} signed out.", session_id),
+            Err(e) => eprintln!("❌ {}", e.user_friendly_message()),
+        },
+        Err(_) => println!("❌ Invalid session number."),
+    }
+}
+
+/// ✅ Changes the user's password after asking for the current one.
+fn handle_change_password(conn: &mut SqliteConnection, user: &User, clock: &dyn Clock) {
+    let (policy, lockout) = match (PasswordPolicy::from_env(), LockoutPolicy::from_env()) {
+        (Ok(policy), Ok(lockout)) => (policy, lockout),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("❌ Failed to change password: {}", e);
+            return;
+        }
+    };
+
+    let current_password = prompt_user_input("Current password: ");
+    let new_password = prompt_user_input("New password: ");
+    if prompt_user_input("Repeat the new password: ") != new_password {
+        println!("❌ The new passwords do not match.");
+        return;
+    }
+
+    match change_password(conn, &policy, &lockout, user.id, &current_password, &new_password, clock) {
+        Ok(()) => println!("✅ Password changed. All sessions and paired devices have been signed out."),
+        Err(e) => eprintln!("❌ Failed to change password: {}", e.user_friendly_message()),
+    }
+}
+
+/// ✅ Lists the user's paired devices, creates pairing codes and revokes devices.
+fn handle_devices(conn: &mut SqliteConnection, user: &User, clock: &dyn Clock) {
+    let tz = user_timezone(conn, user);
+    match list_devices(conn, user.id) {
+        Ok(devices) if devices.is_empty() => println!("📭 No paired devices."),
+        Ok(devices) => {
+            println!("\n📱 Paired Devices:");
+            for device in &devices {
+                println!(
+                    "#{} {} | Paired {} | Last seen {}",
+                    device.id,
+                    device.name,
+                    format_local(&tz, device.paired_at),
+                    device.last_seen_at.map_or_else(|| "never".to_string(), |at| format_local(&tz, at))
+                );
+            }
+        }
+        Err(e) => {
+            eprintln!("❌ Error retrieving devices: {}", e);
+            return;
+        }
+    }
+
+    let choice = prompt_user_input("Type 'pair' to pair a new device, a device # to revoke it, or press Enter to go back: ");
+    if choice.is_empty() {
+        return;
+    }
+    if choice.eq_ignore_ascii_case("pair") {
+        match create_pairing_code(conn, user.id, clock) {
+            Ok(pairing) => println!(
+                "🔗 Enter {} on the new device under Login > Pair This Device. It expires at {}.",
+                pairing.code,
+                format_local(&tz, pairing.expires_at)
+            ),
+            Err(e) => eprintln!("❌ Failed to create a pairing code: {}", e),
+        }
+        return;
+    }
+    match choice.trim_start_matches('#').parse::<i32>() {
+        Ok(device_id) => match revoke_device(conn, user.id, device_id, clock) {
+            Ok(()) => println!("✅ Device #{} revoked and signed out.", device_id),
+            Err(e) => eprintln!("❌ {}", e.user_friendly_message()),
+        },
+        Err(_) => println!("❌ Invalid device number."),
+    }
+}
+
 /// ✅ Prompts the user for input and returns the trimmed string.
 pub(crate) fn prompt_user_input(message: &str) -> String {
     print!("{}", message);
@@ -293,17 +1697,187 @@ fn prompt_user_choice(message: &str) -> Option<i32> {
     input.trim().parse::<i32>().ok()
 }
 
-/// ✅ Prompts the user for a goal ID (Optional)
-fn prompt_optional_goal_id() -> Option<i32> {
-    print!("Enter Goal ID (or press Enter to skip): ");
-    io::stdout().flush().unwrap();
+/// ✅ Prompts the user for a local date and time and returns it in UTC, reporting invalid input.
+fn prompt_datetime(message: &str, tz: &Tz) -> Option<NaiveDateTime> {
+    let input = prompt_user_input(message);
+    match NaiveDateTime::parse_from_str(&input, "%Y-%m-%d %H:%M") {
+        Ok(local) => local_time_to_utc(tz, local),
+        Err(_) => {
+            println!("❌ Invalid date format. Use YYYY-MM-DD HH:MM.");
+            None
+        }
+    }
+}
 
-    let mut input = String::new();
-    io::stdin().read_line(&mut input).unwrap();
+/// ✅ Converts a local time entered by the user to UTC, reporting times skipped by a clock change.
+fn local_time_to_utc(tz: &Tz, local: NaiveDateTime) -> Option<NaiveDateTime> {
+    match local_to_utc(tz, local) {
+        Ok(utc) => Some(utc),
+        Err(e) => {
+            println!("❌ {}", e);
+            None
+        }
+    }
+}
 
-    match input.trim().parse::<i32>() {
-        Ok(id) => Some(id), // ✅ User entered a valid goal ID
-        Err(_) => None,     // ✅ User skipped (pressed Enter)
+/// ✅ The user's timezone for prompts and output (UTC if it cannot be loaded).
+fn user_timezone(conn: &mut SqliteConnection, user: &User) -> Tz {
+    get_user_timezone(conn, user.id).unwrap_or(DEFAULT_TIMEZONE)
+}
+
+/// ✅ Prompts the user for a measurement kind, reporting invalid input.
+fn prompt_measurement_kind() -> Option<MeasurementKind> {
+    match prompt_user_input("Kind (weight, body_fat, waist): ").parse() {
+        Ok(kind) => Some(kind),
+        Err(e) => {
+            println!("❌ {}", e);
+            None
+        }
+    }
+}
+
+/// ✅ Prompts the user for a measurement value, reporting invalid input.
+fn prompt_measurement_value() -> Option<f64> {
+    match prompt_user_input("Value: ").parse::<f64>() {
+        Ok(value) => Some(value),
+        Err(_) => {
+            println!("❌ Invalid number.");
+            None
+        }
+    }
+}
+
+/// ✅ Asks whether to log a break-fast meal and prompts for it (Optional)
+fn prompt_break_fast_meal() -> Option<BreakFastMealInput> {
+    let answer = prompt_user_input("Log what you broke your fast with? (y/N): ");
+    if !answer.eq_ignore_ascii_case("y") {
+        return None;
+    }
+
+    let description = prompt_user_input("What did you eat? ");
+    let calories = prompt_user_input("Approximate calories (e.g. 400-600, or press Enter to skip): ");
+    let (calories_min, calories_max) = match calories.split_once('-') {
+        Some((low, high)) => (low.trim().parse().ok(), high.trim().parse().ok()),
+        None => {
+            let estimate = calories.parse().ok();
+            (estimate, estimate)
+        }
+    };
+    let grams = |name: &str| prompt_user_input(&format!("{} in grams (or press Enter to skip): ", name)).parse().ok();
+
+    Some(BreakFastMealInput {
+        description,
+        calories_min,
+        calories_max,
+        protein_g: grams("Protein"),
+        carbs_g: grams("Carbs"),
+        fat_g: grams("Fat"),
+    })
+}
+
+/// ✅ Prompts for the days a daily goal repeats on, reporting invalid input.
+fn prompt_weekdays() -> Option<Vec<Weekday>> {
+    let input = prompt_user_input("Which days? (weekdays, every day, or e.g. Mon,Wed,Fri): ");
+    match input.to_lowercase().as_str() {
+        "weekdays" => Some(vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri]),
+        "every day" | "everyday" | "daily" => {
+            Some(vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri, Weekday::Sat, Weekday::Sun])
+        }
+        days => match days.split(',').map(|day| day.trim().parse::<Weekday>()).collect() {
+            Ok(parsed) => Some(parsed),
+            Err(_) => {
+                println!("❌ Invalid weekday list.");
+                None
+            }
+        },
+    }
+}
+
+/// ✅ Prompts the user for a 1-10 score (Optional)
+fn prompt_optional_score(message: &str) -> Option<i32> {
+    prompt_user_input(message).parse::<i32>().ok()
+}
+
+/// ✅ Prompts the user for a date, reporting invalid input.
+fn prompt_date(message: &str) -> Option<NaiveDate> {
+    let input = prompt_user_input(message);
+    match NaiveDate::parse_from_str(&input, "%Y-%m-%d") {
+        Ok(date) => Some(date),
+        Err(_) => {
+            println!("❌ Invalid date format. Use YYYY-MM-DD.");
+            None
+        }
+    }
+}
+
+/// ✅ Prompts the user for a time of day, reporting invalid input.
+fn prompt_time(message: &str) -> Option<NaiveTime> {
+    let input = prompt_user_input(message);
+    match NaiveTime::parse_from_str(&input, "%H:%M") {
+        Ok(time) => Some(time),
+        Err(_) => {
+            println!("❌ Invalid time format. Use HH:MM.");
+            None
+        }
+    }
+}
+
+/// ✅ Prompts the user for a local date and time and returns it in UTC (Optional)
+///
+/// - Only empty input skips; malformed or nonexistent times are asked for again.
+fn prompt_optional_datetime(message: &str, tz: &Tz) -> Option<NaiveDateTime> {
+    loop {
+        let input = prompt_user_input(message);
+        if input.is_empty() {
+            return None;
+        }
+        match NaiveDateTime::parse_from_str(&input, "%Y-%m-%d %H:%M") {
+            Ok(local) => {
+                if let Some(utc) = local_time_to_utc(tz, local) {
+                    return Some(utc);
+                }
+            }
+            Err(_) => println!("❌ Invalid date format. Use YYYY-MM-DD HH:MM, or press Enter to skip."),
+        }
+    }
+}
+
+/// ✅ Prompts the user to pick a fasting protocol (Optional)
+fn prompt_optional_protocol_id(conn: &mut SqliteConnection, user: &User) -> Option<i32> {
+    print_protocols(conn, user);
+    let input = prompt_user_input("Enter Protocol ID (or press Enter to skip): ");
+    input.parse::<i32>().ok()
+}
+
+/// ✅ Lets the user pick one of their active goals from a numbered list (Optional)
+fn prompt_optional_goal_id(conn: &mut SqliteConnection, user: &User, clock: &dyn Clock) -> Option<i32> {
+    let tz = user_timezone(conn, user);
+    let goals = match list_active_goals(conn, user.id, clock) {
+        Ok(goals) => goals,
+        Err(e) => {
+            eprintln!("❌ Error retrieving goals: {}", e);
+            return None;
+        }
+    };
+    if goals.is_empty() {
+        println!("ℹ️ No active goals to link; continuing without one.");
+        return None;
+    }
+
+    println!("🎯 Active goals:");
+    for (position, goal) in goals.iter().enumerate() {
+        println!("{}. {}h by {} ({})", position + 1, goal.goal_duration, format_local(&tz, goal.deadline), goal.status);
+    }
+
+    loop {
+        let input = prompt_user_input(&format!("Pick a goal (1-{}, or press Enter to skip): ", goals.len()));
+        if input.is_empty() {
+            return None;
+        }
+        match input.parse::<usize>() {
+            Ok(position) if (1..=goals.len()).contains(&position) => return Some(goals[position - 1].id),
+            _ => println!("❌ Invalid choice. Please pick a number from the list."),
+        }
     }
 }
 
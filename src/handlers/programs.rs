@@ -0,0 +1,746 @@
+use crate::clock::Clock;
+use crate::errors::FastingAppError;
+use crate::handlers::goals::{evaluate_goals, GoalStatus};
+use crate::handlers::timezone::{get_user_timezone, local_midnight_utc, local_today};
+use crate::models::{
+    FastingGoal, FastingProgram, NewFastingGoal, NewFastingProgram, NewProgramDay, NewProgramEnrollment, ProgramDay,
+    ProgramEnrollment,
+};
+use crate::schema::fasting_programs::dsl::{fasting_programs, id, name, slug, user_id};
+use chrono::{Duration, NaiveDate, NaiveDateTime, TimeZone};
+use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use diesel::SqliteConnection;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Built-in programs, shipped as data files in `programs/`.
+const BUILT_IN_PROGRAMS: [&str; 3] = [
+    include_str!("../../programs/starter_16_8.json"),
+    include_str!("../../programs/ramp_to_24h.json"),
+    include_str!("../../programs/reset_18_6.json"),
+];
+
+/// Longest program that can be imported, in days.
+pub const MAX_PROGRAM_DAYS: usize = 366;
+
+/// Highest daily target a program may set, in hours.
+pub const MAX_DAILY_TARGET_HOURS: i32 = 72;
+
+/// A program as written in a data file.
+///
+/// `days` holds the target hours for each day in order; 0 marks a rest day.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProgramDefinition {
+    #[serde(default)]
+    pub slug: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub days: Vec<i32>,
+}
+
+/// Where a single program day stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgramDayOutcome {
+    /// The program sets no fast for this day.
+    Rest,
+    /// The day has not started yet, or the enrollment ended before it.
+    Upcoming,
+    /// The day's goal is open and no fast is running for it.
+    Pending,
+    /// A fast toward the day's goal is running.
+    InProgress,
+    /// The day's target was reached.
+    Achieved,
+    /// The day ended without reaching its target.
+    Missed,
+}
+
+/// One day of an enrollment with its outcome.
+#[derive(Debug, Clone)]
+pub struct ProgramDayProgress {
+    pub day_number: i32,
+    pub date: NaiveDate,
+    pub target_hours: i32,
+    pub outcome: ProgramDayOutcome,
+}
+
+/// How far a user has come in a program they enrolled in.
+#[derive(Debug)]
+pub struct ProgramProgress {
+    pub enrollment: ProgramEnrollment,
+    pub program_name: String,
+    pub days: Vec<ProgramDayProgress>,
+    pub achieved: usize,
+    pub missed: usize,
+    /// Fasting days that are still open or have not started.
+    pub remaining: usize,
+    /// Share of decided fasting days that were achieved; `None` until a day is decided.
+    pub score_percent: Option<f64>,
+}
+
+/// ✅ Parses a program data file and checks that it describes a usable program.
+pub fn parse_program_definition(json: &str) -> Result<ProgramDefinition, FastingAppError> {
+    let definition: ProgramDefinition = serde_json::from_str(json)
+        .map_err(|err| FastingAppError::InvalidRequest(format!("Invalid program file: {}", err)))?;
+
+    if definition.name.trim().is_empty() {
+        return Err(FastingAppError::InvalidRequest(
+            "Program name cannot be empty.".to_string(),
+        ));
+    }
+    if definition.days.is_empty() || definition.days.len() > MAX_PROGRAM_DAYS {
+        return Err(FastingAppError::InvalidRequest(format!(
+            "A program must have between 1 and {} days.",
+            MAX_PROGRAM_DAYS
+        )));
+    }
+    if let Some(position) = definition
+        .days
+        .iter()
+        .position(|hours| !(0..=MAX_DAILY_TARGET_HOURS).contains(hours))
+    {
+        return Err(FastingAppError::InvalidRequest(format!(
+            "Day {} must have a target between 0 and {} hours.",
+            position + 1,
+            MAX_DAILY_TARGET_HOURS
+        )));
+    }
+    if definition.days.iter().all(|hours| *hours == 0) {
+        return Err(FastingAppError::InvalidRequest(
+            "A program needs at least one fasting day.".to_string(),
+        ));
+    }
+    Ok(definition)
+}
+
+/// ✅ Adds any built-in program that is not in the database yet.
+pub fn install_built_in_programs(conn: &mut SqliteConnection, clock: &dyn Clock) -> Result<(), FastingAppError> {
+    for json in BUILT_IN_PROGRAMS {
+        let definition = parse_program_definition(json)?;
+        let installed = fasting_programs
+            .filter(slug.eq(&definition.slug))
+            .select(id)
+            .first::<i32>(conn)
+            .optional()
+            .map_err(FastingAppError::DatabaseError)?
+            .is_some();
+        if !installed {
+            insert_program(conn, None, &definition, clock)?;
+        }
+    }
+    Ok(())
+}
+
+/// ✅ Lists the built-in programs followed by the user's imported programs.
+pub fn list_programs(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    clock: &dyn Clock,
+) -> Result<Vec<FastingProgram>, FastingAppError> {
+    install_built_in_programs(conn, clock)?;
+
+    fasting_programs
+        .filter(user_id.is_null().or(user_id.eq(user_id_input)))
+        .order((user_id.is_not_null(), id.asc()))
+        .select(FastingProgram::as_select())
+        .load::<FastingProgram>(conn)
+        .map_err(FastingAppError::DatabaseError)
+}
+
+/// ✅ Finds a program the user may enroll in (built-in or their own).
+///
+/// - Returns `ProgramNotFound` for unknown IDs and other users' programs.
+pub fn find_program(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    program_id: i32,
+) -> Result<FastingProgram, FastingAppError> {
+    fasting_programs
+        .filter(id.eq(program_id))
+        .filter(user_id.is_null().or(user_id.eq(user_id_input)))
+        .select(FastingProgram::as_select())
+        .first::<FastingProgram>(conn)
+        .optional()
+        .map_err(FastingAppError::DatabaseError)?
+        .ok_or(FastingAppError::ProgramNotFound(program_id))
+}
+
+/// ✅ Returns the daily targets of a program the user can see, in day order.
+pub fn get_program_days(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    program_id: i32,
+) -> Result<Vec<ProgramDay>, FastingAppError> {
+    let program = find_program(conn, user_id_input, program_id)?;
+    load_program_days(conn, program.id)
+}
+
+/// ✅ Imports a program from a JSON data file for the user.
+///
+/// - The file uses the same format as the built-in programs in `programs/`.
+/// - The name must not clash with a program the user can already see.
+pub fn import_program(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    file_path: &str,
+    clock: &dyn Clock,
+) -> Result<FastingProgram, FastingAppError> {
+    let json = std::fs::read_to_string(file_path).map_err(|err| FastingAppError::FileError(err.to_string()))?;
+    let definition = parse_program_definition(&json)?;
+    let program_name = definition.name.trim();
+
+    install_built_in_programs(conn, clock)?;
+    let name_taken = fasting_programs
+        .filter(user_id.is_null().or(user_id.eq(user_id_input)))
+        .filter(name.eq(program_name))
+        .select(id)
+        .first::<i32>(conn)
+        .optional()
+        .map_err(FastingAppError::DatabaseError)?
+        .is_some();
+    if name_taken {
+        return Err(FastingAppError::InvalidRequest(format!(
+            "A program named '{}' already exists.",
+            program_name
+        )));
+    }
+
+    insert_program(conn, Some(user_id_input), &definition, clock)
+}
+
+/// ✅ Deletes one of the user's imported programs.
+///
+/// - Built-in programs cannot be deleted, nor can a program the user is following.
+/// - Past enrollments are removed; their goals are kept as ordinary goals.
+pub fn delete_program(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    program_id: i32,
+) -> Result<(), FastingAppError> {
+    use crate::schema::fasting_goals::dsl::{enrollment_id, fasting_goals};
+    use crate::schema::program_enrollments::dsl::{
+        id as enrollment_pk, program_enrollments, program_id as enrollment_program_id, status,
+    };
+
+    let program = find_program(conn, user_id_input, program_id)?;
+    if program.user_id.is_none() {
+        return Err(FastingAppError::InvalidRequest(format!(
+            "Built-in program '{}' cannot be deleted.",
+            program.name
+        )));
+    }
+
+    conn.transaction(|conn| {
+        let enrollment_ids = program_enrollments
+            .filter(enrollment_program_id.eq(program.id))
+            .select((enrollment_pk, status))
+            .load::<(i32, String)>(conn)
+            .map_err(FastingAppError::DatabaseError)?;
+        if enrollment_ids.iter().any(|(_, enrollment_status)| enrollment_status == "active") {
+            return Err(FastingAppError::InvalidRequest(format!(
+                "Drop out of '{}' before deleting it.",
+                program.name
+            )));
+        }
+        let enrollment_ids: Vec<i32> = enrollment_ids.into_iter().map(|(pk, _)| pk).collect();
+
+        diesel::update(fasting_goals.filter(enrollment_id.eq_any(&enrollment_ids)))
+            .set(enrollment_id.eq(None::<i32>))
+            .execute(conn)
+            .map_err(FastingAppError::DatabaseError)?;
+        diesel::delete(program_enrollments.filter(enrollment_pk.eq_any(&enrollment_ids)))
+            .execute(conn)
+            .map_err(FastingAppError::DatabaseError)?;
+        diesel::delete(fasting_programs.filter(id.eq(program.id)))
+            .execute(conn)
+            .map(|_| ())
+            .map_err(FastingAppError::DatabaseError)
+    })
+}
+
+/// ✅ Enrolls the user in a program starting on `start_date`.
+///
+/// - A user follows one program at a time; drop out of the current one first.
+/// - Each fasting day becomes a goal due at local midnight after that day, created as the day arrives.
+pub fn enroll_in_program(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    program_id: i32,
+    start_date: NaiveDate,
+    clock: &dyn Clock,
+) -> Result<ProgramEnrollment, FastingAppError> {
+    use crate::schema::program_enrollments::dsl::program_enrollments;
+
+    let program = find_program(conn, user_id_input, program_id)?;
+    let new_enrollment = NewProgramEnrollment {
+        user_id: user_id_input,
+        program_id: program.id,
+        start_date,
+        created_at: Some(clock.now()),
+    };
+
+    let enrollment = diesel::insert_into(program_enrollments)
+        .values(&new_enrollment)
+        .returning(ProgramEnrollment::as_returning())
+        .get_result(conn)
+        .map_err(|err| match err {
+            DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => FastingAppError::InvalidRequest(
+                "You are already following a program. Drop out of it before starting another.".to_string(),
+            ),
+            other => FastingAppError::DatabaseError(other),
+        })?;
+
+    let today = local_today(&get_user_timezone(conn, user_id_input)?, clock);
+    generate_program_goals(conn, user_id_input, today, clock)?;
+    Ok(enrollment)
+}
+
+/// ✅ Lists the user's enrollments, newest first.
+pub fn list_enrollments(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    clock: &dyn Clock,
+) -> Result<Vec<ProgramEnrollment>, FastingAppError> {
+    use crate::schema::program_enrollments::dsl::{id as enrollment_pk, program_enrollments, user_id as enrollment_user_id};
+
+    let today = local_today(&get_user_timezone(conn, user_id_input)?, clock);
+    generate_program_goals(conn, user_id_input, today, clock)?;
+
+    program_enrollments
+        .filter(enrollment_user_id.eq(user_id_input))
+        .order(enrollment_pk.desc())
+        .select(ProgramEnrollment::as_select())
+        .load::<ProgramEnrollment>(conn)
+        .map_err(FastingAppError::DatabaseError)
+}
+
+/// ✅ Drops the user out of an active enrollment.
+///
+/// - Goals for days that already ended keep their status; the open day's goal is archived.
+pub fn drop_out_of_program(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    enrollment_id_input: i32,
+    clock: &dyn Clock,
+) -> Result<(), FastingAppError> {
+    use crate::schema::fasting_goals::dsl::{archived_at, deadline, enrollment_id, fasting_goals, status as goal_status};
+    use crate::schema::program_enrollments::dsl::{ended_at, id as enrollment_pk, program_enrollments, status};
+
+    let enrollment = find_enrollment(conn, user_id_input, enrollment_id_input)?;
+    if enrollment.status != "active" {
+        return Err(FastingAppError::InvalidRequest(format!(
+            "Enrollment {} has already ended.",
+            enrollment.id
+        )));
+    }
+    let now = clock.now();
+
+    conn.transaction(|conn| {
+        diesel::update(
+            fasting_goals
+                .filter(enrollment_id.eq(enrollment.id))
+                .filter(deadline.gt(now))
+                .filter(goal_status.ne(GoalStatus::Achieved.as_str())),
+        )
+        .set(archived_at.eq(Some(now)))
+        .execute(conn)
+        .map_err(FastingAppError::DatabaseError)?;
+
+        diesel::update(program_enrollments.filter(enrollment_pk.eq(enrollment.id)))
+            .set((status.eq("dropped"), ended_at.eq(Some(now))))
+            .execute(conn)
+            .map(|_| ())
+            .map_err(FastingAppError::DatabaseError)
+    })
+}
+
+/// ✅ Scores an enrollment from the user's fasts.
+///
+/// - Every program day is listed; days are decided by evaluating their goals.
+pub fn get_program_progress(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    enrollment_id_input: i32,
+    clock: &dyn Clock,
+) -> Result<ProgramProgress, FastingAppError> {
+    use crate::schema::fasting_goals::dsl::{enrollment_id, fasting_goals};
+
+    let today = local_today(&get_user_timezone(conn, user_id_input)?, clock);
+    generate_program_goals(conn, user_id_input, today, clock)?;
+    let enrollment = find_enrollment(conn, user_id_input, enrollment_id_input)?;
+    let program = find_program(conn, user_id_input, enrollment.program_id)?;
+    let days = load_program_days(conn, program.id)?;
+
+    let evaluations: HashMap<i32, GoalStatus> = evaluate_goals(conn, user_id_input, clock)?
+        .into_iter()
+        .map(|evaluation| (evaluation.goal_id, evaluation.status))
+        .collect();
+    let day_statuses: HashMap<NaiveDate, GoalStatus> = fasting_goals
+        .filter(enrollment_id.eq(enrollment.id))
+        .select(FastingGoal::as_select())
+        .load::<FastingGoal>(conn)
+        .map_err(FastingAppError::DatabaseError)?
+        .into_iter()
+        .filter(|goal| goal.archived_at.is_none())
+        .filter_map(|goal| Some((goal.period_start?, *evaluations.get(&goal.id)?)))
+        .collect();
+
+    let days = program_day_outcomes(&days, enrollment.start_date, &day_statuses);
+    let count = |outcome: ProgramDayOutcome| days.iter().filter(|day| day.outcome == outcome).count();
+    let achieved = count(ProgramDayOutcome::Achieved);
+    let missed = count(ProgramDayOutcome::Missed);
+    let remaining = days
+        .iter()
+        .filter(|day| day.outcome != ProgramDayOutcome::Rest)
+        .count()
+        - achieved
+        - missed;
+    let decided = achieved + missed;
+    let score_percent = (decided > 0).then(|| achieved as f64 / decided as f64 * 100.0);
+
+    Ok(ProgramProgress {
+        enrollment,
+        program_name: program.name,
+        days,
+        achieved,
+        missed,
+        remaining,
+        score_percent,
+    })
+}
+
+/// ✅ Pairs each program day with its date and outcome.
+///
+/// - `statuses` maps a day's date to the status of its goal; days without a goal are upcoming.
+pub fn program_day_outcomes(
+    days: &[ProgramDay],
+    start_date: NaiveDate,
+    statuses: &HashMap<NaiveDate, GoalStatus>,
+) -> Vec<ProgramDayProgress> {
+    days.iter()
+        .map(|day| {
+            let date = program_day_date(start_date, day.day_number);
+            let outcome = if day.target_hours == 0 {
+                ProgramDayOutcome::Rest
+            } else {
+                match statuses.get(&date) {
+                    Some(GoalStatus::Achieved) => ProgramDayOutcome::Achieved,
+                    Some(GoalStatus::Missed) => ProgramDayOutcome::Missed,
+                    Some(GoalStatus::InProgress) => ProgramDayOutcome::InProgress,
+                    Some(GoalStatus::Pending) => ProgramDayOutcome::Pending,
+                    None => ProgramDayOutcome::Upcoming,
+                }
+            };
+            ProgramDayProgress {
+                day_number: day.day_number,
+                date,
+                target_hours: day.target_hours,
+                outcome,
+            }
+        })
+        .collect()
+}
+
+/// ✅ Creates the goals for every day of the user's active enrollment up to `through`.
+///
+/// - Days that already have a goal are skipped, so this is safe to call repeatedly.
+/// - Once the last day has ended, the enrollment is marked completed.
+/// - Returns the number of goals created.
+pub fn generate_program_goals(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    through: NaiveDate,
+    clock: &dyn Clock,
+) -> Result<usize, FastingAppError> {
+    use crate::schema::fasting_goals::dsl::fasting_goals;
+    use crate::schema::program_enrollments::dsl::{
+        ended_at, id as enrollment_pk, program_enrollments, status, user_id as enrollment_user_id,
+    };
+
+    let active = program_enrollments
+        .filter(enrollment_user_id.eq(user_id_input))
+        .filter(status.eq("active"))
+        .select(ProgramEnrollment::as_select())
+        .load::<ProgramEnrollment>(conn)
+        .map_err(FastingAppError::DatabaseError)?;
+    let tz = get_user_timezone(conn, user_id_input)?;
+    let now = clock.now();
+    let mut created = 0;
+
+    for enrollment in &active {
+        let days = load_program_days(conn, enrollment.program_id)?;
+        let new_goals: Vec<NewFastingGoal> = days
+            .iter()
+            .filter(|day| day.target_hours > 0)
+            .map(|day| (program_day_date(enrollment.start_date, day.day_number), day.target_hours))
+            .filter(|(date, _)| *date <= through)
+            .map(|(date, hours)| NewFastingGoal {
+                user_id: user_id_input,
+                goal_duration: hours,
+                deadline: day_deadline(&tz, date),
+                created_at: Some(now),
+                recurring_goal_id: None,
+                period_start: Some(date),
+                enrollment_id: Some(enrollment.id),
+            })
+            .collect();
+
+        if !new_goals.is_empty() {
+            created += diesel::insert_or_ignore_into(fasting_goals)
+                .values(&new_goals)
+                .execute(conn)
+                .map_err(FastingAppError::DatabaseError)?;
+        }
+
+        let last_day = days.last().map_or(1, |day| day.day_number);
+        let program_end = day_deadline(&tz, program_day_date(enrollment.start_date, last_day));
+        if program_end <= now {
+            diesel::update(program_enrollments.filter(enrollment_pk.eq(enrollment.id)))
+                .set((status.eq("completed"), ended_at.eq(Some(program_end))))
+                .execute(conn)
+                .map_err(FastingAppError::DatabaseError)?;
+        }
+    }
+    Ok(created)
+}
+
+/// Finds an enrollment by ID, making sure it belongs to the user.
+fn find_enrollment(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    enrollment_id_input: i32,
+) -> Result<ProgramEnrollment, FastingAppError> {
+    use crate::schema::program_enrollments::dsl::{id as enrollment_pk, program_enrollments, user_id as enrollment_user_id};
+
+    program_enrollments
+        .filter(enrollment_pk.eq(enrollment_id_input))
+        .filter(enrollment_user_id.eq(user_id_input))
+        .select(ProgramEnrollment::as_select())
+        .first::<ProgramEnrollment>(conn)
+        .optional()
+        .map_err(FastingAppError::DatabaseError)?
+        .ok_or(FastingAppError::EnrollmentNotFound(enrollment_id_input))
+}
+
+/// Loads a program's days in order.
+fn load_program_days(conn: &mut SqliteConnection, program_id_input: i32) -> Result<Vec<ProgramDay>, FastingAppError> {
+    use crate::schema::program_days::dsl::{day_number, program_days, program_id};
+
+    program_days
+        .filter(program_id.eq(program_id_input))
+        .order(day_number.asc())
+        .select(ProgramDay::as_select())
+        .load::<ProgramDay>(conn)
+        .map_err(FastingAppError::DatabaseError)
+}
+
+/// Inserts a program and its days.
+fn insert_program(
+    conn: &mut SqliteConnection,
+    owner: Option<i32>,
+    definition: &ProgramDefinition,
+    clock: &dyn Clock,
+) -> Result<FastingProgram, FastingAppError> {
+    use crate::schema::program_days::dsl::program_days;
+
+    conn.transaction(|conn| {
+        let new_program = NewFastingProgram {
+            user_id: owner,
+            // Only built-in programs are identified by slug
+            slug: owner.map_or_else(|| definition.slug.clone(), |_| None),
+            name: definition.name.trim().to_string(),
+            description: definition.description.clone(),
+            created_at: Some(clock.now()),
+        };
+        let program = diesel::insert_into(fasting_programs)
+            .values(&new_program)
+            .returning(FastingProgram::as_returning())
+            .get_result(conn)
+            .map_err(FastingAppError::DatabaseError)?;
+
+        let new_days: Vec<NewProgramDay> = definition
+            .days
+            .iter()
+            .zip(1..)
+            .map(|(hours, number)| NewProgramDay {
+                program_id: program.id,
+                day_number: number,
+                target_hours: *hours,
+            })
+            .collect();
+        diesel::insert_into(program_days)
+            .values(&new_days)
+            .execute(conn)
+            .map_err(FastingAppError::DatabaseError)?;
+        Ok(program)
+    })
+}
+
+/// Calendar date of a 1-based program day.
+fn program_day_date(start_date: NaiveDate, day_number: i32) -> NaiveDate {
+    start_date + Duration::days(i64::from(day_number - 1))
+}
+
+/// Deadline of a program day: local midnight after it, as UTC.
+fn day_deadline<Tz: TimeZone>(tz: &Tz, date: NaiveDate) -> NaiveDateTime {
+    local_midnight_utc(tz, date + Duration::days(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FakeClock;
+    use crate::db::{insert_test_user, test_connection};
+    use crate::handlers::fasting::record_completed_fast;
+    use crate::schema::fasting_goals::dsl::{enrollment_id, fasting_goals};
+
+    #[test]
+    fn test_built_in_programs_parse() {
+        for json in BUILT_IN_PROGRAMS {
+            let definition = parse_program_definition(json).unwrap();
+            assert!(definition.slug.is_some());
+        }
+    }
+
+    #[test]
+    fn test_program_definition_rejects_bad_targets() {
+        assert!(parse_program_definition(r#"{"name": "Too long", "days": [16, 80]}"#).is_err());
+        assert!(parse_program_definition(r#"{"name": "Only rest", "days": [0, 0]}"#).is_err());
+        assert!(parse_program_definition(r#"{"name": " ", "days": [16]}"#).is_err());
+        assert!(parse_program_definition(r#"{"name": "Fine", "days": [16, 0, 18]}"#).is_ok());
+    }
+
+    #[test]
+    fn test_program_days_take_goal_outcomes() {
+        let start = NaiveDate::from_ymd_opt(2025, 4, 1).unwrap();
+        let days: Vec<ProgramDay> = [16, 0, 18, 18]
+            .iter()
+            .zip(1..)
+            .map(|(hours, number)| ProgramDay {
+                id: number,
+                program_id: 1,
+                day_number: number,
+                target_hours: *hours,
+            })
+            .collect();
+        let statuses = HashMap::from([
+            (start, GoalStatus::Achieved),
+            (start + Duration::days(2), GoalStatus::InProgress),
+        ]);
+
+        let outcomes: Vec<ProgramDayOutcome> = program_day_outcomes(&days, start, &statuses)
+            .into_iter()
+            .map(|day| day.outcome)
+            .collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                ProgramDayOutcome::Achieved,
+                ProgramDayOutcome::Rest,
+                ProgramDayOutcome::InProgress,
+                ProgramDayOutcome::Upcoming,
+            ]
+        );
+    }
+
+    fn day(number: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 4, number).unwrap()
+    }
+
+    fn at(number: u32, hour: u32) -> NaiveDateTime {
+        day(number).and_hms_opt(hour, 0, 0).unwrap()
+    }
+
+    fn own_program(conn: &mut SqliteConnection, owner: i32, days: &str, clock: &FakeClock) -> FastingProgram {
+        let json = format!(r#"{{"name": "Short program", "days": {}}}"#, days);
+        insert_program(conn, Some(owner), &parse_program_definition(&json).unwrap(), clock).unwrap()
+    }
+
+    fn program_goals(conn: &mut SqliteConnection, enrollment: &ProgramEnrollment) -> Vec<FastingGoal> {
+        fasting_goals
+            .filter(enrollment_id.eq(enrollment.id))
+            .select(FastingGoal::as_select())
+            .load::<FastingGoal>(conn)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_enrollment_creates_goals_as_days_arrive_and_completes() {
+        let mut conn = test_connection();
+        let user = insert_test_user(&mut conn, "follower");
+        let clock = FakeClock::new(at(1, 12));
+        let program = own_program(&mut conn, user, "[16, 0, 18]", &clock);
+        assert_eq!(program.created_at, Some(at(1, 12)));
+
+        let enrollment = enroll_in_program(&mut conn, user, program.id, day(1), &clock).unwrap();
+        assert_eq!(program_goals(&mut conn, &enrollment).len(), 1);
+        assert!(matches!(
+            enroll_in_program(&mut conn, user, program.id, day(2), &clock),
+            Err(FastingAppError::InvalidRequest(_))
+        ));
+        assert_eq!(generate_program_goals(&mut conn, user, day(1), &clock).unwrap(), 0);
+
+        // Day 2 is a rest day, so only day 3 gets a goal
+        clock.set(at(3, 12));
+        assert_eq!(generate_program_goals(&mut conn, user, day(3), &clock).unwrap(), 1);
+        assert_eq!(generate_program_goals(&mut conn, user, day(3), &clock).unwrap(), 0);
+        assert_eq!(list_enrollments(&mut conn, user, &clock).unwrap()[0].status, "active");
+
+        clock.set(at(4, 1));
+        let finished = list_enrollments(&mut conn, user, &clock).unwrap().remove(0);
+        assert_eq!(finished.status, "completed");
+        assert_eq!(finished.ended_at, Some(at(4, 0)));
+        enroll_in_program(&mut conn, user, program.id, day(4), &clock).unwrap();
+    }
+
+    #[test]
+    fn test_dropping_out_archives_only_the_open_day() {
+        let mut conn = test_connection();
+        let user = insert_test_user(&mut conn, "follower");
+        let clock = FakeClock::new(at(1, 12));
+        let program = own_program(&mut conn, user, "[16, 16, 16]", &clock);
+        let enrollment = enroll_in_program(&mut conn, user, program.id, day(1), &clock).unwrap();
+
+        clock.set(at(2, 10));
+        record_completed_fast(&mut conn, user, at(1, 0), at(1, 17), None, None, &clock).unwrap();
+        let progress = get_program_progress(&mut conn, user, enrollment.id, &clock).unwrap();
+        assert_eq!(progress.achieved, 1);
+
+        drop_out_of_program(&mut conn, user, enrollment.id, &clock).unwrap();
+        let archived: Vec<(Option<NaiveDate>, bool)> = program_goals(&mut conn, &enrollment)
+            .into_iter()
+            .map(|goal| (goal.period_start, goal.archived_at.is_some()))
+            .collect();
+        assert_eq!(archived, vec![(Some(day(1)), false), (Some(day(2)), true)]);
+
+        let progress = get_program_progress(&mut conn, user, enrollment.id, &clock).unwrap();
+        assert_eq!(progress.enrollment.status, "dropped");
+        assert_eq!((progress.achieved, progress.missed), (1, 0));
+        assert!(drop_out_of_program(&mut conn, user, enrollment.id, &clock).is_err());
+    }
+
+    #[test]
+    fn test_programs_cannot_be_deleted_while_followed() {
+        let mut conn = test_connection();
+        let user = insert_test_user(&mut conn, "follower");
+        let clock = FakeClock::new(at(1, 12));
+        let program = own_program(&mut conn, user, "[16, 16]", &clock);
+        let enrollment = enroll_in_program(&mut conn, user, program.id, day(1), &clock).unwrap();
+
+        assert!(matches!(
+            delete_program(&mut conn, user, program.id),
+            Err(FastingAppError::InvalidRequest(_))
+        ));
+
+        drop_out_of_program(&mut conn, user, enrollment.id, &clock).unwrap();
+        delete_program(&mut conn, user, program.id).unwrap();
+        assert!(matches!(
+            find_program(&mut conn, user, program.id),
+            Err(FastingAppError::ProgramNotFound(_))
+        ));
+        assert!(program_goals(&mut conn, &enrollment).is_empty());
+        assert_eq!(fasting_goals.count().get_result::<i64>(&mut conn).unwrap(), 1);
+    }
+}
@@ -0,0 +1,197 @@
+use crate::errors::FastingAppError;
+use crate::models::{FastingProtocol, NewFastingProtocol};
+use crate::schema::fasting_protocols::dsl::{fasting_protocols, id, name, user_id};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::SqliteConnection;
+
+impl FastingProtocol {
+    /// ✅ Target length of a single fast under this protocol, in minutes.
+    pub fn target_fasting_minutes(&self) -> i64 {
+        i64::from(self.fasting_hours) * 60
+    }
+
+    /// ✅ Built-in protocols are shared by every user and cannot be changed.
+    pub fn is_built_in(&self) -> bool {
+        self.user_id.is_none()
+    }
+}
+
+/// ✅ Lists the built-in protocols followed by the user's own protocols.
+pub fn list_protocols(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+) -> Result<Vec<FastingProtocol>, FastingAppError> {
+    fasting_protocols
+        .filter(user_id.is_null().or(user_id.eq(user_id_input)))
+        .order((user_id.is_not_null(), id.asc()))
+        .select(FastingProtocol::as_select())
+        .load::<FastingProtocol>(conn)
+        .map_err(FastingAppError::DatabaseError)
+}
+
+/// ✅ Finds a protocol the user may fast with (built-in or their own).
+///
+/// - Returns `ProtocolNotFound` for unknown IDs and other users' protocols.
+pub fn find_protocol(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    protocol_id: i32,
+) -> Result<FastingProtocol, FastingAppError> {
+    fasting_protocols
+        .filter(id.eq(protocol_id))
+        .filter(user_id.is_null().or(user_id.eq(user_id_input)))
+        .select(FastingProtocol::as_select())
+        .first::<FastingProtocol>(conn)
+        .optional()
+        .map_err(FastingAppError::DatabaseError)?
+        .ok_or(FastingAppError::ProtocolNotFound(protocol_id))
+}
+
+/// ✅ Creates a user-defined protocol.
+///
+/// - The name must be non-empty and not clash with a protocol the user can already see.
+/// - The fasting window must be positive and the eating window non-negative.
+pub fn create_protocol(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    protocol_name: &str,
+    fasting_hours: i32,
+    eating_hours: i32,
+    description: Option<&str>,
+) -> Result<(), FastingAppError> {
+    let protocol_name = protocol_name.trim();
+    if protocol_name.is_empty() {
+        return Err(FastingAppError::InvalidRequest(
+            "Protocol name cannot be empty.".to_string(),
+        ));
+    }
+    if fasting_hours <= 0 || eating_hours < 0 {
+        return Err(FastingAppError::InvalidRequest(
+            "Fasting hours must be positive and eating hours cannot be negative.".to_string(),
+        ));
+    }
+
+    let name_taken = fasting_protocols
+        .filter(user_id.is_null().or(user_id.eq(user_id_input)))
+        .filter(name.eq(protocol_name))
+        .select(id)
+        .first::<i32>(conn)
+        .optional()
+        .map_err(FastingAppError::DatabaseError)?
+        .is_some();
+    if name_taken {
+        return Err(FastingAppError::InvalidRequest(format!(
+            "A protocol named '{}' already exists.",
+            protocol_name
+        )));
+    }
+
+    let new_protocol = NewFastingProtocol {
+        user_id: Some(user_id_input),
+        name: protocol_name.to_string(),
+        fasting_hours,
+        eating_hours,
+        description: description.map(str::to_string),
+        created_at: Some(Utc::now().naive_utc()),
+    };
+
+    diesel::insert_into(fasting_protocols)
+        .values(&new_protocol)
+        .execute(conn)
+        .map(|_| ())
+        .map_err(FastingAppError::DatabaseError)
+}
+
+/// ✅ Deletes one of the user's own protocols.
+///
+/// - Built-in protocols cannot be deleted.
+/// - Fasting events that used the protocol keep their history with no protocol.
+pub fn delete_protocol(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    protocol_id: i32,
+) -> Result<(), FastingAppError> {
+    use crate::schema::fasting_events::dsl::{fasting_events, protocol_id as event_protocol_id};
+
+    let protocol = find_protocol(conn, user_id_input, protocol_id)?;
+    if protocol.is_built_in() {
+        return Err(FastingAppError::InvalidRequest(format!(
+            "Built-in protocol '{}' cannot be deleted.",
+            protocol.name
+        )));
+    }
+
+    conn.transaction(|conn| {
+        diesel::update(fasting_events.filter(event_protocol_id.eq(protocol.id)))
+            .set(event_protocol_id.eq(None::<i32>))
+            .execute(conn)
+            .map_err(FastingAppError::DatabaseError)?;
+
+        diesel::delete(fasting_protocols.filter(id.eq(protocol.id)))
+            .execute(conn)
+            .map(|_| ())
+            .map_err(FastingAppError::DatabaseError)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FakeClock;
+    use crate::db::{insert_test_user, test_connection};
+    use crate::handlers::fasting::{find_user_fasting_event, get_user_fasting_events, record_completed_fast};
+    use chrono::NaiveDate;
+
+    fn own_protocol(conn: &mut SqliteConnection, owner: i32, protocol_name: &str) -> FastingProtocol {
+        list_protocols(conn, owner)
+            .unwrap()
+            .into_iter()
+            .find(|protocol| protocol.name == protocol_name)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_users_can_only_delete_their_own_protocols() {
+        let mut conn = test_connection();
+        let alice = insert_test_user(&mut conn, "alice");
+        let bob = insert_test_user(&mut conn, "bob");
+
+        let built_in = list_protocols(&mut conn, alice).unwrap().remove(0);
+        assert!(built_in.is_built_in());
+        assert!(matches!(
+            delete_protocol(&mut conn, alice, built_in.id),
+            Err(FastingAppError::InvalidRequest(_))
+        ));
+        assert!(create_protocol(&mut conn, alice, &built_in.name, 16, 8, None).is_err());
+
+        create_protocol(&mut conn, alice, "Weekday 18:6", 18, 6, None).unwrap();
+        assert!(create_protocol(&mut conn, alice, " Weekday 18:6 ", 18, 6, None).is_err());
+        let mine = own_protocol(&mut conn, alice, "Weekday 18:6");
+        assert!(list_protocols(&mut conn, bob).unwrap().iter().all(|protocol| protocol.id != mine.id));
+        assert!(matches!(
+            delete_protocol(&mut conn, bob, mine.id),
+            Err(FastingAppError::ProtocolNotFound(_))
+        ));
+
+        delete_protocol(&mut conn, alice, mine.id).unwrap();
+        assert!(list_protocols(&mut conn, alice).unwrap().iter().all(|protocol| protocol.id != mine.id));
+    }
+
+    #[test]
+    fn test_deleting_a_protocol_keeps_the_fasts_that_used_it() {
+        let mut conn = test_connection();
+        let alice = insert_test_user(&mut conn, "alice");
+        let day = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        let clock = FakeClock::new(day.and_hms_opt(23, 0, 0).unwrap());
+
+        create_protocol(&mut conn, alice, "Weekend 20:4", 20, 4, Some("Saturdays and Sundays")).unwrap();
+        let weekend = own_protocol(&mut conn, alice, "Weekend 20:4");
+        let (start, stop) = (day.and_hms_opt(0, 0, 0).unwrap(), day.and_hms_opt(22, 0, 0).unwrap());
+        record_completed_fast(&mut conn, alice, start, stop, None, Some(weekend.id), &clock).unwrap();
+        let fast = get_user_fasting_events(&mut conn, alice).unwrap()[0].id;
+
+        delete_protocol(&mut conn, alice, weekend.id).unwrap();
+        assert_eq!(find_user_fasting_event(&mut conn, alice, fast).unwrap().protocol_id, None);
+    }
+}
@@ -0,0 +1,316 @@
+use crate::clock::Clock;
+use crate::errors::FastingAppError;
+use crate::handlers::goals::find_user_goal;
+use crate::handlers::timezone::{get_user_timezone, resolve_local_time};
+use crate::models::{FastingEvent, FastingSchedule, NewFastingSchedule};
+use crate::schema::fasting_schedules::dsl::{fasting_schedules, id, start_time, user_id, weekday};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday};
+use diesel::prelude::*;
+use diesel::SqliteConnection;
+
+/// How far an actual fast may drift from the plan and still count as on time.
+pub const DEFAULT_SCHEDULE_TOLERANCE_MINUTES: i64 = 30;
+
+/// A concrete fast expanded from a weekly schedule window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedFast {
+    pub schedule_id: i32,
+    pub goal_id: Option<i32>,
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+}
+
+/// How a planned fast compares with what actually happened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlannedFastStatus {
+    OnTime,
+    LateStart,
+    EarlyBreak,
+    Missed,
+}
+
+/// A planned fast paired with the fasting event that best matches it.
+#[derive(Debug)]
+pub struct PlannedFastOutcome {
+    pub planned: PlannedFast,
+    pub status: PlannedFastStatus,
+    pub event_id: Option<i32>,
+    /// Positive when the fast started after the planned start.
+    pub start_delta_minutes: Option<i64>,
+    /// Positive when the fast ended after the planned end.
+    pub end_delta_minutes: Option<i64>,
+}
+
+/// ✅ Adds a weekly fasting window for the user.
+///
+/// - An `end` that is not after `start` falls on the following day.
+pub fn add_schedule_window(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    day: Weekday,
+    window_start: NaiveTime,
+    window_end: NaiveTime,
+    goal_id: Option<i32>,
+) -> Result<(), FastingAppError> {
+    if window_start == window_end {
+        return Err(FastingAppError::InvalidRequest(
+            "A fasting window must have different start and end times.".to_string(),
+        ));
+    }
+
+    if let Some(chosen_goal) = goal_id {
+        find_user_goal(conn, user_id_input, chosen_goal)?;
+    }
+
+    let new_window = NewFastingSchedule {
+        user_id: user_id_input,
+        weekday: day.num_days_from_monday() as i32,
+        start_time: window_start,
+        end_time: window_end,
+        goal_id,
+        created_at: Some(Utc::now().naive_utc()),
+    };
+
+    diesel::insert_into(fasting_schedules)
+        .values(&new_window)
+        .execute(conn)
+        .map(|_| ())
+        .map_err(FastingAppError::DatabaseError)
+}
+
+/// ✅ Lists the user's weekly fasting windows, Monday first.
+pub fn list_schedule_windows(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+) -> Result<Vec<FastingSchedule>, FastingAppError> {
+    fasting_schedules
+        .filter(user_id.eq(user_id_input))
+        .order((weekday.asc(), start_time.asc()))
+        .select(FastingSchedule::as_select())
+        .load::<FastingSchedule>(conn)
+        .map_err(FastingAppError::DatabaseError)
+}
+
+/// ✅ Deletes one of the user's weekly fasting windows.
+pub fn delete_schedule_window(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    schedule_id: i32,
+) -> Result<(), FastingAppError> {
+    let deleted = diesel::delete(
+        fasting_schedules
+            .filter(id.eq(schedule_id))
+            .filter(user_id.eq(user_id_input)),
+    )
+    .execute(conn)
+    .map_err(FastingAppError::DatabaseError)?;
+
+    if deleted == 0 {
+        return Err(FastingAppError::ScheduleNotFound(schedule_id));
+    }
+    Ok(())
+}
+
+/// ✅ Expands weekly windows into concrete planned fasts starting between `from` and `to`.
+///
+/// - Window times are local wall-clock times in `tz`; planned fasts are in UTC.
+/// - Both dates are inclusive; results are ordered by start time.
+pub fn expand_schedule<Tz: TimeZone>(
+    windows: &[FastingSchedule],
+    from: NaiveDate,
+    to: NaiveDate,
+    tz: &Tz,
+) -> Vec<PlannedFast> {
+    let mut planned: Vec<PlannedFast> = from
+        .iter_days()
+        .take_while(|date| *date <= to)
+        .flat_map(|date| {
+            windows
+                .iter()
+                .filter(move |window| {
+                    window.weekday == date.weekday().num_days_from_monday() as i32
+                })
+                .map(move |window| {
+                    let start = resolve_local_time(tz, date.and_time(window.start_time));
+                    let end_date = if window.end_time > window.start_time {
+                        date
+                    } else {
+                        date + Duration::days(1)
+                    };
+                    PlannedFast {
+                        schedule_id: window.id,
+                        goal_id: window.goal_id,
+                        start,
+                        end: resolve_local_time(tz, end_date.and_time(window.end_time)),
+                    }
+                })
+        })
+        .collect();
+
+    planned.sort_by_key(|fast| fast.start);
+    planned
+}
+
+/// ✅ Matches each planned fast with the fasting event that overlaps it most.
+///
+/// - `LateStart` takes precedence over `EarlyBreak` when both apply.
+/// - Ongoing events are treated as ending at `now`.
+pub fn compare_planned_to_actual(
+    planned: &[PlannedFast],
+    events: &[FastingEvent],
+    tolerance: Duration,
+    now: NaiveDateTime,
+) -> Vec<PlannedFastOutcome> {
+    planned
+        .iter()
+        .map(|plan| {
+            let best_match = events
+                .iter()
+                .map(|event| {
+                    let event_end = event.stop_time.unwrap_or(now);
+                    let overlap = event_end.min(plan.end) - event.start_time.max(plan.start);
+                    (event, event_end, overlap)
+                })
+                .filter(|(_, _, overlap)| *overlap > Duration::zero())
+                .max_by_key(|(_, _, overlap)| *overlap);
+
+            match best_match {
+                None => PlannedFastOutcome {
+                    planned: plan.clone(),
+                    status: PlannedFastStatus::Missed,
+                    event_id: None,
+                    start_delta_minutes: None,
+                    end_delta_minutes: None,
+                },
+                Some((event, event_end, _)) => {
+                    let start_delta = event.start_time - plan.start;
+                    let end_delta = event_end - plan.end;
+                    let status = if start_delta > tolerance {
+                        PlannedFastStatus::LateStart
+                    } else if end_delta < -tolerance {
+                        PlannedFastStatus::EarlyBreak
+                    } else {
+                        PlannedFastStatus::OnTime
+                    };
+                    PlannedFastOutcome {
+                        planned: plan.clone(),
+                        status,
+                        event_id: Some(event.id),
+                        start_delta_minutes: Some(start_delta.num_minutes()),
+                        end_delta_minutes: Some(end_delta.num_minutes()),
+                    }
+                }
+            }
+        })
+        .collect()
+}
+
+/// ✅ Compares the user's planned fasts with their actual fasting events.
+///
+/// - `from` and `to` are local dates; only planned fasts that have ended by `clock.now()` are classified.
+pub fn review_schedule(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    from: NaiveDate,
+    to: NaiveDate,
+    clock: &dyn Clock,
+) -> Result<Vec<PlannedFastOutcome>, FastingAppError> {
+    use crate::schema::fasting_events::dsl::{
+        fasting_events, start_time as event_start_time, stop_time as event_stop_time,
+        user_id as event_user_id,
+    };
+
+    let now = clock.now();
+    let windows = list_schedule_windows(conn, user_id_input)?;
+    let tz = get_user_timezone(conn, user_id_input)?;
+    let planned: Vec<PlannedFast> = expand_schedule(&windows, from, to, &tz)
+        .into_iter()
+        .filter(|plan| plan.end <= now)
+        .collect();
+
+    let (Some(first), Some(last_end)) = (
+        planned.first().map(|plan| plan.start),
+        planned.iter().map(|plan| plan.end).max(),
+    ) else {
+        return Ok(Vec::new());
+    };
+
+    let events = fasting_events
+        .filter(event_user_id.eq(user_id_input))
+        .filter(event_start_time.lt(last_end))
+        .filter(event_stop_time.is_null().or(event_stop_time.gt(first)))
+        .select(FastingEvent::as_select())
+        .load::<FastingEvent>(conn)
+        .map_err(FastingAppError::DatabaseError)?;
+
+    Ok(compare_planned_to_actual(
+        &planned,
+        &events,
+        Duration::minutes(DEFAULT_SCHEDULE_TOLERANCE_MINUTES),
+        now,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 3, day).unwrap() // 2025-03-17 is a Monday
+    }
+
+    fn time(hour: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, 0, 0).unwrap()
+    }
+
+    fn overnight_window() -> FastingSchedule {
+        FastingSchedule {
+            id: 1,
+            user_id: 1,
+            weekday: 0,
+            start_time: time(20),
+            end_time: time(12),
+            goal_id: None,
+            created_at: None,
+        }
+    }
+
+    fn event(start: NaiveDateTime, stop: NaiveDateTime) -> FastingEvent {
+        FastingEvent {
+            id: 7,
+            user_id: 1,
+            start_time: start,
+            stop_time: Some(stop),
+            created_at: None,
+            goal_id: None,
+            protocol_id: None,
+        }
+    }
+
+    #[test]
+    fn test_expand_schedule_wraps_past_midnight() {
+        let planned = expand_schedule(&[overnight_window()], date(17), date(30), &Utc);
+        assert_eq!(planned.len(), 2);
+        assert_eq!(planned[0].start, date(17).and_time(time(20)));
+        assert_eq!(planned[0].end, date(18).and_time(time(12)));
+    }
+
+    #[test]
+    fn test_compare_planned_to_actual_classifies_outcomes() {
+        let plan = expand_schedule(&[overnight_window()], date(17), date(17), &Utc);
+        let tolerance = Duration::minutes(30);
+        let now = date(31).and_time(time(0));
+        let classify = |events: &[FastingEvent]| {
+            compare_planned_to_actual(&plan, events, tolerance, now)[0].status
+        };
+
+        let on_time = event(date(17).and_time(time(20)), date(18).and_time(time(12)));
+        let late = event(date(17).and_time(time(22)), date(18).and_time(time(12)));
+        let early = event(date(17).and_time(time(20)), date(18).and_time(time(9)));
+
+        assert_eq!(classify(&[on_time]), PlannedFastStatus::OnTime);
+        assert_eq!(classify(&[late]), PlannedFastStatus::LateStart);
+        assert_eq!(classify(&[early]), PlannedFastStatus::EarlyBreak);
+        assert_eq!(classify(&[]), PlannedFastStatus::Missed);
+    }
+}
@@ -0,0 +1,167 @@
+use crate::clock::Clock;
+use crate::errors::FastingAppError;
+use crate::handlers::fasting::{get_current_fasting_status, FastingStatus};
+use diesel::SqliteConnection;
+
+/// A metabolic stage reached after fasting for `starts_at_hours`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetabolicStage {
+    pub name: String,
+    pub starts_at_hours: u32,
+    pub description: String,
+}
+
+impl MetabolicStage {
+    pub fn new(name: &str, starts_at_hours: u32, description: &str) -> Self {
+        MetabolicStage {
+            name: name.to_string(),
+            starts_at_hours,
+            description: description.to_string(),
+        }
+    }
+
+    fn starts_at_minutes(&self) -> i64 {
+        i64::from(self.starts_at_hours) * 60
+    }
+}
+
+/// An ordered set of metabolic stages, starting at hour 0.
+#[derive(Debug, Clone)]
+pub struct StageModel {
+    stages: Vec<MetabolicStage>,
+}
+
+impl StageModel {
+    /// ✅ Builds a custom stage model.
+    ///
+    /// - Stages are sorted by threshold; the first must start at hour 0.
+    /// - Two stages cannot share a threshold.
+    pub fn new(mut stages: Vec<MetabolicStage>) -> Result<Self, FastingAppError> {
+        stages.sort_by_key(|stage| stage.starts_at_hours);
+
+        if stages.first().map(|stage| stage.starts_at_hours) != Some(0) {
+            return Err(FastingAppError::InvalidRequest(
+                "The first metabolic stage must start at hour 0.".to_string(),
+            ));
+        }
+        if stages
+            .windows(2)
+            .any(|pair| pair[0].starts_at_hours == pair[1].starts_at_hours)
+        {
+            return Err(FastingAppError::InvalidRequest(
+                "Metabolic stages must have distinct hour thresholds.".to_string(),
+            ));
+        }
+
+        Ok(StageModel { stages })
+    }
+
+    pub fn stages(&self) -> &[MetabolicStage] {
+        &self.stages
+    }
+
+    /// ✅ Returns the stage reached after `elapsed_minutes` and the stage after it, if any.
+    pub fn stage_at(&self, elapsed_minutes: i64) -> (&MetabolicStage, Option<&MetabolicStage>) {
+        let reached = self
+            .stages
+            .iter()
+            .rposition(|stage| stage.starts_at_minutes() <= elapsed_minutes)
+            .unwrap_or(0);
+        (&self.stages[reached], self.stages.get(reached + 1))
+    }
+}
+
+impl Default for StageModel {
+    /// Commonly cited stages; thresholds vary between people and are approximate.
+    fn default() -> Self {
+        StageModel {
+            stages: vec![
+                MetabolicStage::new(
+                    "Fed state",
+                    0,
+                    "Your body is digesting and absorbing your last meal; insulin is elevated.",
+                ),
+                MetabolicStage::new(
+                    "Early fasting",
+                    4,
+                    "Blood sugar and insulin fall and the body starts drawing on stored glycogen.",
+                ),
+                MetabolicStage::new(
+                    "Fat burning",
+                    12,
+                    "Glycogen stores run low and the body shifts toward burning fat for fuel.",
+                ),
+                MetabolicStage::new(
+                    "Ketosis",
+                    18,
+                    "The liver produces ketones from fat, providing an alternative fuel for the brain.",
+                ),
+                MetabolicStage::new(
+                    "Autophagy",
+                    24,
+                    "Cells ramp up autophagy, breaking down and recycling damaged components.",
+                ),
+            ],
+        }
+    }
+}
+
+/// The ongoing fast together with the metabolic stage it has reached.
+#[derive(Debug)]
+pub struct StageStatus {
+    pub fasting: FastingStatus,
+    pub current_stage: MetabolicStage,
+    pub next_stage: Option<MetabolicStage>,
+    /// Fasting minutes left until `next_stage` begins.
+    pub minutes_to_next_stage: Option<i64>,
+}
+
+/// ✅ Reports the metabolic stage of the user's ongoing fast.
+///
+/// - Uses effective fasting minutes, so paused time does not advance the stage.
+/// - Returns `None` when the user has no ongoing fast.
+pub fn get_fasting_stage_status(
+    conn: &mut SqliteConnection,
+    user_id: i32,
+    model: &StageModel,
+    clock: &dyn Clock,
+) -> Result<Option<StageStatus>, FastingAppError> {
+    let Some(fasting) = get_current_fasting_status(conn, user_id, clock)? else {
+        return Ok(None);
+    };
+
+    let (current_stage, next_stage) = model.stage_at(fasting.elapsed_minutes);
+    let minutes_to_next_stage =
+        next_stage.map(|stage| stage.starts_at_minutes() - fasting.elapsed_minutes);
+
+    Ok(Some(StageStatus {
+        current_stage: current_stage.clone(),
+        next_stage: next_stage.cloned(),
+        minutes_to_next_stage,
+        fasting,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stage_at_uses_hour_thresholds() {
+        let model = StageModel::default();
+
+        let (stage, next) = model.stage_at(13 * 60);
+        assert_eq!(stage.name, "Fat burning");
+        assert_eq!(next.map(|s| s.name.as_str()), Some("Ketosis"));
+
+        let (stage, next) = model.stage_at(30 * 60);
+        assert_eq!(stage.name, "Autophagy");
+        assert!(next.is_none());
+    }
+
+    #[test]
+    fn test_custom_model_requires_stage_at_hour_zero() {
+        let result = StageModel::new(vec![MetabolicStage::new("Late", 6, "")]);
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,130 @@
+use crate::clock::Clock;
+use crate::errors::FastingAppError;
+use chrono::{DateTime, Duration, LocalResult, NaiveDate, NaiveDateTime, TimeZone};
+use diesel::prelude::*;
+use diesel::SqliteConnection;
+use std::fmt::Display;
+
+/// Timezone of users who have not picked one.
+pub const DEFAULT_TIMEZONE: chrono_tz::Tz = chrono_tz::Tz::UTC;
+
+/// ✅ Parses an IANA timezone name such as `Europe/Berlin`.
+pub fn parse_timezone(timezone_name: &str) -> Result<chrono_tz::Tz, FastingAppError> {
+    timezone_name
+        .trim()
+        .parse::<chrono_tz::Tz>()
+        .map_err(|_| FastingAppError::InvalidTimezone(timezone_name.trim().to_string()))
+}
+
+/// ✅ Returns the user's timezone.
+///
+/// - A stored name that is no longer recognised falls back to UTC.
+pub fn get_user_timezone(conn: &mut SqliteConnection, user_id_input: i32) -> Result<chrono_tz::Tz, FastingAppError> {
+    use crate::schema::users::dsl::{id as users_id, timezone, users};
+
+    let timezone_name = users
+        .filter(users_id.eq(user_id_input))
+        .select(timezone)
+        .first::<String>(conn)
+        .map_err(FastingAppError::DatabaseError)?;
+
+    Ok(parse_timezone(&timezone_name).unwrap_or(DEFAULT_TIMEZONE))
+}
+
+/// ✅ Stores the user's timezone after checking that the name is valid.
+pub fn set_user_timezone(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    timezone_name: &str,
+) -> Result<chrono_tz::Tz, FastingAppError> {
+    use crate::schema::users::dsl::{id as users_id, timezone, users};
+
+    let tz = parse_timezone(timezone_name)?;
+    diesel::update(users.filter(users_id.eq(user_id_input)))
+        .set(timezone.eq(tz.name()))
+        .execute(conn)
+        .map_err(FastingAppError::DatabaseError)?;
+    Ok(tz)
+}
+
+/// ✅ Converts a stored UTC timestamp to local time.
+pub fn to_local<Tz: TimeZone>(tz: &Tz, utc: NaiveDateTime) -> DateTime<Tz> {
+    tz.from_utc_datetime(&utc)
+}
+
+/// ✅ Formats a stored UTC timestamp as local time, e.g. `2025-03-30 08:15 CEST`.
+pub fn format_local<Tz: TimeZone>(tz: &Tz, utc: NaiveDateTime) -> String
+where
+    Tz::Offset: Display,
+{
+    to_local(tz, utc).format("%Y-%m-%d %H:%M %Z").to_string()
+}
+
+/// ✅ Converts a local wall-clock time entered by the user to UTC for storage.
+///
+/// - Times repeated when clocks go back resolve to the first occurrence.
+/// - Times skipped when clocks go forward are rejected with `NonexistentLocalTime`.
+pub fn local_to_utc(tz: &chrono_tz::Tz, local: NaiveDateTime) -> Result<NaiveDateTime, FastingAppError> {
+    match tz.from_local_datetime(&local) {
+        LocalResult::Single(at) | LocalResult::Ambiguous(at, _) => Ok(at.naive_utc()),
+        LocalResult::None => Err(FastingAppError::NonexistentLocalTime(local, tz.name().to_string())),
+    }
+}
+
+/// ✅ The user's local date at `clock.now()`.
+pub fn local_today<Tz: TimeZone>(tz: &Tz, clock: &dyn Clock) -> NaiveDate {
+    to_local(tz, clock.now()).date_naive()
+}
+
+/// ✅ The UTC instant of a computed local time, such as a schedule window or midnight.
+///
+/// - Where a clock change skips the time, the first local time after the gap is used.
+/// - Where the time occurs twice, the earlier instant is used.
+pub fn resolve_local_time<Tz: TimeZone>(tz: &Tz, local: NaiveDateTime) -> NaiveDateTime {
+    (0..=24)
+        .map(|step| local + Duration::minutes(step * 5))
+        .find_map(|candidate| tz.from_local_datetime(&candidate).earliest())
+        .map_or(local, |resolved| resolved.naive_utc())
+}
+
+/// ✅ The UTC instant at which `date` begins in `tz`.
+pub fn local_midnight_utc<Tz: TimeZone>(tz: &Tz, date: NaiveDate) -> NaiveDateTime {
+    resolve_local_time(tz, date.and_hms_opt(0, 0, 0).expect("midnight is a valid time"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::America::{New_York, Santiago};
+
+    #[test]
+    fn test_local_days_follow_dst() {
+        // New York springs forward on 2025-03-09: that local day lasts 23 hours
+        let day = NaiveDate::from_ymd_opt(2025, 3, 9).unwrap();
+        let start = local_midnight_utc(&New_York, day);
+        let end = local_midnight_utc(&New_York, day.succ_opt().unwrap());
+        assert_eq!(start, NaiveDate::from_ymd_opt(2025, 3, 9).unwrap().and_hms_opt(5, 0, 0).unwrap());
+        assert_eq!(end - start, Duration::hours(23));
+    }
+
+    #[test]
+    fn test_midnight_inside_a_gap_starts_after_it() {
+        // Santiago skips from 00:00 to 01:00 on 2024-09-08
+        let day = NaiveDate::from_ymd_opt(2024, 9, 8).unwrap();
+        let start = to_local(&Santiago, local_midnight_utc(&Santiago, day));
+        assert_eq!(start.naive_local(), day.and_hms_opt(1, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn test_local_input_in_gap_is_rejected() {
+        let skipped = NaiveDate::from_ymd_opt(2025, 3, 9).unwrap().and_hms_opt(2, 30, 0).unwrap();
+        assert!(matches!(
+            local_to_utc(&New_York, skipped),
+            Err(FastingAppError::NonexistentLocalTime(..))
+        ));
+
+        // 01:30 happens twice on 2025-11-02; the first one is still daylight time
+        let repeated = NaiveDate::from_ymd_opt(2025, 11, 2).unwrap().and_hms_opt(1, 30, 0).unwrap();
+        assert_eq!(local_to_utc(&New_York, repeated).unwrap(), repeated + Duration::hours(4));
+    }
+}
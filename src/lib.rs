@@ -1,33 +1,92 @@
+pub mod api;
+pub mod auth;
+pub mod clock;
 pub mod db;
 pub mod errors;
+pub mod export;
 pub mod models;
 pub mod schema;
 
 // ✅ submodules need decaration when mod is not present
 pub mod handlers {
     pub mod analytics;
+    pub mod body_metrics;
+    pub mod charts;
     pub mod fasting;
     pub mod goals;
+    pub mod hydration;
+    pub mod journal;
+    pub mod meals;
     pub mod menu;
+    pub mod programs;
+    pub mod protocols;
+    pub mod schedule;
+    pub mod stages;
+    pub mod timezone;
 }
 
 pub mod users {
+    pub mod devices;
     pub mod find;
+    pub mod lockout;
     pub mod login;
+    pub mod password;
     pub mod update;
     pub mod create;
 }
 
 // ✅ Publicly re-export functions so they are accessible from `lib.rs`
+pub use api::{build_rocket, ApiState};
+pub use auth::{
+    authenticate, issue_tokens, list_active_sessions, refresh_tokens, revoke_all_sessions, revoke_session, sign_in,
+    sign_in_local, sign_in_local_with_device, sign_in_with_device, verify_access_token, AuthConfig, SessionInfo,
+    TokenPair,
+};
+pub use clock::{Clock, FakeClock, SystemClock};
+pub use db::{establish_pool, DbPool};
 pub use handlers::analytics::{
     calculate_average_fasting_duration, calculate_total_fasting_time, show_fasting_history,
+    calculate_fasting_time_between, calculate_weight_change, journal_scores_by_fasting_hour,
+    summarize_fasts_by_protocol, find_large_refeeds, summarize_break_fast_patterns, calculate_streaks,
+    StreakReport, aggregate_fasting_by_period, BucketSize, PeriodStats,
+};
+pub use handlers::body_metrics::{
+    add_measurement, delete_measurement, list_measurements, set_unit_preferences,
+    update_measurement,
+};
+pub use handlers::fasting::{
+    delete_fasting_event, edit_fasting_event, get_current_fasting_status, pause_fasting,
+    record_completed_fast, repair_fasting_events, resume_fasting, start_fasting, stop_fasting,
+};
+pub use handlers::goals::{
+    create_goal, create_recurring_goal, delete_goal, delete_recurring_goal, evaluate_goals, find_user_goal,
+    generate_recurring_goals, list_active_goals, list_goals, list_recurring_goals, recurring_periods,
+    set_goal_archived, update_goal, validate_goal_link, view_goals, GoalStatus, RecurrencePeriod,
 };
-pub use handlers::fasting::{get_current_fasting_status, start_fasting, stop_fasting};
-pub use handlers::goals::{add_goal, view_goals};
+pub use handlers::hydration::{
+    current_fast_hydration, hydration_totals_by_event, log_intake, set_daily_hydration_target,
+};
+pub use handlers::journal::{add_journal_entry, list_journal_entries};
+pub use handlers::meals::{get_break_fast_meal, list_break_fast_meals, log_break_fast_meal};
+pub use handlers::programs::{
+    delete_program, drop_out_of_program, enroll_in_program, generate_program_goals, get_program_days,
+    get_program_progress, import_program, list_enrollments, list_programs, ProgramDayOutcome, ProgramProgress,
+};
+pub use handlers::protocols::{create_protocol, delete_protocol, list_protocols};
+pub use handlers::schedule::{add_schedule_window, expand_schedule, review_schedule};
+pub use handlers::stages::{get_fasting_stage_status, StageModel};
+pub use handlers::timezone::{format_local, get_user_timezone, local_to_utc, parse_timezone, set_user_timezone};
 pub use handlers::menu::display_main_menu;
 
+pub use users::create::create_user;
+pub use users::devices::{
+    create_pairing_code, list_devices, login_with_device, redeem_pairing_code, revoke_all_devices, revoke_device,
+    DeviceCredential, DeviceInfo, PairingCode,
+};
 pub use users::find::find_user_by_id;
-pub use users::login::{associate_device_id, login_user};
+pub use users::lockout::{list_lockout_audit, unlock_login, LockoutPolicy, LockoutScope};
+pub use users::login::login_user;
+pub use users::password::{change_password, PasswordPolicy, PasswordViolation};
 pub use users::update::update_user_profile;
 
 pub use crate::errors::FastingAppError::*;
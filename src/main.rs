@@ -3,27 +3,75 @@ extern crate diesel;
 extern crate dotenv;
 
 use dotenv::dotenv;
+use structopt::StructOpt;
 //use log;
 
+use crate::clock::SystemClock;
 use crate::db::establish_connection;
+use handlers::fasting::repair_fasting_events;
 use handlers::menu::display_main_menu;
-mod db;
+use users::lockout::{list_lockout_audit, unlock_login, LockoutScope};
+pub mod auth;
+pub mod clock;
+pub mod db;
 mod errors;
+mod export;
 mod models;
 mod schema;
 pub mod handlers {
     pub mod analytics;
+    pub mod body_metrics;
+    pub mod charts;
     pub mod fasting;
     pub mod goals;
+    pub mod hydration;
+    pub mod journal;
+    pub mod meals;
     pub mod menu;
+    pub mod programs;
+    pub mod protocols;
+    pub mod schedule;
+    pub mod stages;
+    pub mod timezone;
 }
 pub mod users {
     pub mod create;
+    pub mod devices;
     pub mod find;
+    pub mod lockout;
     pub mod login;
+    pub mod password;
     pub mod update;
 }
 
+/// Command-line options. Without a subcommand the interactive menu is shown.
+#[derive(StructOpt)]
+#[structopt(name = "fasting-rust", about = "A fasting tracker.")]
+struct Cli {
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(StructOpt)]
+enum Command {
+    /// Closes overlapping open fasts and removes duplicate fasting events.
+    RepairEvents,
+    /// Lifts a sign-in lockout and resets its failed attempt count.
+    Unlock {
+        /// The locked username, or the client's IP address with --device.
+        subject: String,
+        /// Unlock a client IP address instead of a username.
+        #[structopt(long)]
+        device: bool,
+    },
+    /// Shows recent sign-in lockouts and unlocks.
+    LockoutLog {
+        /// How many entries to show.
+        #[structopt(long, default_value = "20")]
+        limit: i64,
+    },
+}
+
 fn main() {
     dotenv().ok();
     env_logger::init();
@@ -36,5 +84,40 @@ fn main() {
         }
     };
 
-    display_main_menu(&mut conn);
+    match Cli::from_args().command {
+        Some(Command::RepairEvents) => match repair_fasting_events(&mut conn) {
+            Ok(report) => println!(
+                "✅ Repair complete: {} duplicate(s) removed, {} event(s) closed.",
+                report.duplicates_removed, report.events_closed
+            ),
+            Err(e) => log::error!("Failed to repair fasting events: {:?}", e),
+        },
+        Some(Command::Unlock { subject, device }) => {
+            let scope = if device { LockoutScope::Device } else { LockoutScope::Username };
+            let actor = std::env::var("USER").unwrap_or_else(|_| "admin".to_string());
+            match unlock_login(&mut conn, scope, &subject, &actor, &SystemClock) {
+                Ok(true) => println!("✅ Unlocked {} '{}'.", scope.as_str(), subject),
+                Ok(false) => println!("ℹ️ {} '{}' had no failed sign-ins to clear.", scope.as_str(), subject),
+                Err(e) => log::error!("Failed to unlock {} '{}': {:?}", scope.as_str(), subject, e),
+            }
+        }
+        Some(Command::LockoutLog { limit }) => match list_lockout_audit(&mut conn, limit) {
+            Ok(entries) => {
+                for entry in entries {
+                    println!(
+                        "{} | {} {} '{}' | {} failed attempt(s) | until {} | by {}",
+                        entry.created_at.format("%Y-%m-%d %H:%M:%S"),
+                        entry.event,
+                        entry.scope,
+                        entry.subject,
+                        entry.failed_attempts,
+                        entry.locked_until.map_or_else(|| "-".to_string(), |at| at.format("%Y-%m-%d %H:%M:%S").to_string()),
+                        entry.actor.as_deref().unwrap_or("system")
+                    );
+                }
+            }
+            Err(e) => log::error!("Failed to read the lockout log: {:?}", e),
+        },
+        None => display_main_menu(&mut conn, &SystemClock),
+    }
 }
@@ -1,6 +1,10 @@
-use crate::schema::{fasting_events, fasting_goals, fasting_sessions, users};
-use chrono::NaiveDateTime;
+use crate::schema::{
+    auth_tokens, body_measurements, break_fast_meals, device_pairing_codes, fast_journal_entries, fasting_events, fasting_goals, fasting_pauses, fasting_protocols,
+    fasting_programs, fasting_schedules, fluid_intakes, lockout_audit_log, login_failures, program_days, program_enrollments, recurring_goals, user_devices, users,
+};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
 use diesel::{AsChangeset, Identifiable, Insertable, Queryable, Selectable};
+use serde::Serialize;
 //use diesel::prelude::*;
 
 /// Represents a user in the database.
@@ -11,9 +15,12 @@ pub struct User {
     pub id: i32,                           // Non-nullable Integer
     pub username: String,                  // Text
     pub hashed_password: String,           // Text
-    pub device_id: Option<String>,         // Nullable<Text>
     pub created_at: Option<NaiveDateTime>, // Nullable<Timestamp>    
     pub last_active: Option<NaiveDateTime>, // ✅ Ensure this exists
+    pub weight_unit: String,                // "kg" or "lb"
+    pub length_unit: String,                // "cm" or "in"
+    pub daily_hydration_target_ml: Option<i32>,
+    pub timezone: String,                   // IANA name, e.g. "Europe/Berlin"
 }
 
 /// Represents a new user to be inserted into the database.
@@ -22,11 +29,10 @@ pub struct User {
 pub struct NewUser {
     pub username: String,
     pub hashed_password: String,
-    pub device_id: Option<String>,
 }
 
 /// Represents a fasting event in the database.
-#[derive(Queryable, Insertable, Identifiable, Debug, Selectable)]
+#[derive(Queryable, Insertable, Identifiable, Debug, Selectable, Serialize)]
 #[diesel(table_name = fasting_events)]
 #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
 pub struct FastingEvent {
@@ -36,6 +42,7 @@ pub struct FastingEvent {
     pub stop_time: Option<NaiveDateTime>,
     pub created_at: Option<NaiveDateTime>,
     pub goal_id: Option<i32>, // ✅ Ensure goal_id is also included
+    pub protocol_id: Option<i32>,
 }
 
 /// Represents a new fasting event to be inserted into the database.
@@ -47,9 +54,32 @@ pub struct NewFastingEvent {
     pub stop_time: Option<NaiveDateTime>,
     pub created_at: Option<NaiveDateTime>,
     pub goal_id: Option<i32>, 
+    pub protocol_id: Option<i32>,
 }
+/// Represents a pause within a fasting event (e.g. medication taken with food).
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone)]
+#[diesel(table_name = fasting_pauses)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct FastingPause {
+    pub id: i32,
+    pub event_id: i32,
+    pub pause_time: NaiveDateTime,
+    pub resume_time: Option<NaiveDateTime>, // None while the fast is paused
+    pub created_at: Option<NaiveDateTime>,
+}
+
+/// Represents a new pause to be inserted into the database.
+#[derive(Insertable, Debug)]
+#[diesel(table_name = fasting_pauses)]
+pub struct NewFastingPause {
+    pub event_id: i32,
+    pub pause_time: NaiveDateTime,
+    pub resume_time: Option<NaiveDateTime>,
+    pub created_at: Option<NaiveDateTime>,
+}
+
 /// Represents a fasting goal in the database.
-#[derive(Queryable, Selectable, Identifiable, Insertable, Debug)]
+#[derive(Queryable, Selectable, Identifiable, Insertable, Debug, Serialize)]
 #[diesel(table_name = fasting_goals)]
 #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
 pub struct FastingGoal {
@@ -58,6 +88,12 @@ pub struct FastingGoal {
     pub goal_duration: i32,
     pub deadline: NaiveDateTime,
     pub created_at: Option<NaiveDateTime>,
+    pub status: String,                     // "pending", "in_progress", "achieved" or "missed"
+    pub achieved_at: Option<NaiveDateTime>,
+    pub archived_at: Option<NaiveDateTime>, // Hidden from the goal list when set
+    pub recurring_goal_id: Option<i32>,     // Set on goals generated from a recurring goal
+    pub period_start: Option<NaiveDate>,
+    pub enrollment_id: Option<i32>,         // Set on goals generated from a program enrollment
 }
 
 #[derive(Insertable)]
@@ -67,15 +103,373 @@ pub struct NewFastingGoal {
     pub goal_duration: i32,
     pub deadline: NaiveDateTime,
     pub created_at: Option<NaiveDateTime>,
+    pub recurring_goal_id: Option<i32>,
+    pub period_start: Option<NaiveDate>,
+    pub enrollment_id: Option<i32>,
 }
 
-/// Represents a fasting session.
-#[derive(Queryable, Identifiable, Debug, Selectable)]
-#[diesel(table_name = fasting_sessions)]
-pub struct FastingSession {
+/// Represents a goal that repeats every day (on chosen weekdays) or every week.
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone)]
+#[diesel(table_name = recurring_goals)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct RecurringGoal {
     pub id: i32,
     pub user_id: i32,
-    pub start_time: NaiveDateTime,
-    pub stop_time: Option<NaiveDateTime>,
+    pub goal_duration: i32, // Hours
+    pub period: String,     // "daily" or "weekly"
+    pub weekdays: i32,      // Bit 0 = Monday; only used by daily goals
+    pub starts_on: NaiveDate,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+/// Represents a new recurring goal to be inserted into the database.
+#[derive(Insertable, Debug)]
+#[diesel(table_name = recurring_goals)]
+pub struct NewRecurringGoal {
+    pub user_id: i32,
+    pub goal_duration: i32,
+    pub period: String,
+    pub weekdays: i32,
+    pub starts_on: NaiveDate,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+/// Represents a multi-day fasting program, e.g. a 30-day 16:8 starter.
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone)]
+#[diesel(table_name = fasting_programs)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct FastingProgram {
+    pub id: i32,
+    pub user_id: Option<i32>, // None for built-in programs
+    pub slug: Option<String>, // Set on built-in programs
+    pub name: String,
+    pub description: Option<String>,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+/// Represents a new fasting program to be inserted into the database.
+#[derive(Insertable, Debug)]
+#[diesel(table_name = fasting_programs)]
+pub struct NewFastingProgram {
+    pub user_id: Option<i32>,
+    pub slug: Option<String>,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+/// Represents the fasting target for one day of a program.
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone)]
+#[diesel(table_name = program_days)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct ProgramDay {
+    pub id: i32,
+    pub program_id: i32,
+    pub day_number: i32,   // 1-based
+    pub target_hours: i32, // 0 marks a rest day
+}
+
+/// Represents a new program day to be inserted into the database.
+#[derive(Insertable, Debug)]
+#[diesel(table_name = program_days)]
+pub struct NewProgramDay {
+    pub program_id: i32,
+    pub day_number: i32,
+    pub target_hours: i32,
+}
+
+/// Represents a user following a program from a start date.
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone)]
+#[diesel(table_name = program_enrollments)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct ProgramEnrollment {
+    pub id: i32,
+    pub user_id: i32,
+    pub program_id: i32,
+    pub start_date: NaiveDate,
+    pub status: String, // "active", "completed" or "dropped"
+    pub ended_at: Option<NaiveDateTime>,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+/// Represents a new program enrollment to be inserted into the database.
+#[derive(Insertable, Debug)]
+#[diesel(table_name = program_enrollments)]
+pub struct NewProgramEnrollment {
+    pub user_id: i32,
+    pub program_id: i32,
+    pub start_date: NaiveDate,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+/// Represents a named fasting protocol such as 16:8 or OMAD.
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone)]
+#[diesel(table_name = fasting_protocols)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct FastingProtocol {
+    pub id: i32,
+    pub user_id: Option<i32>, // None for built-in protocols
+    pub name: String,
+    pub fasting_hours: i32,
+    pub eating_hours: i32,
+    pub description: Option<String>,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+/// Represents a new user-defined protocol to be inserted into the database.
+#[derive(Insertable, Debug)]
+#[diesel(table_name = fasting_protocols)]
+pub struct NewFastingProtocol {
+    pub user_id: Option<i32>,
+    pub name: String,
+    pub fasting_hours: i32,
+    pub eating_hours: i32,
+    pub description: Option<String>,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+/// Represents a recurring weekly fasting window, e.g. Monday 20:00 to 12:00.
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone)]
+#[diesel(table_name = fasting_schedules)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct FastingSchedule {
+    pub id: i32,
+    pub user_id: i32,
+    pub weekday: i32,        // 0 = Monday ... 6 = Sunday
+    pub start_time: NaiveTime,
+    pub end_time: NaiveTime, // On the next day when not after start_time
+    pub goal_id: Option<i32>,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+/// Represents a new weekly fasting window to be inserted into the database.
+#[derive(Insertable, Debug)]
+#[diesel(table_name = fasting_schedules)]
+pub struct NewFastingSchedule {
+    pub user_id: i32,
+    pub weekday: i32,
+    pub start_time: NaiveTime,
+    pub end_time: NaiveTime,
+    pub goal_id: Option<i32>,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+/// Represents a journal entry logged during a fast.
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone)]
+#[diesel(table_name = fast_journal_entries)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct FastJournalEntry {
+    pub id: i32,
+    pub event_id: i32,
+    pub entry_time: NaiveDateTime,
+    pub note: Option<String>,
+    pub mood: Option<i32>,     // 1-10
+    pub hunger: Option<i32>,   // 1-10
+    pub energy: Option<i32>,   // 1-10
+    pub symptoms: Option<String>, // Comma-separated tags
+    pub created_at: Option<NaiveDateTime>,
+}
+
+/// Represents a new journal entry to be inserted into the database.
+#[derive(Insertable, Debug)]
+#[diesel(table_name = fast_journal_entries)]
+pub struct NewFastJournalEntry {
+    pub event_id: i32,
+    pub entry_time: NaiveDateTime,
+    pub note: Option<String>,
+    pub mood: Option<i32>,
+    pub hunger: Option<i32>,
+    pub energy: Option<i32>,
+    pub symptoms: Option<String>,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+/// Represents the meal a fast was broken with.
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone)]
+#[diesel(table_name = break_fast_meals)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct BreakFastMeal {
+    pub id: i32,
+    pub event_id: i32,
+    pub description: String,
+    pub calories_min: Option<i32>, // Approximate calorie range
+    pub calories_max: Option<i32>,
+    pub protein_g: Option<f64>,
+    pub carbs_g: Option<f64>,
+    pub fat_g: Option<f64>,
+    pub eaten_at: NaiveDateTime,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+/// Represents a new break-fast meal to be inserted into the database.
+#[derive(Insertable, Debug)]
+#[diesel(table_name = break_fast_meals)]
+pub struct NewBreakFastMeal {
+    pub event_id: i32,
+    pub description: String,
+    pub calories_min: Option<i32>,
+    pub calories_max: Option<i32>,
+    pub protein_g: Option<f64>,
+    pub carbs_g: Option<f64>,
+    pub fat_g: Option<f64>,
+    pub eaten_at: NaiveDateTime,
     pub created_at: Option<NaiveDateTime>,
 }
+
+/// Represents a body measurement such as weight, body fat or waist size.
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone)]
+#[diesel(table_name = body_measurements)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct BodyMeasurement {
+    pub id: i32,
+    pub user_id: i32,
+    pub kind: String,  // "weight", "body_fat" or "waist"
+    pub value: f64,
+    pub unit: String,  // Unit the value was recorded in
+    pub measured_at: NaiveDateTime,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+/// Represents a new body measurement to be inserted into the database.
+#[derive(Insertable, Debug)]
+#[diesel(table_name = body_measurements)]
+pub struct NewBodyMeasurement {
+    pub user_id: i32,
+    pub kind: String,
+    pub value: f64,
+    pub unit: String,
+    pub measured_at: NaiveDateTime,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+/// Represents a drink logged as water, electrolytes, black coffee or tea.
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone)]
+#[diesel(table_name = fluid_intakes)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct FluidIntake {
+    pub id: i32,
+    pub user_id: i32,
+    pub event_id: Option<i32>, // Fast that was active when logged
+    pub kind: String,
+    pub amount_ml: i32,
+    pub consumed_at: NaiveDateTime,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+/// Represents a new fluid intake to be inserted into the database.
+#[derive(Insertable, Debug)]
+#[diesel(table_name = fluid_intakes)]
+pub struct NewFluidIntake {
+    pub user_id: i32,
+    pub event_id: Option<i32>,
+    pub kind: String,
+    pub amount_ml: i32,
+    pub consumed_at: NaiveDateTime,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+/// A signed-in session, identified by its current refresh token.
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone)]
+#[diesel(table_name = auth_tokens)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct AuthToken {
+    pub id: i32,
+    pub user_id: i32,
+    pub token_hash: String,                  // SHA-256 of the refresh token, never the token itself
+    pub previous_token_hash: Option<String>, // The token replaced by the last rotation
+    pub device_name: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub last_used_at: NaiveDateTime,
+    pub expires_at: NaiveDateTime,
+    pub revoked_at: Option<NaiveDateTime>,
+    pub device_id: Option<i32>,              // Set when the session was started with a device credential
+}
+
+/// Represents a new session to be inserted into the database.
+#[derive(Insertable, Debug)]
+#[diesel(table_name = auth_tokens)]
+pub struct NewAuthToken {
+    pub user_id: i32,
+    pub token_hash: String,
+    pub device_name: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub last_used_at: NaiveDateTime,
+    pub expires_at: NaiveDateTime,
+    pub device_id: Option<i32>,
+}
+
+/// A device paired with a user's account.
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone)]
+#[diesel(table_name = user_devices)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct UserDevice {
+    pub id: i32,
+    pub user_id: i32,
+    pub name: String,
+    pub credential_hash: String, // SHA-256 of the device credential, never the credential itself
+    pub paired_at: NaiveDateTime,
+    pub last_seen_at: Option<NaiveDateTime>,
+    pub revoked_at: Option<NaiveDateTime>,
+}
+
+/// Represents a new paired device to be inserted into the database.
+#[derive(Insertable, Debug)]
+#[diesel(table_name = user_devices)]
+pub struct NewUserDevice {
+    pub user_id: i32,
+    pub name: String,
+    pub credential_hash: String,
+    pub paired_at: NaiveDateTime,
+}
+
+/// Represents a new pairing code to be inserted into the database.
+#[derive(Insertable, Debug)]
+#[diesel(table_name = device_pairing_codes)]
+pub struct NewDevicePairingCode {
+    pub user_id: i32,
+    pub code_hash: String,
+    pub created_at: NaiveDateTime,
+    pub expires_at: NaiveDateTime,
+}
+
+/// Failed sign-in attempts for one username or device since its last successful sign-in.
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone)]
+#[diesel(table_name = login_failures)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct LoginFailure {
+    pub id: i32,
+    pub scope: String,   // "username" or "device"
+    pub subject: String, // The username or the client's IP address
+    pub failed_attempts: i32,
+    pub lockouts: i32,
+    pub last_failed_at: NaiveDateTime,
+    pub locked_until: Option<NaiveDateTime>,
+}
+
+/// A lockout or unlock, kept for auditing.
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone, Serialize)]
+#[diesel(table_name = lockout_audit_log)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct LockoutAuditEntry {
+    pub id: i32,
+    pub scope: String,
+    pub subject: String,
+    pub event: String, // "locked" or "unlocked"
+    pub failed_attempts: i32,
+    pub locked_until: Option<NaiveDateTime>,
+    pub actor: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+/// Represents a new audit entry to be inserted into the database.
+#[derive(Insertable, Debug)]
+#[diesel(table_name = lockout_audit_log)]
+pub struct NewLockoutAuditEntry {
+    pub scope: String,
+    pub subject: String,
+    pub event: String,
+    pub failed_attempts: i32,
+    pub locked_until: Option<NaiveDateTime>,
+    pub actor: Option<String>,
+    pub created_at: NaiveDateTime,
+}
@@ -1,5 +1,73 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    auth_tokens (id) {
+        id -> Integer,
+        user_id -> Integer,
+        token_hash -> Text,
+        previous_token_hash -> Nullable<Text>,
+        device_name -> Nullable<Text>,
+        created_at -> Timestamp,
+        last_used_at -> Timestamp,
+        expires_at -> Timestamp,
+        revoked_at -> Nullable<Timestamp>,
+        device_id -> Nullable<Integer>,
+    }
+}
+
+diesel::table! {
+    body_measurements (id) {
+        id -> Integer,
+        user_id -> Integer,
+        kind -> Text,
+        value -> Double,
+        unit -> Text,
+        measured_at -> Timestamp,
+        created_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    break_fast_meals (id) {
+        id -> Integer,
+        event_id -> Integer,
+        description -> Text,
+        calories_min -> Nullable<Integer>,
+        calories_max -> Nullable<Integer>,
+        protein_g -> Nullable<Double>,
+        carbs_g -> Nullable<Double>,
+        fat_g -> Nullable<Double>,
+        eaten_at -> Timestamp,
+        created_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    device_pairing_codes (id) {
+        id -> Integer,
+        user_id -> Integer,
+        code_hash -> Text,
+        created_at -> Timestamp,
+        expires_at -> Timestamp,
+        redeemed_at -> Nullable<Timestamp>,
+        device_id -> Nullable<Integer>,
+    }
+}
+
+diesel::table! {
+    fast_journal_entries (id) {
+        id -> Integer,
+        event_id -> Integer,
+        entry_time -> Timestamp,
+        note -> Nullable<Text>,
+        mood -> Nullable<Integer>,
+        hunger -> Nullable<Integer>,
+        energy -> Nullable<Integer>,
+        symptoms -> Nullable<Text>,
+        created_at -> Nullable<Timestamp>,
+    }
+}
+
 diesel::table! {
     fasting_events (id) {
         id -> Integer,
@@ -8,6 +76,7 @@ diesel::table! {
         stop_time -> Nullable<Timestamp>,
         created_at -> Nullable<Timestamp>,
         goal_id -> Nullable<Integer>,
+        protocol_id -> Nullable<Integer>,
     }
 }
 
@@ -18,38 +87,200 @@ diesel::table! {
         goal_duration -> Integer,
         deadline -> Timestamp,
         created_at -> Nullable<Timestamp>,
+        status -> Text,
+        achieved_at -> Nullable<Timestamp>,
+        archived_at -> Nullable<Timestamp>,
+        recurring_goal_id -> Nullable<Integer>,
+        period_start -> Nullable<Date>,
+        enrollment_id -> Nullable<Integer>,
+    }
+}
+
+diesel::table! {
+    fasting_pauses (id) {
+        id -> Integer,
+        event_id -> Integer,
+        pause_time -> Timestamp,
+        resume_time -> Nullable<Timestamp>,
+        created_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    fasting_protocols (id) {
+        id -> Integer,
+        user_id -> Nullable<Integer>,
+        name -> Text,
+        fasting_hours -> Integer,
+        eating_hours -> Integer,
+        description -> Nullable<Text>,
+        created_at -> Nullable<Timestamp>,
     }
 }
 
 diesel::table! {
-    fasting_sessions (id) {
+    fasting_schedules (id) {
         id -> Integer,
         user_id -> Integer,
-        start_time -> Timestamp,
-        stop_time -> Nullable<Timestamp>,
+        weekday -> Integer,
+        start_time -> Time,
+        end_time -> Time,
+        goal_id -> Nullable<Integer>,
         created_at -> Nullable<Timestamp>,
     }
 }
 
+diesel::table! {
+    fluid_intakes (id) {
+        id -> Integer,
+        user_id -> Integer,
+        event_id -> Nullable<Integer>,
+        kind -> Text,
+        amount_ml -> Integer,
+        consumed_at -> Timestamp,
+        created_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    fasting_programs (id) {
+        id -> Integer,
+        user_id -> Nullable<Integer>,
+        slug -> Nullable<Text>,
+        name -> Text,
+        description -> Nullable<Text>,
+        created_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    lockout_audit_log (id) {
+        id -> Integer,
+        scope -> Text,
+        subject -> Text,
+        event -> Text,
+        failed_attempts -> Integer,
+        locked_until -> Nullable<Timestamp>,
+        actor -> Nullable<Text>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    login_failures (id) {
+        id -> Integer,
+        scope -> Text,
+        subject -> Text,
+        failed_attempts -> Integer,
+        lockouts -> Integer,
+        last_failed_at -> Timestamp,
+        locked_until -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    program_days (id) {
+        id -> Integer,
+        program_id -> Integer,
+        day_number -> Integer,
+        target_hours -> Integer,
+    }
+}
+
+diesel::table! {
+    program_enrollments (id) {
+        id -> Integer,
+        user_id -> Integer,
+        program_id -> Integer,
+        start_date -> Date,
+        status -> Text,
+        ended_at -> Nullable<Timestamp>,
+        created_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    recurring_goals (id) {
+        id -> Integer,
+        user_id -> Integer,
+        goal_duration -> Integer,
+        period -> Text,
+        weekdays -> Integer,
+        starts_on -> Date,
+        created_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    user_devices (id) {
+        id -> Integer,
+        user_id -> Integer,
+        name -> Text,
+        credential_hash -> Text,
+        paired_at -> Timestamp,
+        last_seen_at -> Nullable<Timestamp>,
+        revoked_at -> Nullable<Timestamp>,
+    }
+}
+
 diesel::table! {
     users (id) {
         id -> Integer,
         username -> Text,
         hashed_password -> Text,
-        device_id -> Nullable<Text>,
         created_at -> Nullable<Timestamp>,
         last_active -> Nullable<Timestamp>,
+        weight_unit -> Text,
+        length_unit -> Text,
+        daily_hydration_target_ml -> Nullable<Integer>,
+        timezone -> Text,
     }
 }
 
+diesel::joinable!(auth_tokens -> user_devices (device_id));
+diesel::joinable!(auth_tokens -> users (user_id));
+diesel::joinable!(body_measurements -> users (user_id));
+diesel::joinable!(break_fast_meals -> fasting_events (event_id));
+diesel::joinable!(device_pairing_codes -> user_devices (device_id));
+diesel::joinable!(device_pairing_codes -> users (user_id));
+diesel::joinable!(fast_journal_entries -> fasting_events (event_id));
 diesel::joinable!(fasting_events -> fasting_goals (goal_id));
+diesel::joinable!(fasting_events -> fasting_protocols (protocol_id));
 diesel::joinable!(fasting_events -> users (user_id));
+diesel::joinable!(fasting_goals -> program_enrollments (enrollment_id));
+diesel::joinable!(fasting_goals -> recurring_goals (recurring_goal_id));
 diesel::joinable!(fasting_goals -> users (user_id));
-diesel::joinable!(fasting_sessions -> users (user_id));
+diesel::joinable!(fasting_pauses -> fasting_events (event_id));
+diesel::joinable!(fasting_programs -> users (user_id));
+diesel::joinable!(fasting_protocols -> users (user_id));
+diesel::joinable!(fasting_schedules -> fasting_goals (goal_id));
+diesel::joinable!(fasting_schedules -> users (user_id));
+diesel::joinable!(fluid_intakes -> fasting_events (event_id));
+diesel::joinable!(fluid_intakes -> users (user_id));
+diesel::joinable!(program_days -> fasting_programs (program_id));
+diesel::joinable!(program_enrollments -> fasting_programs (program_id));
+diesel::joinable!(program_enrollments -> users (user_id));
+diesel::joinable!(recurring_goals -> users (user_id));
+diesel::joinable!(user_devices -> users (user_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    auth_tokens,
+    body_measurements,
+    break_fast_meals,
+    device_pairing_codes,
+    fast_journal_entries,
     fasting_events,
     fasting_goals,
-    fasting_sessions,
+    fasting_pauses,
+    fasting_programs,
+    fasting_protocols,
+    fasting_schedules,
+    fluid_intakes,
+    lockout_audit_log,
+    login_failures,
+    program_days,
+    program_enrollments,
+    recurring_goals,
+    user_devices,
     users,
 );
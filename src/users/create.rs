@@ -1,29 +1,33 @@
 use crate::errors::FastingAppError;
-use crate::models::NewUser;
+use crate::models::{NewUser, User};
 use crate::schema::users::dsl::users;
+use crate::users::password::PasswordPolicy;
 use bcrypt::{hash, DEFAULT_COST};
 use diesel::prelude::*;
 use diesel::SqliteConnection;
 
-/// ✅ Creates a new user
+/// ✅ Creates a new user and returns it.
+///
+/// - Returns `WeakPassword`, listing every broken rule, if the password does not satisfy `policy`.
 pub fn create_user(
     conn: &mut SqliteConnection, 
     username_input: &str, 
-    password_input: &str
-) -> Result<(), FastingAppError> {
+    password_input: &str,
+    policy: &PasswordPolicy,
+) -> Result<User, FastingAppError> {
+    policy.check(username_input, password_input)?;
+
     let hashed_password = hash(password_input, DEFAULT_COST)
         .map_err(FastingAppError::PasswordHashError)?;
 
     let new_user = NewUser {
         username: username_input.to_string(),
         hashed_password,
-        device_id: None,
     };
 
     diesel::insert_into(users)
         .values(&new_user)
-        .execute(conn)
-        .map_err(FastingAppError::DatabaseError)?;
-
-    Ok(())
+        .returning(User::as_returning())
+        .get_result(conn)
+        .map_err(FastingAppError::DatabaseError)
 }
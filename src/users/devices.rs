@@ -0,0 +1,306 @@
+use crate::auth::{generate_token, hash_token};
+use crate::clock::Clock;
+use crate::errors::FastingAppError;
+use crate::models::{NewDevicePairingCode, NewUserDevice, User, UserDevice};
+use crate::schema::device_pairing_codes::dsl as codes;
+use crate::schema::user_devices::dsl as devices;
+use crate::users::find::find_user_by_id;
+use chrono::{Duration, NaiveDateTime};
+use diesel::prelude::*;
+use diesel::SqliteConnection;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Pairing codes can be redeemed for this long after they are created (10 minutes).
+pub const PAIRING_CODE_TTL_MINUTES: i64 = 10;
+
+/// Characters used in pairing codes; look-alikes such as 0/O and 1/I/L are left out.
+const PAIRING_CODE_ALPHABET: &[u8] = b"ABCDEFGHJKMNPQRSTUVWXYZ23456789";
+
+/// Characters in a pairing code, shown as two groups of four.
+const PAIRING_CODE_LENGTH: usize = 8;
+
+/// A code to type into a new device, shown once on the device that created it.
+#[derive(Debug, Clone, Serialize)]
+pub struct PairingCode {
+    pub code: String,
+    pub expires_at: NaiveDateTime,
+}
+
+/// What a newly paired device keeps to sign in; the credential is only shown once.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceCredential {
+    pub device_id: i32,
+    pub credential: String,
+}
+
+/// A paired device as shown to its user.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceInfo {
+    pub id: i32,
+    pub name: String,
+    pub paired_at: NaiveDateTime,
+    pub last_seen_at: Option<NaiveDateTime>,
+}
+
+impl From<UserDevice> for DeviceInfo {
+    fn from(device: UserDevice) -> Self {
+        DeviceInfo {
+            id: device.id,
+            name: device.name,
+            paired_at: device.paired_at,
+            last_seen_at: device.last_seen_at,
+        }
+    }
+}
+
+/// ✅ Creates a single-use pairing code for a signed-in user.
+///
+/// - The code expires after `PAIRING_CODE_TTL_MINUTES`; only its hash is stored.
+pub fn create_pairing_code(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    clock: &dyn Clock,
+) -> Result<PairingCode, FastingAppError> {
+    let now = clock.now();
+    let mut rng = rand::rngs::OsRng;
+    let raw: String = (0..PAIRING_CODE_LENGTH)
+        .map(|_| PAIRING_CODE_ALPHABET[rng.gen_range(0..PAIRING_CODE_ALPHABET.len())] as char)
+        .collect();
+
+    let new_code = NewDevicePairingCode {
+        user_id: user_id_input,
+        code_hash: hash_token(&raw),
+        created_at: now,
+        expires_at: now + Duration::minutes(PAIRING_CODE_TTL_MINUTES),
+    };
+    diesel::insert_into(codes::device_pairing_codes)
+        .values(&new_code)
+        .execute(conn)
+        .map_err(FastingAppError::DatabaseError)?;
+
+    Ok(PairingCode {
+        code: format!("{}-{}", &raw[..4], &raw[4..]),
+        expires_at: new_code.expires_at,
+    })
+}
+
+/// ✅ Redeems a pairing code on a new device, returning the device's credential.
+///
+/// - Codes are case-insensitive and may be typed with or without the dash.
+/// - Returns `InvalidPairingCode` for unknown, expired or already used codes.
+pub fn redeem_pairing_code(
+    conn: &mut SqliteConnection,
+    code: &str,
+    device_name: &str,
+    clock: &dyn Clock,
+) -> Result<DeviceCredential, FastingAppError> {
+    let device_name = device_name.trim();
+    if device_name.is_empty() {
+        return Err(FastingAppError::InvalidRequest("Device name cannot be empty.".to_string()));
+    }
+    let normalized: String = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    let now = clock.now();
+
+    conn.immediate_transaction(|conn| {
+        let (code_id, owner_id) = codes::device_pairing_codes
+            .filter(codes::code_hash.eq(hash_token(&normalized)))
+            .filter(codes::redeemed_at.is_null())
+            .filter(codes::expires_at.gt(now))
+            .select((codes::id, codes::user_id))
+            .first::<(i32, i32)>(conn)
+            .optional()
+            .map_err(FastingAppError::DatabaseError)?
+            .ok_or(FastingAppError::InvalidPairingCode)?;
+
+        let credential = generate_token();
+        let device_id = diesel::insert_into(devices::user_devices)
+            .values(&NewUserDevice {
+                user_id: owner_id,
+                name: device_name.to_string(),
+                credential_hash: hash_token(&credential),
+                paired_at: now,
+            })
+            .returning(devices::id)
+            .get_result::<i32>(conn)
+            .map_err(FastingAppError::DatabaseError)?;
+
+        diesel::update(codes::device_pairing_codes.filter(codes::id.eq(code_id)))
+            .set((codes::redeemed_at.eq(Some(now)), codes::device_id.eq(Some(device_id))))
+            .execute(conn)
+            .map_err(FastingAppError::DatabaseError)?;
+
+        Ok(DeviceCredential { device_id, credential })
+    })
+}
+
+/// ✅ Finds the user a device credential belongs to and records that the device was seen.
+///
+/// - Returns `InvalidCredentials` for unknown or revoked devices.
+pub fn login_with_device(
+    conn: &mut SqliteConnection,
+    device_credential: &str,
+    clock: &dyn Clock,
+) -> Result<(User, DeviceInfo), FastingAppError> {
+    let device = diesel::update(
+        devices::user_devices
+            .filter(devices::credential_hash.eq(hash_token(device_credential.trim())))
+            .filter(devices::revoked_at.is_null()),
+    )
+    .set(devices::last_seen_at.eq(Some(clock.now())))
+    .returning(UserDevice::as_returning())
+    .get_result::<UserDevice>(conn)
+    .optional()
+    .map_err(FastingAppError::DatabaseError)?
+    .ok_or_else(|| FastingAppError::invalid_credentials("device"))?;
+
+    let user = find_user_by_id(conn, device.user_id, clock)?;
+    Ok((user, device.into()))
+}
+
+/// ✅ Lists the user's paired devices that have not been revoked, most recently seen first.
+pub fn list_devices(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+) -> Result<Vec<DeviceInfo>, FastingAppError> {
+    let paired = devices::user_devices
+        .filter(devices::user_id.eq(user_id_input))
+        .filter(devices::revoked_at.is_null())
+        .order((devices::last_seen_at.desc(), devices::paired_at.desc()))
+        .select(UserDevice::as_select())
+        .load::<UserDevice>(conn)
+        .map_err(FastingAppError::DatabaseError)?;
+
+    Ok(paired.into_iter().map(DeviceInfo::from).collect())
+}
+
+/// ✅ Revokes one of the user's devices and signs out every session it started.
+///
+/// - Returns `DeviceNotFound` for unknown, other users' or already revoked devices.
+pub fn revoke_device(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    device_id: i32,
+    clock: &dyn Clock,
+) -> Result<(), FastingAppError> {
+    use crate::schema::auth_tokens::dsl::{auth_tokens, device_id as session_device_id, revoked_at};
+
+    let now = clock.now();
+    conn.transaction(|conn| {
+        let revoked = diesel::update(
+            devices::user_devices
+                .filter(devices::id.eq(device_id))
+                .filter(devices::user_id.eq(user_id_input))
+                .filter(devices::revoked_at.is_null()),
+        )
+        .set(devices::revoked_at.eq(Some(now)))
+        .execute(conn)
+        .map_err(FastingAppError::DatabaseError)?;
+        if revoked == 0 {
+            return Err(FastingAppError::DeviceNotFound(device_id));
+        }
+
+        diesel::update(
+            auth_tokens
+                .filter(session_device_id.eq(device_id))
+                .filter(revoked_at.is_null()),
+        )
+        .set(revoked_at.eq(Some(now)))
+        .execute(conn)
+        .map(|_| ())
+        .map_err(FastingAppError::DatabaseError)
+    })
+}
+
+/// ✅ Revokes every paired device of the user and cancels their unredeemed pairing codes.
+///
+/// - Returns how many devices were revoked. Sessions are left alone; see `revoke_all_sessions`.
+pub fn revoke_all_devices(
+    conn: &mut SqliteConnection,
+    user_id_input: i32,
+    clock: &dyn Clock,
+) -> Result<usize, FastingAppError> {
+    let now = clock.now();
+    conn.transaction(|conn| {
+        diesel::update(
+            codes::device_pairing_codes
+                .filter(codes::user_id.eq(user_id_input))
+                .filter(codes::redeemed_at.is_null())
+                .filter(codes::expires_at.gt(now)),
+        )
+        .set(codes::expires_at.eq(now))
+        .execute(conn)
+        .map_err(FastingAppError::DatabaseError)?;
+
+        diesel::update(
+            devices::user_devices
+                .filter(devices::user_id.eq(user_id_input))
+                .filter(devices::revoked_at.is_null()),
+        )
+        .set(devices::revoked_at.eq(Some(now)))
+        .execute(conn)
+        .map_err(FastingAppError::DatabaseError)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{authenticate, sign_in_with_device, AuthConfig};
+    use crate::clock::FakeClock;
+    use crate::db::{insert_test_user, test_connection};
+    use chrono::NaiveDate;
+
+    fn clock() -> FakeClock {
+        FakeClock::new(NaiveDate::from_ymd_opt(2025, 3, 1).unwrap().and_hms_opt(12, 0, 0).unwrap())
+    }
+
+    #[test]
+    fn test_pairing_codes_are_single_use_and_expire() {
+        let mut conn = test_connection();
+        let alice = insert_test_user(&mut conn, "alice");
+        let clock = clock();
+
+        let pairing = create_pairing_code(&mut conn, alice, &clock).unwrap();
+        let typed = pairing.code.replace('-', "").to_lowercase();
+        let paired = redeem_pairing_code(&mut conn, &typed, "Phone", &clock).unwrap();
+        assert!(matches!(
+            redeem_pairing_code(&mut conn, &pairing.code, "Tablet", &clock),
+            Err(FastingAppError::InvalidPairingCode)
+        ));
+
+        let (user, device) = login_with_device(&mut conn, &paired.credential, &clock).unwrap();
+        assert_eq!((user.id, device.id, device.name.as_str()), (alice, paired.device_id, "Phone"));
+
+        let late = create_pairing_code(&mut conn, alice, &clock).unwrap();
+        clock.advance(Duration::minutes(PAIRING_CODE_TTL_MINUTES));
+        assert!(redeem_pairing_code(&mut conn, &late.code, "Tablet", &clock).is_err());
+    }
+
+    #[test]
+    fn test_revoking_a_device_ends_its_sessions() {
+        let mut conn = test_connection();
+        let alice = insert_test_user(&mut conn, "alice");
+        let bob = insert_test_user(&mut conn, "bob");
+        let clock = clock();
+        let config = AuthConfig::new("a test secret that is long enough!").unwrap();
+
+        let pairing = create_pairing_code(&mut conn, alice, &clock).unwrap();
+        let paired = redeem_pairing_code(&mut conn, &pairing.code, "Phone", &clock).unwrap();
+        let (_, tokens) = sign_in_with_device(&mut conn, &config, &paired.credential, &clock).unwrap();
+
+        assert!(matches!(
+            revoke_device(&mut conn, bob, paired.device_id, &clock),
+            Err(FastingAppError::DeviceNotFound(_))
+        ));
+        revoke_device(&mut conn, alice, paired.device_id, &clock).unwrap();
+
+        assert!(list_devices(&mut conn, alice).unwrap().is_empty());
+        assert!(login_with_device(&mut conn, &paired.credential, &clock).is_err());
+        assert!(authenticate(&mut conn, &config, &tokens.access_token, &clock).is_err());
+    }
+}
@@ -1,15 +1,17 @@
+use crate::clock::Clock;
 use crate::errors::FastingAppError;
 use crate::models::User;
-use chrono::Utc;
 use diesel::prelude::*;
 use crate::schema::users::dsl::*;
 
 /// ✅ Finds a user by their **ID**.
 /// - **Public**: Used in multiple modules (analytics, fasting, etc.).
 /// - Returns `FastingAppError::DatabaseError` if user is not found.
+/// - Records `clock.now()` as the user's last activity.
 pub fn find_user_by_id(
     conn: &mut SqliteConnection,
     user_id_input: i32,
+    clock: &dyn Clock,
 ) -> Result<User, FastingAppError> {
     let user = users
         .filter(id.eq(user_id_input))
@@ -19,9 +21,34 @@ pub fn find_user_by_id(
 
     // ✅ Update last active time
     diesel::update(users.filter(id.eq(user_id_input)))
-        .set(last_active.eq(&Utc::now().naive_utc()))
+        .set(last_active.eq(&clock.now()))
         .execute(conn)
         .map_err(FastingAppError::DatabaseError)?;
 
     Ok(user)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FakeClock;
+    use crate::db::{insert_test_user, test_connection};
+    use chrono::{NaiveDate, NaiveDateTime};
+
+    #[test]
+    fn test_lookup_records_last_active_from_the_clock() {
+        let mut conn = test_connection();
+        let user_id = insert_test_user(&mut conn, "returning");
+        let seen_at = NaiveDate::from_ymd_opt(2025, 4, 28).unwrap().and_hms_opt(7, 45, 0).unwrap();
+
+        let user = find_user_by_id(&mut conn, user_id, &FakeClock::new(seen_at)).unwrap();
+        assert_eq!(user.last_active, None);
+
+        let stored = users
+            .filter(id.eq(user_id))
+            .select(last_active)
+            .first::<Option<NaiveDateTime>>(&mut conn)
+            .unwrap();
+        assert_eq!(stored, Some(seen_at));
+    }
+}
@@ -0,0 +1,317 @@
+use crate::clock::Clock;
+use crate::errors::FastingAppError;
+use crate::models::{LockoutAuditEntry, LoginFailure, NewLockoutAuditEntry};
+use crate::schema::lockout_audit_log::dsl as audit;
+use crate::schema::login_failures::dsl as failures;
+use chrono::{Duration, NaiveDateTime};
+use diesel::prelude::*;
+use diesel::SqliteConnection;
+use dotenv::dotenv;
+use std::env;
+
+/// Failed sign-ins in a row before a username or client is locked.
+pub const DEFAULT_LOCKOUT_THRESHOLD: i32 = 5;
+
+/// How long the first lockout lasts (30 seconds); each later one lasts twice as long.
+pub const DEFAULT_LOCKOUT_SECONDS: i64 = 30;
+
+/// The longest a single lockout can last (1 hour).
+pub const DEFAULT_MAX_LOCKOUT_SECONDS: i64 = 60 * 60;
+
+/// What failed sign-ins are counted against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockoutScope {
+    Username,
+    /// The client signing in, by its remote IP address; never a name the client chose.
+    Device,
+}
+
+impl LockoutScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LockoutScope::Username => "username",
+            LockoutScope::Device => "device",
+        }
+    }
+}
+
+/// When repeated failed sign-ins lock a username or client, and for how long.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LockoutPolicy {
+    /// Failed sign-ins in a row that trigger a lockout.
+    pub threshold: i32,
+    /// Length of the first lockout.
+    pub base_lockout: Duration,
+    /// Upper bound on any one lockout.
+    pub max_lockout: Duration,
+}
+
+impl Default for LockoutPolicy {
+    fn default() -> Self {
+        LockoutPolicy {
+            threshold: DEFAULT_LOCKOUT_THRESHOLD,
+            base_lockout: Duration::seconds(DEFAULT_LOCKOUT_SECONDS),
+            max_lockout: Duration::seconds(DEFAULT_MAX_LOCKOUT_SECONDS),
+        }
+    }
+}
+
+impl LockoutPolicy {
+    /// ✅ Reads `LOGIN_LOCKOUT_THRESHOLD`, `LOGIN_LOCKOUT_SECONDS` and `LOGIN_LOCKOUT_MAX_SECONDS`,
+    /// using the defaults for any that are unset.
+    pub fn from_env() -> Result<Self, FastingAppError> {
+        dotenv().ok();
+        let defaults = LockoutPolicy::default();
+        let policy = LockoutPolicy {
+            threshold: env_number("LOGIN_LOCKOUT_THRESHOLD")?.map_or(defaults.threshold, |n| n as i32),
+            base_lockout: env_number("LOGIN_LOCKOUT_SECONDS")?.map_or(defaults.base_lockout, Duration::seconds),
+            max_lockout: env_number("LOGIN_LOCKOUT_MAX_SECONDS")?.map_or(defaults.max_lockout, Duration::seconds),
+        };
+        if policy.threshold < 1 || policy.base_lockout <= Duration::zero() || policy.max_lockout < policy.base_lockout {
+            return Err(FastingAppError::InvalidRequest(
+                "Login lockout settings must have a threshold of at least 1 and a maximum no shorter than the first lockout"
+                    .to_string(),
+            ));
+        }
+        Ok(policy)
+    }
+
+    /// ✅ How long the lockout after `previous_lockouts` earlier ones lasts.
+    pub fn lockout_duration(&self, previous_lockouts: i32) -> Duration {
+        let factor = 1i32.checked_shl(previous_lockouts.clamp(0, 30) as u32).unwrap_or(i32::MAX);
+        self.base_lockout
+            .checked_mul(factor)
+            .map_or(self.max_lockout, |duration| duration.min(self.max_lockout))
+    }
+}
+
+/// Reads an optional non-negative whole number from the environment.
+fn env_number(name: &str) -> Result<Option<i64>, FastingAppError> {
+    match env::var(name) {
+        Ok(value) => value
+            .trim()
+            .parse::<i64>()
+            .ok()
+            .filter(|n| (0..=i32::MAX as i64).contains(n))
+            .map(Some)
+            .ok_or_else(|| FastingAppError::InvalidRequest(format!("{} must be a whole number of seconds or attempts", name))),
+        Err(_) => Ok(None),
+    }
+}
+
+/// ✅ Returns `AccountLocked` if any of `subjects` is locked at `clock.now()`.
+pub fn check_login_allowed(
+    conn: &mut SqliteConnection,
+    subjects: &[(LockoutScope, &str)],
+    clock: &dyn Clock,
+) -> Result<(), FastingAppError> {
+    let now = clock.now();
+    let mut retry_after: Option<NaiveDateTime> = None;
+    for (scope, subject) in subjects {
+        let locked_until = failures::login_failures
+            .filter(failures::scope.eq(scope.as_str()))
+            .filter(failures::subject.eq(*subject))
+            .filter(failures::locked_until.gt(now))
+            .select(failures::locked_until)
+            .first::<Option<NaiveDateTime>>(conn)
+            .optional()
+            .map_err(FastingAppError::DatabaseError)?
+            .flatten();
+        retry_after = retry_after.max(locked_until);
+    }
+
+    match retry_after {
+        Some(retry_after) => Err(FastingAppError::AccountLocked { retry_after }),
+        None => Ok(()),
+    }
+}
+
+/// ✅ Counts a failed sign-in against each of `subjects`.
+///
+/// - A subject reaching `policy.threshold` failures is locked, twice as long as its previous lockout,
+///   and the lockout is written to the audit log.
+/// - Returns the latest time any subject is now locked until, if one was locked.
+pub fn record_failed_login(
+    conn: &mut SqliteConnection,
+    policy: &LockoutPolicy,
+    subjects: &[(LockoutScope, &str)],
+    clock: &dyn Clock,
+) -> Result<Option<NaiveDateTime>, FastingAppError> {
+    let now = clock.now();
+    conn.immediate_transaction(|conn| {
+        let mut retry_after: Option<NaiveDateTime> = None;
+        for (scope, subject) in subjects {
+            let failure = diesel::insert_into(failures::login_failures)
+                .values((
+                    failures::scope.eq(scope.as_str()),
+                    failures::subject.eq(*subject),
+                    failures::failed_attempts.eq(1),
+                    failures::last_failed_at.eq(now),
+                ))
+                .on_conflict((failures::scope, failures::subject))
+                .do_update()
+                .set((
+                    failures::failed_attempts.eq(failures::failed_attempts + 1),
+                    failures::last_failed_at.eq(now),
+                ))
+                .returning(LoginFailure::as_returning())
+                .get_result::<LoginFailure>(conn)
+                .map_err(FastingAppError::DatabaseError)?;
+            if failure.failed_attempts < policy.threshold {
+                continue;
+            }
+
+            let locked_until = now + policy.lockout_duration(failure.lockouts);
+            diesel::update(failures::login_failures.filter(failures::id.eq(failure.id)))
+                .set((
+                    failures::failed_attempts.eq(0),
+                    failures::lockouts.eq(failures::lockouts + 1),
+                    failures::locked_until.eq(Some(locked_until)),
+                ))
+                .execute(conn)
+                .map_err(FastingAppError::DatabaseError)?;
+            write_audit_entry(conn, *scope, subject, "locked", failure.failed_attempts, Some(locked_until), None, now)?;
+            log::warn!("Locked {} '{}' until {} after {} failed sign-ins", scope.as_str(), subject, locked_until, failure.failed_attempts);
+            retry_after = retry_after.max(Some(locked_until));
+        }
+        Ok(retry_after)
+    })
+}
+
+/// ✅ Forgets failed sign-ins for each of `subjects`, e.g. after a successful sign-in.
+pub fn clear_failed_logins(
+    conn: &mut SqliteConnection,
+    subjects: &[(LockoutScope, &str)],
+) -> Result<(), FastingAppError> {
+    for (scope, subject) in subjects {
+        diesel::delete(
+            failures::login_failures
+                .filter(failures::scope.eq(scope.as_str()))
+                .filter(failures::subject.eq(*subject)),
+        )
+        .execute(conn)
+        .map_err(FastingAppError::DatabaseError)?;
+    }
+    Ok(())
+}
+
+/// ✅ Lifts any lockout on a username or client and resets its failure count and backoff.
+///
+/// - Writes an audit entry naming `actor`.
+/// - Returns `false` if there was nothing to unlock.
+pub fn unlock_login(
+    conn: &mut SqliteConnection,
+    scope: LockoutScope,
+    subject: &str,
+    actor: &str,
+    clock: &dyn Clock,
+) -> Result<bool, FastingAppError> {
+    let now = clock.now();
+    conn.immediate_transaction(|conn| {
+        let removed = diesel::delete(
+            failures::login_failures
+                .filter(failures::scope.eq(scope.as_str()))
+                .filter(failures::subject.eq(subject)),
+        )
+        .returning(LoginFailure::as_returning())
+        .get_result::<LoginFailure>(conn)
+        .optional()
+        .map_err(FastingAppError::DatabaseError)?;
+
+        match removed {
+            Some(failure) => {
+                write_audit_entry(
+                    conn,
+                    scope,
+                    subject,
+                    "unlocked",
+                    failure.failed_attempts,
+                    failure.locked_until,
+                    Some(actor),
+                    now,
+                )?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    })
+}
+
+/// ✅ The most recent lockouts and unlocks, newest first.
+pub fn list_lockout_audit(conn: &mut SqliteConnection, limit: i64) -> Result<Vec<LockoutAuditEntry>, FastingAppError> {
+    audit::lockout_audit_log
+        .order((audit::created_at.desc(), audit::id.desc()))
+        .limit(limit)
+        .select(LockoutAuditEntry::as_select())
+        .load(conn)
+        .map_err(FastingAppError::DatabaseError)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn write_audit_entry(
+    conn: &mut SqliteConnection,
+    scope: LockoutScope,
+    subject: &str,
+    event: &str,
+    failed_attempts: i32,
+    locked_until: Option<NaiveDateTime>,
+    actor: Option<&str>,
+    now: NaiveDateTime,
+) -> Result<(), FastingAppError> {
+    diesel::insert_into(audit::lockout_audit_log)
+        .values(&NewLockoutAuditEntry {
+            scope: scope.as_str().to_string(),
+            subject: subject.to_string(),
+            event: event.to_string(),
+            failed_attempts,
+            locked_until,
+            actor: actor.map(str::to_string),
+            created_at: now,
+        })
+        .execute(conn)
+        .map(|_| ())
+        .map_err(FastingAppError::DatabaseError)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FakeClock;
+    use crate::db::test_connection;
+    use chrono::NaiveDate;
+
+    const ALICE: &[(LockoutScope, &str)] = &[(LockoutScope::Username, "alice")];
+
+    #[test]
+    fn test_lockouts_back_off_exponentially() {
+        let mut conn = test_connection();
+        let clock = FakeClock::new(NaiveDate::from_ymd_opt(2025, 3, 1).unwrap().and_hms_opt(12, 0, 0).unwrap());
+        let policy = LockoutPolicy {
+            threshold: 3,
+            base_lockout: Duration::seconds(30),
+            max_lockout: Duration::seconds(90),
+        };
+
+        let mut lock_lengths = Vec::new();
+        for _ in 0..3 {
+            let start = clock.now();
+            assert_eq!(record_failed_login(&mut conn, &policy, ALICE, &clock).unwrap(), None);
+            assert_eq!(record_failed_login(&mut conn, &policy, ALICE, &clock).unwrap(), None);
+            let locked_until = record_failed_login(&mut conn, &policy, ALICE, &clock).unwrap().unwrap();
+            assert!(matches!(
+                check_login_allowed(&mut conn, ALICE, &clock),
+                Err(FastingAppError::AccountLocked { retry_after }) if retry_after == locked_until
+            ));
+            lock_lengths.push((locked_until - start).num_seconds());
+            clock.set(locked_until);
+            assert!(check_login_allowed(&mut conn, ALICE, &clock).is_ok());
+        }
+        assert_eq!(lock_lengths, vec![30, 60, 90]);
+        assert_eq!(list_lockout_audit(&mut conn, 10).unwrap().len(), 3);
+
+        assert!(unlock_login(&mut conn, LockoutScope::Username, "alice", "admin", &clock).unwrap());
+        assert!(!unlock_login(&mut conn, LockoutScope::Username, "alice", "admin", &clock).unwrap());
+        let latest = &list_lockout_audit(&mut conn, 1).unwrap()[0];
+        assert_eq!((latest.event.as_str(), latest.actor.as_deref()), ("unlocked", Some("admin")));
+    }
+}
@@ -0,0 +1,230 @@
+use crate::auth::revoke_all_sessions;
+use crate::clock::Clock;
+use crate::errors::FastingAppError;
+use crate::models::User;
+use crate::schema::users::dsl::{hashed_password, id, users};
+use crate::users::devices::revoke_all_devices;
+use crate::users::lockout::{
+    check_login_allowed, clear_failed_logins, record_failed_login, LockoutPolicy, LockoutScope,
+};
+use bcrypt::{hash, verify, DEFAULT_COST};
+use diesel::prelude::*;
+use diesel::SqliteConnection;
+use dotenv::dotenv;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fmt;
+
+/// Shortest password accepted by the default policy, in characters.
+pub const DEFAULT_MIN_PASSWORD_LENGTH: usize = 8;
+
+/// Passwords rejected as too common, one per line; lines starting with `#` are comments.
+const COMMON_PASSWORDS: &str = include_str!("../../data/common_passwords.txt");
+
+/// A password policy rule that a password breaks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum PasswordViolation {
+    TooShort { min_length: usize },
+    Common,
+    ContainsUsername,
+}
+
+impl fmt::Display for PasswordViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PasswordViolation::TooShort { min_length } => write!(f, "must be at least {} characters long", min_length),
+            PasswordViolation::Common => write!(f, "is too common"),
+            PasswordViolation::ContainsUsername => write!(f, "must not contain the username"),
+        }
+    }
+}
+
+/// ✅ Joins violations into one sentence fragment, e.g. "must be at least 8 characters long; is too common".
+pub fn describe_violations(violations: &[PasswordViolation]) -> String {
+    violations.iter().map(ToString::to_string).collect::<Vec<_>>().join("; ")
+}
+
+/// The rules new passwords must follow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    /// Reject passwords on the bundled common-password list.
+    pub reject_common: bool,
+    /// Reject passwords that contain the username, ignoring case.
+    pub reject_username: bool,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        PasswordPolicy {
+            min_length: DEFAULT_MIN_PASSWORD_LENGTH,
+            reject_common: true,
+            reject_username: true,
+        }
+    }
+}
+
+impl PasswordPolicy {
+    /// ✅ Reads `PASSWORD_MIN_LENGTH`, `PASSWORD_REJECT_COMMON` and `PASSWORD_REJECT_USERNAME`,
+    /// using the defaults for any that are unset.
+    pub fn from_env() -> Result<Self, FastingAppError> {
+        dotenv().ok();
+        let defaults = PasswordPolicy::default();
+        let min_length = match env::var("PASSWORD_MIN_LENGTH") {
+            Ok(value) => value.trim().parse::<usize>().ok().filter(|n| *n >= 1).ok_or_else(|| {
+                FastingAppError::InvalidRequest("PASSWORD_MIN_LENGTH must be a whole number of at least 1".to_string())
+            })?,
+            Err(_) => defaults.min_length,
+        };
+        Ok(PasswordPolicy {
+            min_length,
+            reject_common: env_flag("PASSWORD_REJECT_COMMON")?.unwrap_or(defaults.reject_common),
+            reject_username: env_flag("PASSWORD_REJECT_USERNAME")?.unwrap_or(defaults.reject_username),
+        })
+    }
+
+    /// ✅ Every rule `password` breaks for an account called `username`.
+    pub fn violations(&self, username: &str, password: &str) -> Vec<PasswordViolation> {
+        let mut violations = Vec::new();
+        if password.chars().count() < self.min_length {
+            violations.push(PasswordViolation::TooShort { min_length: self.min_length });
+        }
+        if self.reject_common && is_common_password(password) {
+            violations.push(PasswordViolation::Common);
+        }
+        let username = username.trim().to_lowercase();
+        if self.reject_username && !username.is_empty() && password.to_lowercase().contains(&username) {
+            violations.push(PasswordViolation::ContainsUsername);
+        }
+        violations
+    }
+
+    /// ✅ Returns `WeakPassword` listing every broken rule, if any.
+    pub fn check(&self, username: &str, password: &str) -> Result<(), FastingAppError> {
+        let violations = self.violations(username, password);
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(FastingAppError::WeakPassword(violations))
+        }
+    }
+}
+
+/// Reads an optional true/false setting from the environment.
+fn env_flag(name: &str) -> Result<Option<bool>, FastingAppError> {
+    match env::var(name) {
+        Ok(value) => match value.trim().to_lowercase().as_str() {
+            "1" | "true" | "yes" => Ok(Some(true)),
+            "0" | "false" | "no" => Ok(Some(false)),
+            _ => Err(FastingAppError::InvalidRequest(format!("{} must be true or false", name))),
+        },
+        Err(_) => Ok(None),
+    }
+}
+
+fn is_common_password(password: &str) -> bool {
+    let password = password.trim().to_lowercase();
+    COMMON_PASSWORDS
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .any(|common| common == password)
+}
+
+/// ✅ Changes a user's password after checking their current one.
+///
+/// - A wrong current password counts as a failed sign-in for the username under `lockout`,
+///   so a stolen session cannot be used to guess the password.
+/// - The new password must satisfy `policy`; returns `WeakPassword` otherwise.
+/// - Signs out every session and revokes every paired device, so other devices must sign in again.
+pub fn change_password(
+    conn: &mut SqliteConnection,
+    policy: &PasswordPolicy,
+    lockout: &LockoutPolicy,
+    user_id_input: i32,
+    current_password: &str,
+    new_password: &str,
+    clock: &dyn Clock,
+) -> Result<(), FastingAppError> {
+    let user = users
+        .filter(id.eq(user_id_input))
+        .select(User::as_select())
+        .first::<User>(conn)
+        .map_err(FastingAppError::DatabaseError)?;
+    let subjects = [(LockoutScope::Username, user.username.as_str())];
+    check_login_allowed(conn, &subjects, clock)?;
+    if !verify(current_password, &user.hashed_password).map_err(FastingAppError::PasswordHashError)? {
+        return Err(match record_failed_login(conn, lockout, &subjects, clock)? {
+            Some(retry_after) => FastingAppError::AccountLocked { retry_after },
+            None => FastingAppError::invalid_credentials(&user.username),
+        });
+    }
+    clear_failed_logins(conn, &subjects)?;
+    policy.check(&user.username, new_password)?;
+
+    let new_hash = hash(new_password, DEFAULT_COST).map_err(FastingAppError::PasswordHashError)?;
+    conn.immediate_transaction(|conn| {
+        diesel::update(users.filter(id.eq(user_id_input)))
+            .set(hashed_password.eq(new_hash))
+            .execute(conn)
+            .map_err(FastingAppError::DatabaseError)?;
+        revoke_all_sessions(conn, user_id_input, clock)?;
+        revoke_all_devices(conn, user_id_input, clock)?;
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_lists_every_broken_rule() {
+        let policy = PasswordPolicy::default();
+        assert_eq!(
+            policy.violations("alice", "Qwerty"),
+            vec![PasswordViolation::TooShort { min_length: 8 }, PasswordViolation::Common]
+        );
+        assert_eq!(policy.violations("alice", "xxALICE-2025xx"), vec![PasswordViolation::ContainsUsername]);
+        assert!(policy.check("alice", "correct horse battery").is_ok());
+
+        let lenient = PasswordPolicy { min_length: 4, reject_common: false, reject_username: false };
+        assert!(lenient.check("alice", "alice").is_ok());
+    }
+
+    #[test]
+    fn test_change_password_checks_the_current_one_and_signs_everything_out() {
+        use crate::auth::{list_active_sessions, sign_in_local};
+        use crate::clock::FakeClock;
+        use crate::db::{insert_test_user, test_connection};
+        use crate::users::devices::{create_pairing_code, list_devices, redeem_pairing_code};
+        use chrono::NaiveDate;
+
+        let mut conn = test_connection();
+        let alice = insert_test_user(&mut conn, "alice");
+        diesel::update(users.filter(id.eq(alice)))
+            .set(hashed_password.eq(hash("old passphrase", 4).unwrap()))
+            .execute(&mut conn)
+            .unwrap();
+        let clock = FakeClock::new(NaiveDate::from_ymd_opt(2025, 3, 1).unwrap().and_hms_opt(12, 0, 0).unwrap());
+        let policy = PasswordPolicy::default();
+        let lockout = LockoutPolicy { threshold: 2, ..LockoutPolicy::default() };
+
+        sign_in_local(&mut conn, &lockout, "alice", "old passphrase", None, &clock).unwrap();
+        let pairing = create_pairing_code(&mut conn, alice, &clock).unwrap();
+        redeem_pairing_code(&mut conn, &pairing.code, "Phone", &clock).unwrap();
+
+        let mut change = |current: &str, clock: &FakeClock| {
+            change_password(&mut conn, &policy, &lockout, alice, current, "a much better passphrase", clock)
+        };
+        assert!(matches!(change("wrong", &clock), Err(FastingAppError::InvalidCredentials(_))));
+        assert!(matches!(change("wrong", &clock), Err(FastingAppError::AccountLocked { .. })));
+        assert!(matches!(change("old passphrase", &clock), Err(FastingAppError::AccountLocked { .. })));
+
+        clock.advance(lockout.base_lockout);
+        change("old passphrase", &clock).unwrap();
+        assert!(list_active_sessions(&mut conn, alice, &clock).unwrap().is_empty());
+        assert!(list_devices(&mut conn, alice).unwrap().is_empty());
+    }
+}